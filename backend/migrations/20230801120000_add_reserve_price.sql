@@ -0,0 +1,6 @@
+-- Add migration script here
+ALTER TABLE auction_item
+ADD COLUMN reserve_price INTEGER DEFAULT NULL;  -- if NULL, there is no reserve; this is never shown to members
+
+ALTER TABLE auction_item
+ADD COLUMN reserve_not_met INTEGER NOT NULL DEFAULT 0;  -- 1 if the item was auctioned, but the bidding ended below the reserve
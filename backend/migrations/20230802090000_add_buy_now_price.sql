@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE auction_item
+ADD COLUMN buy_now_price INTEGER DEFAULT NULL;  -- if NULL, the item cannot be bought instantly
@@ -0,0 +1,35 @@
+-- Add migration script here
+ALTER TABLE auction_item
+ADD COLUMN quantity INTEGER NOT NULL DEFAULT 1;  -- number of identical units; each unit is sold to a different member
+
+-- Items with multiple units have multiple sales, so a sale can no longer be identified by its item.
+-- SQLite cannot drop the UNIQUE constraint, so the sale tables are rebuilt with their own IDs.
+CREATE TABLE auction_item_sale_new (
+    id INTEGER PRIMARY KEY NOT NULL,
+    item_id INTEGER NOT NULL,
+    buyer_id INTEGER NOT NULL,
+    sale_price INTEGER NOT NULL,
+    FOREIGN KEY (item_id) REFERENCES auction_item(id) ON DELETE CASCADE,
+    FOREIGN KEY (buyer_id) REFERENCES auction_user(id) ON DELETE CASCADE
+);
+
+INSERT INTO auction_item_sale_new (item_id, buyer_id, sale_price)
+SELECT item_id, buyer_id, sale_price FROM auction_item_sale;
+
+CREATE TABLE sale_contribution_new (
+    sale_id INTEGER NOT NULL REFERENCES auction_item_sale_new(id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL REFERENCES auction_user(id) ON DELETE CASCADE,
+    amount INTEGER NOT NULL
+);
+
+INSERT INTO sale_contribution_new (sale_id, user_id, amount)
+SELECT auction_item_sale_new.id, sale_contribution.user_id, sale_contribution.amount
+FROM sale_contribution
+INNER JOIN auction_item_sale_new ON auction_item_sale_new.item_id = sale_contribution.sale_id;
+
+DROP TABLE sale_contribution;
+DROP TABLE auction_item_sale;
+ALTER TABLE auction_item_sale_new RENAME TO auction_item_sale;
+ALTER TABLE sale_contribution_new RENAME TO sale_contribution;
+
+CREATE INDEX auction_item_sale_item_id ON auction_item_sale(item_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+ALTER TABLE auction_item
+ADD COLUMN lot_id INTEGER DEFAULT NULL REFERENCES auction_item(id) ON DELETE SET NULL;
+-- if not NULL, this item is part of a lot: the lot is itself an item, which is auctioned instead of this one
+
+CREATE TABLE IF NOT EXISTS lot_sale_share (
+    sale_id INTEGER NOT NULL REFERENCES auction_item_sale(id) ON DELETE CASCADE,
+    item_id INTEGER NOT NULL REFERENCES auction_item(id) ON DELETE CASCADE,
+    amount INTEGER NOT NULL
+    -- the part of the lot's sale price that is attributed to this item in the reports
+);
@@ -0,0 +1,10 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS candle_auction_log (
+    id INTEGER PRIMARY KEY NOT NULL,
+    item_id INTEGER NOT NULL REFERENCES auction_item(id) ON DELETE CASCADE,
+    min_duration_ms INTEGER NOT NULL,
+    max_duration_ms INTEGER NOT NULL,
+    started_at_ms INTEGER NOT NULL,  -- milliseconds since the Unix epoch
+    ends_at_ms INTEGER NOT NULL
+    -- the secret end time that was chosen at random, kept so that it can be audited after the auction
+);
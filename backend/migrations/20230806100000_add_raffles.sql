@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS raffle_draw (
+    sale_id INTEGER PRIMARY KEY NOT NULL REFERENCES auction_item_sale(id) ON DELETE CASCADE,
+    seed INTEGER NOT NULL,  -- the random seed of the draw (a u64 stored as its bits)
+    ticket_price INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS raffle_entry (
+    sale_id INTEGER NOT NULL REFERENCES raffle_draw(sale_id) ON DELETE CASCADE,
+    user_id INTEGER NOT NULL,  -- not a reference, so that the draw can still be checked if the user is deleted
+    tickets INTEGER NOT NULL
+);
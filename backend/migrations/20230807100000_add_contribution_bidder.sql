@@ -0,0 +1,9 @@
+-- Add migration script here
+ALTER TABLE sale_contribution
+ADD COLUMN bidder_id INTEGER DEFAULT NULL;
+-- the member whose bid this contribution pays for (the member themselves, or someone they sponsor).
+-- In an all-pay auction or a raffle, this may be a member who did not win the item.
+
+-- Before this, only the buyer's bid was paid for.
+UPDATE sale_contribution
+SET bidder_id = (SELECT buyer_id FROM auction_item_sale WHERE auction_item_sale.id = sale_contribution.sale_id);
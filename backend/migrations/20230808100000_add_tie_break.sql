@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE auction_item_sale
+ADD COLUMN tie_break INTEGER DEFAULT NULL;
+-- if this unit was won in a tie in a Japanese auction, the policy that broke it (see JapaneseTieBreak::to_db_val).
+
+ALTER TABLE auction_item_sale
+ADD COLUMN tie_break_seed INTEGER DEFAULT NULL;
+-- if the tie was broken by a random draw, its seed (a u64 stored as its bits).
@@ -0,0 +1,8 @@
+-- Add migration script here
+ALTER TABLE auction_item_sale
+ADD COLUMN contribution_split INTEGER DEFAULT NULL;
+-- how the price was split between the buyer and their sponsors (see ContributionSplit::to_db_val).
+
+ALTER TABLE auction_item_sale
+ADD COLUMN contribution_split_seed INTEGER DEFAULT NULL;
+-- if the split was random, its seed (a u64 stored as its bits).
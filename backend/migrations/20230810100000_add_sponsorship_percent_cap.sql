@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE sponsorship
+ADD COLUMN max_percent INTEGER DEFAULT NULL;
+-- if not NULL, the donor covers at most this percentage of the price of each purchase.
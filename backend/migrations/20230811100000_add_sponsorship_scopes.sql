@@ -0,0 +1,12 @@
+-- Add migration script here
+ALTER TABLE auction_item
+ADD COLUMN category TEXT DEFAULT NULL;
+-- what kind of item this is; sponsorships can be limited to categories.
+
+CREATE TABLE IF NOT EXISTS sponsorship_scope (
+    sponsorship_id INTEGER NOT NULL REFERENCES sponsorship(id) ON DELETE CASCADE,
+    item_id INTEGER DEFAULT NULL REFERENCES auction_item(id) ON DELETE CASCADE,
+    category TEXT DEFAULT NULL
+    -- exactly one of item_id and category is set;
+    -- if a sponsorship has any scopes, it can only be used for the items they include
+);
@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS sponsorship_log (
+    id INTEGER PRIMARY KEY NOT NULL,
+    sponsorship_id INTEGER NOT NULL REFERENCES sponsorship(id) ON DELETE CASCADE,
+    kind INTEGER NOT NULL,
+    -- 0 for requested, 1 for created, 2 for adjusted, 3 for used on an item, 4 for retracted, 5 for rejected
+    amount INTEGER DEFAULT NULL,
+    -- the amount requested, the balance after creating or adjusting, or the amount used on the item
+    item_name TEXT DEFAULT NULL,
+    -- the name of the item that the sponsorship was used for, kept even if the item is deleted later
+    happened_at_ms INTEGER NOT NULL  -- milliseconds since the Unix epoch
+);
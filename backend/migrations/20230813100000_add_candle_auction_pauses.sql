@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE candle_auction_log
+ADD COLUMN paused_ms INTEGER NOT NULL DEFAULT 0;
+-- how long the auction was paused in total. ends_at_ms is moved later by the same amount,
+-- so the end time that was chosen at random is ends_at_ms - paused_ms - started_at_ms after the start.
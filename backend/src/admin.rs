@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use axum::extract::ws::{close_code, Message, WebSocket};
 
-use communication::{decode, encode, AdminClientMessage, AdminServerMessage, WithTimestamp};
+use communication::{
+    auction::state::SealedBidPricing, decode, encode, AdminClientMessage, AdminServerMessage,
+    WithTimestamp,
+};
 #[allow(unused_imports)]
 use tracing::{debug, error, info, trace, warn};
 
@@ -49,6 +52,8 @@ pub async fn handle_socket(
     send!(socket, AdminServerMessage::AdminState(state));
     let state = sync_handle.sponsorship_state.borrow().clone();
     send!(socket, AdminServerMessage::SponsorshipState(state.into()));
+    let state = sync_handle.silent_auction_state.borrow().clone();
+    send!(socket, AdminServerMessage::SilentAuctionState(state.into()));
 
     loop {
         tokio::select! {
@@ -72,8 +77,16 @@ pub async fn handle_socket(
                                 match msg {
                                     AdminClientMessage::StartAuction => sync_handle.send_event(AuctionEvent::StartAuction).await,
                                     AdminClientMessage::PrepareAuctioning(item_id) => sync_handle.send_event(AuctionEvent::PrepareAuctioning(item_id)).await,
-                                    AdminClientMessage::RunEnglishAuction(item_id) => sync_handle.send_event(AuctionEvent::RunEnglishAuction(item_id)).await,
-                                    AdminClientMessage::RunJapaneseAuction(item_id) => sync_handle.send_event(AuctionEvent::RunJapaneseAuction(item_id)).await,
+                                    AdminClientMessage::RunEnglishAuction { item_id, soft_close_policy, rerun } => sync_handle.send_event(AuctionEvent::RunEnglishAuction { item_id, soft_close_policy, initial_price: None, rerun }).await,
+                                    AdminClientMessage::RunJapaneseAuction { item_id, settings, rerun } => sync_handle.send_event(AuctionEvent::RunJapaneseAuction { item_id, settings, initial_price: None, rerun }).await,
+                                    AdminClientMessage::RunDutchAuction { item_id, starting_price } => sync_handle.send_event(AuctionEvent::RunDutchAuction { item_id, starting_price }).await,
+                                    AdminClientMessage::RunSealedBidAuction(item_id) => sync_handle.send_event(AuctionEvent::RunSealedBidAuction { item_id, pricing: SealedBidPricing::FirstPrice }).await,
+                                    AdminClientMessage::RunVickreyAuction(item_id) => sync_handle.send_event(AuctionEvent::RunSealedBidAuction { item_id, pricing: SealedBidPricing::SecondPrice }).await,
+                                    AdminClientMessage::RunAllPayAuction(item_id) => sync_handle.send_event(AuctionEvent::RunAllPayAuction(item_id)).await,
+                                    AdminClientMessage::RunRaffle { item_id, ticket_price, entry_window_secs } => sync_handle.send_event(AuctionEvent::RunRaffle { item_id, ticket_price, entry_window: Duration::from_secs(entry_window_secs) }).await,
+                                    AdminClientMessage::PauseAuction => sync_handle.send_event(AuctionEvent::PauseAuction).await,
+                                    AdminClientMessage::ResumeAuction => sync_handle.send_event(AuctionEvent::ResumeAuction).await,
+                                    AdminClientMessage::SetContributionSplit(split) => sync_handle.send_event(AuctionEvent::SetContributionSplit(split)).await,
                                     AdminClientMessage::FinishAuction => sync_handle.send_event(AuctionEvent::FinishAuction).await,
                                     AdminClientMessage::StartAuctionAnew => sync_handle.send_event(AuctionEvent::StartAuctionAnew).await,
                                     AdminClientMessage::KickFromJapaneseAuction(item_id, user_id) => {
@@ -94,11 +107,26 @@ pub async fn handle_socket(
                                             crate::auction::JapaneseAuctionEvent::NewPriceClockInterval { price_increase_per_100_seconds: new_rate }
                                         )
                                     ).await,
+                                    AdminClientMessage::SetJapanesePriceStep(new_step) => sync_handle.send_event(
+                                        AuctionEvent::JapaneseAuctionAction(
+                                            crate::auction::JapaneseAuctionEvent::NewPriceStep(new_step)
+                                        )
+                                    ).await,
+                                    AdminClientMessage::SetJapaneseArenaClosingSecs(secs) => sync_handle.send_event(
+                                        AuctionEvent::JapaneseAuctionAction(
+                                            crate::auction::JapaneseAuctionEvent::NewArenaClosingDuration(Duration::from_secs(secs as u64))
+                                        )
+                                    ).await,
                                     AdminClientMessage::SetJapaneseVisibilityMode(new_mode) => sync_handle.send_event(
                                         AuctionEvent::JapaneseAuctionAction(
                                             crate::auction::JapaneseAuctionEvent::NewArenaVisibilityMode(new_mode)
                                         )
                                     ).await,
+                                    AdminClientMessage::SetDutchClockRate(new_rate) => sync_handle.send_event(
+                                        AuctionEvent::DutchAuctionAction(
+                                            crate::auction::DutchAuctionEvent::NewPriceClockInterval { price_decrease_per_100_seconds: new_rate }
+                                        )
+                                    ).await,
                                     AdminClientMessage::ChangeUserName{id, new_name} => {
                                         sync_handle.send_event(
                                             AuctionEvent::EditUser { id: Some(id), name: Some(new_name), balance: None }
@@ -160,9 +188,83 @@ pub async fn handle_socket(
                                             AuctionEvent::EditItem {id: Some(id), name: None, initial_price: price},
                                         ).await;
                                     },
+                                    AdminClientMessage::ChangeItemReservePrice{id, new_price} => {
+                                        // An empty value removes the reserve price.
+                                        // Otherwise, try parsing the provided value as a number, and if that fails, ignore it.
+                                        let reserve_price = if new_price.trim().is_empty() {
+                                            None
+                                        } else {
+                                            match new_price.trim().parse() {
+                                                Ok(v) => Some(v),
+                                                Err(_) => {
+                                                    warn!("Admin inputted invalid number: {new_price}");
+                                                    // Send the latest item state immediately.
+                                                    {
+                                                        let items: WithTimestamp<_> = sync_handle.item_sale_states.borrow().clone().into();
+                                                        send!(socket, AdminServerMessage::ItemStates(items));
+                                                    }
+                                                    continue;
+                                                },
+                                            }
+                                        };
+                                        sync_handle.send_event(
+                                            AuctionEvent::SetItemReservePrice {id, reserve_price},
+                                        ).await;
+                                    },
+                                    AdminClientMessage::ChangeItemBuyNowPrice{id, new_price} => {
+                                        // An empty value removes the buy-now price.
+                                        // Otherwise, try parsing the provided value as a number, and if that fails, ignore it.
+                                        let buy_now_price = if new_price.trim().is_empty() {
+                                            None
+                                        } else {
+                                            match new_price.trim().parse() {
+                                                Ok(v) => Some(v),
+                                                Err(_) => {
+                                                    warn!("Admin inputted invalid number: {new_price}");
+                                                    // Send the latest item state immediately.
+                                                    {
+                                                        let items: WithTimestamp<_> = sync_handle.item_sale_states.borrow().clone().into();
+                                                        send!(socket, AdminServerMessage::ItemStates(items));
+                                                    }
+                                                    continue;
+                                                },
+                                            }
+                                        };
+                                        sync_handle.send_event(
+                                            AuctionEvent::SetItemBuyNowPrice {id, buy_now_price},
+                                        ).await;
+                                    },
+                                    AdminClientMessage::ChangeItemQuantity{id, new_quantity} => {
+                                        // Try parsing the provided value as a number, and if that fails, or it is zero, ignore it.
+                                        let quantity = match new_quantity.trim().parse() {
+                                            Ok(v) if v > 0 => v,
+                                            _ => {
+                                                warn!("Admin inputted invalid quantity: {new_quantity}");
+                                                // Send the latest item state immediately.
+                                                {
+                                                    let items: WithTimestamp<_> = sync_handle.item_sale_states.borrow().clone().into();
+                                                    send!(socket, AdminServerMessage::ItemStates(items));
+                                                }
+                                                continue;
+                                            },
+                                        };
+                                        sync_handle.send_event(
+                                            AuctionEvent::SetItemQuantity {id, quantity},
+                                        ).await;
+                                    },
+                                    AdminClientMessage::ChangeItemCategory{id, new_category} => {
+                                        // An empty value removes the category.
+                                        let category = Some(new_category.trim().to_string()).filter(|c| !c.is_empty());
+                                        sync_handle.send_event(
+                                            AuctionEvent::SetItemCategory {id, category},
+                                        ).await;
+                                    },
                                     AdminClientMessage::DeleteItem{id} => sync_handle.send_event(
                                             AuctionEvent::EditItem {id: Some(id), name: None, initial_price: None},
                                     ).await,
+                                    AdminClientMessage::CreateLot{name, item_ids} => sync_handle.send_event(
+                                            AuctionEvent::CreateLot {name, item_ids},
+                                    ).await,
                                     AdminClientMessage::CreateItem{name} => sync_handle.send_event(
                                             AuctionEvent::EditItem {id: None, name: Some(name), initial_price: None},
                                     ).await,
@@ -172,6 +274,9 @@ pub async fn handle_socket(
                                     AdminClientMessage::SetEnglishAuctionCommitPeriod {new_period_ms} => sync_handle.send_event(
                                         AuctionEvent::EnglishAuctionAction(crate::auction::EnglishAuctionEvent::SetCommitPeriod { new_period: Duration::from_millis(new_period_ms as u64) })
                                     ).await,
+                                    AdminClientMessage::SetEnglishAuctionIncrementRule(rule) => sync_handle.send_event(
+                                        AuctionEvent::EnglishAuctionAction(crate::auction::EnglishAuctionEvent::SetIncrementRule(rule))
+                                    ).await,
                                     AdminClientMessage::StartClosingJapaneseArena => {
                                         sync_handle.send_event(
                                             AuctionEvent::JapaneseAuctionAction(
@@ -179,6 +284,21 @@ pub async fn handle_socket(
                                             )
                                         ).await;
                                     }
+                                    AdminClientMessage::CloseSealedBidding => sync_handle.send_event(
+                                        AuctionEvent::SealedBidAuctionAction(crate::auction::SealedBidAuctionEvent::CloseBidding)
+                                    ).await,
+                                    AdminClientMessage::CloseRaffleEntries => sync_handle.send_event(
+                                        AuctionEvent::RaffleAction(crate::auction::RaffleEvent::CloseEntries)
+                                    ).await,
+                                    AdminClientMessage::OpenSilentAuctionItem { item_id, duration_secs } => sync_handle.send_event(
+                                        AuctionEvent::SilentAuctionAction(crate::auction::SilentAuctionEvent::OpenItem { item_id, duration: Duration::from_secs(duration_secs) })
+                                    ).await,
+                                    AdminClientMessage::CloseSilentAuctionItem(item_id) => sync_handle.send_event(
+                                        AuctionEvent::SilentAuctionAction(crate::auction::SilentAuctionEvent::CloseItem(item_id))
+                                    ).await,
+                                    AdminClientMessage::RemoveSilentAuctionItem(item_id) => sync_handle.send_event(
+                                        AuctionEvent::SilentAuctionAction(crate::auction::SilentAuctionEvent::RemoveItem(item_id))
+                                    ).await,
                                 }
                             },
                             _ => {},
@@ -206,6 +326,10 @@ pub async fn handle_socket(
                 let latest_state = sync_handle.sponsorship_state.borrow().clone();
                 send!(socket, AdminServerMessage::SponsorshipState(latest_state.into()));
             },
+            _ = sync_handle.silent_auction_state.changed() => {
+                let latest_state = sync_handle.silent_auction_state.borrow().clone();
+                send!(socket, AdminServerMessage::SilentAuctionState(latest_state.into()));
+            },
         }
     }
 }
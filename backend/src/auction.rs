@@ -2,7 +2,11 @@ use std::{sync::Arc, time::Duration};
 
 use communication::{
     admin_state::AdminState,
-    auction::state::{AuctionItem, AuctionReport, AuctionState, Sponsorship, SponsorshipStatus},
+    auction::state::{
+        AuctionItem, AuctionReport, AuctionState, ContributionSplit, JapaneseAuctionSettings,
+        RerunAuctionKind, RerunPolicy, SilentAuctionState, SoftClosePolicy, Sponsorship,
+        SponsorshipLogEntry, SponsorshipLogEvent, SponsorshipScope, SponsorshipStatus,
+    },
     forget_user_secrets, ItemState, Money, UserAccountDataWithSecrets,
 };
 use rand::prelude::*;
@@ -11,20 +15,35 @@ use tokio::{
     sync::*,
     time::{interval, Instant},
 };
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
 
+mod all_pay;
 mod auction_event;
+mod buy_now;
 mod db_actions;
+mod dutch;
 mod english;
 mod japanese;
+mod raffle;
+mod sealed_bid;
+mod silent;
 mod sync_handle;
+pub use all_pay::*;
 pub use auction_event::*;
+pub use dutch::*;
 pub use english::*;
 pub use japanese::*;
+pub use raffle::*;
+pub use sealed_bid::*;
+pub use silent::*;
 pub use sync_handle::*;
 
 use crate::{
-    auction::db_actions::{get_item_state, get_sponsorship_state, get_user_state},
+    auction::db_actions::{
+        create_lot, get_contribution_split, get_item_state, get_sponsorship_log,
+        get_sponsorship_state, get_units_left, get_user_state, log_sponsorship_event,
+        replace_sponsorship_status, status_change_event,
+    },
     Ignorable,
 };
 
@@ -54,6 +73,69 @@ async fn gen_sponsorship_code(pool: &SqlitePool) -> anyhow::Result<String> {
     Ok(code)
 }
 
+/// The event that auctions an unsold item again at the given initial price according to the re-run policy,
+/// given the event that started the auction that did not sell it.
+fn rerun_event(policy: RerunPolicy, last_run: AuctionEvent, initial_price: Money) -> AuctionEvent {
+    let rerun = Some(RerunPolicy {
+        max_reruns: policy.max_reruns.saturating_sub(1),
+        ..policy
+    });
+    let initial_price = Some(initial_price);
+    // Switching to the kind of auction that was already used keeps its settings.
+    let switch_to = match (policy.switch_to, &last_run) {
+        (Some(RerunAuctionKind::English), AuctionEvent::RunEnglishAuction { .. })
+        | (Some(RerunAuctionKind::Japanese), AuctionEvent::RunJapaneseAuction { .. }) => None,
+        (switch_to, _) => switch_to,
+    };
+    match (switch_to, last_run) {
+        (
+            Some(RerunAuctionKind::English),
+            AuctionEvent::RunJapaneseAuction { item_id, .. }
+            | AuctionEvent::RunEnglishAuction { item_id, .. },
+        ) => AuctionEvent::RunEnglishAuction {
+            item_id,
+            soft_close_policy: SoftClosePolicy::default(),
+            initial_price,
+            rerun,
+        },
+        (
+            Some(RerunAuctionKind::Japanese),
+            AuctionEvent::RunEnglishAuction { item_id, .. }
+            | AuctionEvent::RunJapaneseAuction { item_id, .. },
+        ) => AuctionEvent::RunJapaneseAuction {
+            item_id,
+            settings: JapaneseAuctionSettings::default(),
+            initial_price,
+            rerun,
+        },
+        (
+            None,
+            AuctionEvent::RunEnglishAuction {
+                item_id,
+                soft_close_policy,
+                ..
+            },
+        ) => AuctionEvent::RunEnglishAuction {
+            item_id,
+            soft_close_policy,
+            initial_price,
+            rerun,
+        },
+        (
+            None,
+            AuctionEvent::RunJapaneseAuction {
+                item_id, settings, ..
+            },
+        ) => AuctionEvent::RunJapaneseAuction {
+            item_id,
+            settings,
+            initial_price,
+            rerun,
+        },
+        (_, other) => other,
+    }
+}
+
 async fn auction_manager_inner(
     pool: &SqlitePool,
     auction_member_tx: &mut watch::Sender<Vec<UserAccountDataWithSecrets>>,
@@ -66,12 +148,18 @@ async fn auction_manager_inner(
     item_sale_state_tx: &mut watch::Sender<Vec<ItemState>>,
     admin_state_tx: &mut watch::Sender<AdminState>,
     sponsorship_state_tx: &mut watch::Sender<Vec<Sponsorship>>,
+    sponsorship_log_tx: &mut watch::Sender<Vec<SponsorshipLogEntry>>,
+    silent_auction_state_tx: &mut watch::Sender<SilentAuctionState>,
     sync_handle: AuctionSyncHandle,
 ) -> anyhow::Result<()> {
     let mut user_data_refresh_interval = tokio::time::interval(Duration::from_secs(1));
 
     let mut item_data_refresh_interval = tokio::time::interval(Duration::from_secs(5));
 
+    // Sponsorships are also used by the auctions, which do not report it to this task,
+    // so the sponsorship history is checked for new entries periodically.
+    let mut sponsorship_log_refresh_interval = tokio::time::interval(Duration::from_secs(1));
+
     // Initially, the running auction handle is set to a noop task
     // It will be cancelled when needed
     async fn noop() -> anyhow::Result<()> {
@@ -79,21 +167,52 @@ async fn auction_manager_inner(
     }
     let mut running_auction_handle = tokio::spawn(noop());
 
-    // These are used to communicate with the two kinds of auction that are running
+    // The item that the live auction was last shown or started with.
+    let mut live_item_id: Option<i64> = None;
+
+    // These are used to communicate with the kinds of auction that are running
     // The receiving halves are inside mutexes. Expected to only run one auction task at the same time, so no blocking.
     let (english_tx, english_rx) = mpsc::channel(100);
     let english_rx = Arc::new(Mutex::new(english_rx));
     let (japanese_tx, japanese_rx) = mpsc::channel(100);
     let japanese_rx = Arc::new(Mutex::new(japanese_rx));
+    let (dutch_tx, dutch_rx) = mpsc::channel(100);
+    let dutch_rx = Arc::new(Mutex::new(dutch_rx));
+    let (sealed_bid_tx, sealed_bid_rx) = mpsc::channel(100);
+    let sealed_bid_rx = Arc::new(Mutex::new(sealed_bid_rx));
+    let (raffle_tx, raffle_rx) = mpsc::channel(100);
+    let raffle_rx = Arc::new(Mutex::new(raffle_rx));
+    let (all_pay_tx, all_pay_rx) = mpsc::channel(100);
+    let all_pay_rx = Arc::new(Mutex::new(all_pay_rx));
     let (state_tx, mut state_rx) = mpsc::channel(100);
     enum AuctionType {
         NoAuction,
         English,
         Japanese,
+        Dutch,
+        SealedBid,
+        Raffle,
+        AllPay,
     }
     use AuctionType::*;
     let mut current_auction = NoAuction;
 
+    // If the running English or Japanese auction ends without any bids for its item,
+    // the item is auctioned again with this re-run policy, below the initial price that this event started it at.
+    let mut pending_rerun: Option<(i64, RerunPolicy, Option<Money>, AuctionEvent)> = None;
+
+    // The silent auction runs alongside whatever is happening in the live auction.
+    // It stops when this function returns, because its event sender is dropped.
+    let (silent_tx, silent_rx) = mpsc::channel(100);
+    let (silent_state_tx, mut silent_state_rx) = mpsc::channel(100);
+    silent_auction_state_tx.send_replace(SilentAuctionState::default());
+    tokio::spawn(run_silent_auction(
+        pool.clone(),
+        silent_rx,
+        silent_state_tx,
+        sync_handle.clone(),
+    ));
+
     // Ensure that the needed KV records are in the database.
     if query!("SELECT * FROM kv_data_int WHERE key='holding_balance'")
         .fetch_optional(pool)
@@ -104,6 +223,19 @@ async fn auction_manager_inner(
             .execute(pool)
             .await?;
     }
+    if query!("SELECT * FROM kv_data_int WHERE key='contribution_split'")
+        .fetch_optional(pool)
+        .await?
+        .is_none()
+    {
+        let split = ContributionSplit::default().to_db_val();
+        query!(
+            "INSERT INTO kv_data_int (key,value) VALUES ('contribution_split', ?)",
+            split
+        )
+        .execute(pool)
+        .await?;
+    }
 
     // Gather the initial admin state and send it.
     let get_admin_state = {
@@ -113,14 +245,21 @@ async fn auction_manager_inner(
                     -> anyhow::Result<AdminState> {
             let mut lhaca = last_holding_account_checked_at.lock().await;
 
-            let holding_account_balance = if lhaca.elapsed().as_secs() >= 1 {
+            let (holding_account_balance, contribution_split) = if lhaca.elapsed().as_secs() >= 1 {
                 *lhaca = Instant::now();
-                query!("SELECT value FROM kv_data_int WHERE key='holding_balance'")
-                    .fetch_one(pool)
-                    .await?
-                    .value as Money
+                (
+                    query!("SELECT value FROM kv_data_int WHERE key='holding_balance'")
+                        .fetch_one(pool)
+                        .await?
+                        .value as Money,
+                    get_contribution_split(pool).await?,
+                )
             } else {
-                sync_handle.admin_state.borrow().holding_account_balance
+                let admin_state = sync_handle.admin_state.borrow();
+                (
+                    admin_state.holding_account_balance,
+                    admin_state.contribution_split,
+                )
             };
 
             // To get the list of connected users, we'll go through the HashMap of connected handles,
@@ -136,6 +275,7 @@ async fn auction_manager_inner(
             let state = AdminState {
                 holding_account_balance,
                 connected_users,
+                contribution_split,
             };
             Ok(state)
         }
@@ -145,6 +285,7 @@ async fn auction_manager_inner(
 
     admin_state_tx.send_replace(get_admin_state(&pool, &sync_handle, lhaca.clone()).await?);
     sponsorship_state_tx.send_replace(get_sponsorship_state(pool).await?);
+    sponsorship_log_tx.send_replace(get_sponsorship_log(pool).await?);
 
     let mut admin_data_refresh_interval = interval(Duration::from_millis(100));
 
@@ -161,6 +302,12 @@ async fn auction_manager_inner(
                 auction_member_tx.send_replace(get_user_state(pool).await?);
             },
 
+            // Only notify the receivers if there are new entries.
+            _ = sponsorship_log_refresh_interval.tick() => {
+                let log = get_sponsorship_log(pool).await?;
+                sponsorship_log_tx.send_if_modified(|old| if *old == log { false } else { *old = log; true });
+            },
+
             // This one is definitely necessary: the admin state can change by external means (user connects/disconnects)
             _ = admin_data_refresh_interval.tick() => {
                     admin_state_tx.send_replace(get_admin_state(&pool, &sync_handle, lhaca).await?);
@@ -186,31 +333,102 @@ async fn auction_manager_inner(
 
             Some(event) = auction_event_rx.recv() => {
                 debug!("Received auction event: {event:?}");
+
+                // An item that belongs to a lot is only ever sold as part of the lot.
+                if let Some(item_id) = event.item_to_auction() {
+                    let lot_id = query!("SELECT lot_id FROM auction_item WHERE id=?", item_id).fetch_optional(pool).await?.and_then(|row| row.lot_id);
+                    if let Some(lot_id) = lot_id {
+                        warn!("Item {item_id} belongs to lot {lot_id}, so it cannot be auctioned on its own; ignoring");
+                        continue;
+                    }
+
+                    // Nor can an item be auctioned live while its bidding is open in the silent auction, as both could sell its last unit.
+                    if silent_auction_state_tx.borrow().items.iter().any(|i| i.item.id == item_id && i.outcome.is_none()) {
+                        warn!("Item {item_id} is open in the silent auction, so it cannot be auctioned live; ignoring");
+                        continue;
+                    }
+                    live_item_id = Some(item_id);
+                }
+
                 match event {
                     AuctionEvent::StartAuction => {
                         // Switch to auction state = waiting
                         auction_state_tx.send_replace(AuctionState::WaitingForItem);
                         current_auction = NoAuction;
                         running_auction_handle.abort();
+                        pending_rerun = None;
 
                     },
                     AuctionEvent::PrepareAuctioning(item_id) => {
                         // Switch to auction state of showing the item
                         let item = query!("SELECT * FROM auction_item WHERE id=?", item_id).fetch_one(pool).await?;
-                        let item = AuctionItem{id: item.id, name: item.name, initial_price: item.initial_price as Money};
+                        let item = AuctionItem{id: item.id, name: item.name, initial_price: item.initial_price as Money, reserve_price: item.reserve_price.map(|p| p as Money), buy_now_price: item.buy_now_price.map(|p| p as Money), quantity: item.quantity as u32, category: item.category};
                         auction_state_tx.send_replace(AuctionState::ShowingItemBeforeBidding(item));
                         current_auction = NoAuction;
                         running_auction_handle.abort();
+                        pending_rerun = None;
                     },
-                    AuctionEvent::RunEnglishAuction(item_id) => {
+                    AuctionEvent::RunEnglishAuction { item_id, soft_close_policy, initial_price, rerun } => {
                         running_auction_handle.abort();
                         current_auction = English;
-                        running_auction_handle = tokio::spawn(run_english_auction(item_id, pool.clone(), english_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                        pending_rerun = rerun.filter(|r| r.max_reruns > 0).map(|r| (item_id, r, initial_price, AuctionEvent::RunEnglishAuction { item_id, soft_close_policy, initial_price, rerun }));
+                        running_auction_handle = tokio::spawn(run_english_auction(item_id, soft_close_policy, initial_price, pool.clone(), english_rx.clone(), state_tx.clone(), sync_handle.clone()));
                     },
-                    AuctionEvent::RunJapaneseAuction(item_id) => {
+                    AuctionEvent::RunJapaneseAuction { item_id, settings, initial_price, rerun } => {
                         running_auction_handle.abort();
                         current_auction = Japanese;
-                        running_auction_handle = tokio::spawn(run_japanese_auction(item_id, pool.clone(), japanese_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                        pending_rerun = rerun.filter(|r| r.max_reruns > 0).map(|r| (item_id, r, initial_price, AuctionEvent::RunJapaneseAuction { item_id, settings, initial_price, rerun }));
+                        running_auction_handle = tokio::spawn(run_japanese_auction(item_id, settings, initial_price, pool.clone(), japanese_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                    },
+                    AuctionEvent::RunDutchAuction { item_id, starting_price } => {
+                        running_auction_handle.abort();
+                        current_auction = Dutch;
+                        pending_rerun = None;
+                        running_auction_handle = tokio::spawn(run_dutch_auction(item_id, starting_price, pool.clone(), dutch_rx.clone(), state_tx.clone()));
+                    },
+                    AuctionEvent::RunSealedBidAuction { item_id, pricing } => {
+                        running_auction_handle.abort();
+                        current_auction = SealedBid;
+                        pending_rerun = None;
+                        running_auction_handle = tokio::spawn(run_sealed_bid_auction(item_id, pricing, pool.clone(), sealed_bid_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                    },
+                    AuctionEvent::RunRaffle { item_id, ticket_price, entry_window } => {
+                        running_auction_handle.abort();
+                        current_auction = Raffle;
+                        pending_rerun = None;
+                        running_auction_handle = tokio::spawn(run_raffle(item_id, ticket_price, entry_window, pool.clone(), raffle_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                    },
+                    AuctionEvent::RunAllPayAuction(item_id) => {
+                        running_auction_handle.abort();
+                        current_auction = AllPay;
+                        pending_rerun = None;
+                        running_auction_handle = tokio::spawn(run_all_pay_auction(item_id, pool.clone(), all_pay_rx.clone(), state_tx.clone(), sync_handle.clone()));
+                    },
+                    AuctionEvent::ItemGotNoBids(item_id) => {
+                        // If the item has a re-run policy, it is shown at a lower price for a while, and then auctioned again.
+                        // An item that was not sold for another reason, such as its reserve price not being met, is not.
+                        let (policy, last_price, last_run) = match pending_rerun.take() {
+                            Some((id, policy, last_price, last_run)) if id == item_id => (policy, last_price, last_run),
+                            _ => continue,
+                        };
+                        let row = query!("SELECT * FROM auction_item WHERE id=?", item_id).fetch_one(pool).await?;
+                        let mut item = AuctionItem{id: row.id, name: row.name, initial_price: row.initial_price as Money, reserve_price: row.reserve_price.map(|p| p as Money), buy_now_price: row.buy_now_price.map(|p| p as Money), quantity: row.quantity as u32, category: row.category};
+                        if get_units_left(pool, &item).await? == 0 {continue;}
+
+                        // The item keeps its own initial price: the lower one is only used for auctioning it again.
+                        item.initial_price = policy.reduced_price(last_price.unwrap_or(item.initial_price));
+                        let new_price = item.initial_price;
+                        let next_run = rerun_event(policy, last_run, new_price);
+                        info!("Item {item_id} got no bids; auctioning it again at {new_price} in {}s", policy.pause_secs);
+
+                        auction_state_tx.send_replace(AuctionState::ShowingItemBeforeBidding(item));
+                        current_auction = NoAuction;
+                        let sync_handle = sync_handle.clone();
+                        running_auction_handle = tokio::spawn(async move {
+                            tokio::time::sleep(Duration::from_secs(policy.pause_secs as u64)).await;
+                            sync_handle.send_event(next_run).await;
+                            Ok(())
+                        });
                     },
 
                     AuctionEvent::EnglishAuctionAction(action) => {
@@ -225,9 +443,72 @@ async fn auction_manager_inner(
                         japanese_tx.send(action).await?;
                     },
 
+                    AuctionEvent::DutchAuctionAction(action) => {
+                        // If there is no Dutch auction currently in progress, ignore this
+                        if !matches!(current_auction, Dutch) { continue; }
+                        dutch_tx.send(action).await?;
+                    },
+
+                    AuctionEvent::SealedBidAuctionAction(action) => {
+                        // If there is no sealed-bid auction currently in progress, ignore this
+                        if !matches!(current_auction, SealedBid) { continue; }
+                        sealed_bid_tx.send(action).await?;
+                    },
+
+                    AuctionEvent::RaffleAction(action) => {
+                        // If there is no raffle currently in progress, ignore this
+                        if !matches!(current_auction, Raffle) { continue; }
+                        raffle_tx.send(action).await?;
+                    },
+
+                    AuctionEvent::AllPayAuctionAction(action) => {
+                        // If there is no all-pay auction currently in progress, ignore this
+                        if !matches!(current_auction, AllPay) { continue; }
+                        all_pay_tx.send(action).await?;
+                    },
+
+                    AuctionEvent::SilentAuctionAction(action) => {
+                        // An item cannot be opened in the silent auction while the live auction is showing it or selling it.
+                        if let SilentAuctionEvent::OpenItem { item_id, .. } = action {
+                            let live_item_id = match &*auction_state_tx.borrow() {
+                                AuctionState::ShowingItemBeforeBidding(item) => Some(item.id),
+                                _ => live_item_id.filter(|_| !running_auction_handle.is_finished()),
+                            };
+                            if live_item_id == Some(item_id) {
+                                warn!("Item {item_id} is in the live auction, so it cannot be opened in the silent auction; ignoring");
+                                continue;
+                            }
+                        }
+                        silent_tx.send(action).await?;
+                    },
+
+                    AuctionEvent::PauseAuction => {
+                        // Only English and Japanese auctions can be paused; otherwise, ignore this
+                        match current_auction {
+                            English => english_tx.send(EnglishAuctionEvent::Pause).await?,
+                            Japanese => japanese_tx.send(JapaneseAuctionEvent::Pause).await?,
+                            _ => continue,
+                        }
+                    },
+
+                    AuctionEvent::ResumeAuction => {
+                        match current_auction {
+                            English => english_tx.send(EnglishAuctionEvent::Resume).await?,
+                            Japanese => japanese_tx.send(JapaneseAuctionEvent::Resume).await?,
+                            _ => continue,
+                        }
+                    },
+
+                    AuctionEvent::SetContributionSplit(split) => {
+                        let split_val = split.to_db_val();
+                        query!("UPDATE kv_data_int SET value=? WHERE key='contribution_split'", split_val).execute(pool).await?;
+                        admin_state_tx.send_modify(|state| state.contribution_split = split);
+                    },
+
                     AuctionEvent::FinishAuction => {
                         running_auction_handle.abort();
                         current_auction = NoAuction;
+                        pending_rerun = None;
 
                         // Gather auction report
                         // First, collect the latest user data
@@ -244,6 +525,7 @@ async fn auction_manager_inner(
                     AuctionEvent::StartAuctionAnew => {
                         running_auction_handle.abort();
                         current_auction = NoAuction;
+                        pending_rerun = None;
                         auction_state_tx.send_replace(AuctionState::WaitingForAuction);
                     },
 
@@ -297,9 +579,10 @@ async fn auction_manager_inner(
                     },
 
                     AuctionEvent::ClearSaleStatus {id} => {
-                        // Remove the sale row, if it exists.
+                        // Remove the sale rows of all the item's units, if they exist.
                         // After, send the item states.
                         query!("DELETE FROM auction_item_sale WHERE item_id=?", id).execute(pool).await?;
+                        query!("UPDATE auction_item SET reserve_not_met=0 WHERE id=?", id).execute(pool).await?;
                         item_sale_state_tx.send_replace(get_item_state(pool).await?);
                     },
 
@@ -340,6 +623,47 @@ async fn auction_manager_inner(
                         // After the action was taken, send the current item states.
                         item_sale_state_tx.send_replace(get_item_state(pool).await?);
                     },
+                    AuctionEvent::SetItemReservePrice { id, reserve_price } => {
+                        query!("UPDATE auction_item SET reserve_price=? WHERE id=?", reserve_price, id).execute(pool).await?;
+                        item_sale_state_tx.send_replace(get_item_state(pool).await?);
+                    },
+                    AuctionEvent::SetItemBuyNowPrice { id, buy_now_price } => {
+                        query!("UPDATE auction_item SET buy_now_price=? WHERE id=?", buy_now_price, id).execute(pool).await?;
+                        item_sale_state_tx.send_replace(get_item_state(pool).await?);
+                    },
+                    AuctionEvent::CreateLot { name, item_ids } => {
+                        create_lot(pool, &name, &item_ids).await?;
+                        item_sale_state_tx.send_replace(get_item_state(pool).await?);
+                    },
+                    AuctionEvent::SetItemQuantity { id, quantity } => {
+                        query!("UPDATE auction_item SET quantity=? WHERE id=?", quantity, id).execute(pool).await?;
+                        item_sale_state_tx.send_replace(get_item_state(pool).await?);
+                    },
+                    AuctionEvent::SetItemCategory { id, category } => {
+                        query!("UPDATE auction_item SET category=? WHERE id=?", category, id).execute(pool).await?;
+                        item_sale_state_tx.send_replace(get_item_state(pool).await?);
+                    },
+                    AuctionEvent::BuyNow { user_id, item_id } => {
+                        match current_auction {
+                            // In an English auction, the auction task decides whether the price has been passed.
+                            English => english_tx.send(EnglishAuctionEvent::BuyNow { buyer_id: user_id, item_id }).await?,
+
+                            // Before the bidding starts, this task handles the sale.
+                            // Events are processed one at a time, so if two members press the button together,
+                            // the second one will find the item already sold.
+                            NoAuction => {
+                                let item = match &*auction_state_tx.borrow() {
+                                    AuctionState::ShowingItemBeforeBidding(item) if item.id == item_id => item.clone(),
+                                    _ => continue,
+                                };
+                                if let Some(sale) = buy_now::try_buy_now(pool, &item, user_id).await? {
+                                    auction_state_tx.send_replace(AuctionState::after_units_sold(item, vec![sale]));
+                                }
+                            },
+
+                            _ => continue,
+                        }
+                    },
                     AuctionEvent::HoldingAccountTransfer { user_id, new_balance } => {
                         let mut tx = pool.begin().await?;
                         let user_balance = query!("SELECT balance FROM auction_user WHERE id=?", user_id).fetch_optional(&mut tx).await?;
@@ -413,27 +737,50 @@ async fn auction_manager_inner(
                         let mut tx = pool.begin().await?;
 
                         // Any other sponsorships from the donor to the recepient that are Active need to be changed to Retracted.
-                        let retracted = SponsorshipStatus::Retracted.to_db_val();
-                        let active = SponsorshipStatus::Active.to_db_val();
-                        query!("UPDATE sponsorship SET status=? WHERE status=? AND donor_id=? AND recepient_id=?",
-                            retracted, active,
-                            user_id, row.id,
-                        ).execute(&mut tx).await?;
+                        replace_sponsorship_status(&mut tx, user_id, row.id, SponsorshipStatus::Active, SponsorshipStatus::Retracted).await?;
 
                         // Create the sponsorship row
-                        query!(
+                        let sponsorship_id = query!(
                             "INSERT INTO sponsorship (donor_id, recepient_id, status, remaining_balance) VALUES (?,?,?,?)",
                             user_id, row.id, 1 /*status=active*/, balance
-                            ).execute(&mut tx).await?;
+                            ).execute(&mut tx).await?.last_insert_rowid();
+                        log_sponsorship_event(&mut tx, sponsorship_id, &SponsorshipLogEvent::Created { balance }).await?;
 
                         tx.commit().await?;
 
                         // Fetch current sponsorships
                         sponsorship_state_tx.send_replace(get_sponsorship_state(pool).await?);
+                        sponsorship_log_tx.send_replace(get_sponsorship_log(pool).await?);
+
+                    },
+                    AuctionEvent::RequestSponsorship { user_id, donor_id, amount } => {
+                        // The donor must be another user who exists.
+                        if user_id == donor_id { continue; }
+                        if !auction_member_tx.borrow().iter().any(|u| u.id == donor_id) {
+                            warn!("User {user_id} requested a sponsorship from user ID={donor_id}; no such user: hacking detected?");
+                            continue;
+                        }
+
+                        let mut tx = pool.begin().await?;
+
+                        // Any earlier request from the user to the donor that is still Pending is withdrawn, so it is Rejected.
+                        replace_sponsorship_status(&mut tx, donor_id, user_id, SponsorshipStatus::Pending, SponsorshipStatus::Rejected).await?;
+
+                        // Create the pending sponsorship row, with the requested amount as its balance.
+                        let pending = SponsorshipStatus::Pending.to_db_val();
+                        let sponsorship_id = query!(
+                            "INSERT INTO sponsorship (donor_id, recepient_id, status, remaining_balance) VALUES (?,?,?,?)",
+                            donor_id, user_id, pending, amount
+                            ).execute(&mut tx).await?.last_insert_rowid();
+                        log_sponsorship_event(&mut tx, sponsorship_id, &SponsorshipLogEvent::Requested { amount }).await?;
+
+                        tx.commit().await?;
 
+                        sponsorship_state_tx.send_replace(get_sponsorship_state(pool).await?);
+                        sponsorship_log_tx.send_replace(get_sponsorship_log(pool).await?);
                     },
 
-                    AuctionEvent::UpdateSponsorship { actor_id, sponsorship_id, new_status, new_amount } => {
+                    AuctionEvent::UpdateSponsorship { actor_id, sponsorship_id, new_status, new_amount, new_max_percent, new_only_for } => {
                         // Fetch the sponsorship. If it doesn't exist, ignore.
                         let mut sponsorship = {
                             let sponsorships = sponsorship_state_tx.borrow();
@@ -444,6 +791,7 @@ async fn auction_manager_inner(
                                 Some(s) => s.clone(),
                             }
                         };
+                        let old_status = sponsorship.status;
                         let mut did_change = false;
 
                         // If I'm the recepient, I can change the status to Rejected.
@@ -458,21 +806,75 @@ async fn auction_manager_inner(
                             did_change = true;
                         }}
 
+                        // If I'm the donor, I can change the percentage cap to anything from 1% to 100%, or remove it.
+                        if let Some(p) = new_max_percent { if actor_id == sponsorship.donor_id {
+                            if p.is_some_and(|p| !(1..=100).contains(&p)) {
+                                warn!("User {actor_id} tried to set sponsorship {sponsorship_id} to cover {p:?}% of each purchase; ignoring");
+                                continue;
+                            }
+                            sponsorship.max_percent = p;
+                            did_change = true;
+                        }}
+
+                        // If I'm the donor, I can limit the sponsorship to any items and categories, or to none of them.
+                        if let Some(only_for) = new_only_for { if actor_id == sponsorship.donor_id {
+                            sponsorship.only_for = only_for;
+                            did_change = true;
+                        }}
+
                         // If I'm the donor, I can change the status to Retracted.
                         if actor_id == sponsorship.donor_id && new_status == Some(SponsorshipStatus::Retracted) {
                             sponsorship.status = SponsorshipStatus::Retracted;
                             did_change = true;
                         }
 
+                        // If I'm the donor, I can accept a Pending request, changing the status to Active.
+                        let did_accept = actor_id == sponsorship.donor_id
+                            && sponsorship.status == SponsorshipStatus::Pending
+                            && new_status == Some(SponsorshipStatus::Active);
+                        if did_accept {
+                            sponsorship.status = SponsorshipStatus::Active;
+                            did_change = true;
+                        }
+
 
                         // If any of the changes were applied, persist them,
                         // then update the receivers.
                         if did_change {
                             let status = sponsorship.status.to_db_val();
-                            query!("UPDATE sponsorship SET status=?, remaining_balance=? WHERE id=?",
-                                status, sponsorship.balance_remaining, sponsorship.id)
-                                .execute(pool).await?;
+                            let mut tx = pool.begin().await?;
+
+                            // As when using a code, any other sponsorships from the donor to the recepient that are Active need to be changed to Retracted.
+                            // (This sponsorship is still Pending in the database, so it is not affected.)
+                            if did_accept {
+                                replace_sponsorship_status(&mut tx, sponsorship.donor_id, sponsorship.recepient_id, SponsorshipStatus::Active, SponsorshipStatus::Retracted).await?;
+                            }
+                            query!("UPDATE sponsorship SET status=?, remaining_balance=?, max_percent=? WHERE id=?",
+                                status, sponsorship.balance_remaining, sponsorship.max_percent, sponsorship.id)
+                                .execute(&mut tx).await?;
+                            query!("DELETE FROM sponsorship_scope WHERE sponsorship_id=?", sponsorship.id).execute(&mut tx).await?;
+                            for scope in sponsorship.only_for.iter() {
+                                let (item_id, category) = match scope {
+                                    SponsorshipScope::Item(id) => (Some(*id), None),
+                                    SponsorshipScope::Category(c) => (None, Some(c.trim())),
+                                };
+                                query!("INSERT INTO sponsorship_scope (sponsorship_id, item_id, category) VALUES (?,?,?)",
+                                    sponsorship.id, item_id, category)
+                                    .execute(&mut tx).await?;
+                            }
+
+                            // Record the change in the sponsorship's history.
+                            let event = if sponsorship.status == old_status {
+                                Some(SponsorshipLogEvent::Adjusted { balance: sponsorship.balance_remaining })
+                            } else {
+                                status_change_event(sponsorship.status, sponsorship.balance_remaining)
+                            };
+                            if let Some(event) = event {
+                                log_sponsorship_event(&mut tx, sponsorship.id, &event).await?;
+                            }
+                            tx.commit().await?;
                             sponsorship_state_tx.send_replace(get_sponsorship_state(pool).await?);
+                            sponsorship_log_tx.send_replace(get_sponsorship_log(pool).await?);
 
                         }
                     },
@@ -508,9 +910,12 @@ async fn auction_manager_inner(
             Some(state) = state_rx.recv() => {
                 // auction process is publishing an auction state
                 // but only if an auction is supposed to be running
-                if !matches!(current_auction, NoAuction) {
-                    auction_state_tx.send_replace(state);
-                }
+                if matches!(current_auction, NoAuction) {continue;}
+
+                auction_state_tx.send_replace(state);
+            }
+            Some(state) = silent_state_rx.recv() => {
+                silent_auction_state_tx.send_replace(state);
             }
 
         }
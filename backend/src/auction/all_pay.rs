@@ -0,0 +1,298 @@
+use std::{sync::Arc, time::Duration};
+
+use communication::{
+    auction::state::{
+        ActiveBidState, AuctionItem, AuctionState, BiddingState, Sponsorship, SponsorshipStatus,
+    },
+    forget_user_secrets, Money,
+};
+use sqlx::{query, SqlitePool};
+use tokio::{
+    sync::*,
+    time::{interval, Instant},
+};
+use tracing::warn;
+
+use crate::auction::db_actions::{
+    apply_all_pay_sale, confirmation_code, get_contribution_split, get_sponsorship_state,
+    get_units_left, get_user_state, mark_reserve_not_met,
+};
+
+use super::sync_handle;
+
+#[derive(Debug)]
+pub enum AllPayAuctionEvent {
+    /// A user has raised the highest bid.
+    BidPlaced {
+        bidder_id: i64,
+        bid_amount: Money,
+        item_id: i64,
+    },
+}
+
+pub async fn run_all_pay_auction(
+    item_id: i64,
+    pool: SqlitePool,
+    rx: Arc<Mutex<mpsc::Receiver<AllPayAuctionEvent>>>,
+    state_tx: mpsc::Sender<AuctionState>,
+    sync_handle: sync_handle::AuctionSyncHandle,
+) -> anyhow::Result<()> {
+    let pool = &pool;
+    let mut rx = rx.lock().await;
+
+    // Discard any bids left over from a previous all-pay auction.
+    while rx.try_recv().is_ok() {}
+
+    let row = query!(
+        r#"
+    SELECT
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
+    FROM auction_item
+    WHERE id=?
+    "#,
+        item_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let item = AuctionItem {
+        id: row.id,
+        name: row.name,
+        initial_price: row.initial_price as Money,
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
+    };
+
+    // An all-pay auction sells a single unit.
+    if get_units_left(pool, &item).await? == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    let bidding_duration = Duration::from_secs(15);
+    let mut time_when_bidding_over = Instant::now() + Duration::from_secs(u64::MAX / 8); // initial time is basically infinite, but needs to be inside the allowable range.
+    let mut check_interval = interval(Duration::from_millis(100));
+
+    // The last bid of each member, in the order that they were placed,
+    // so the last one is the highest.
+    let mut bids: Vec<(i64, Money)> = vec![];
+
+    loop {
+        // First check if the bidding has expired
+        if time_when_bidding_over < Instant::now() {
+            settle(pool, &item, &bids, &state_tx).await?;
+            return Ok(());
+        }
+
+        tokio::select! {
+            _ = check_interval.tick() => {
+                // Construct an AuctionState and publish it
+                let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                let bid_state = BiddingState {
+                    item: item.clone(),
+                    active_bid: ActiveBidState::AllPayAuctionBid {
+                        bids: bids
+                            .iter()
+                            .rev()
+                            .filter_map(|(id, bid)| users.iter().find(|u| u.id == *id).map(|u| (u.clone(), *bid)))
+                            .collect(),
+                        minimum_bid: minimum_bid(&item, &bids),
+                        seconds_until_commit: if bids.is_empty() {
+                            bidding_duration.as_secs_f32()
+                        } else {
+                            time_when_bidding_over.duration_since(Instant::now()).as_secs_f32()
+                        },
+                        max_millis_until_commit: bidding_duration.as_millis(),
+                    },
+                    is_paused: false,
+                };
+                state_tx.send(AuctionState::Bidding(bid_state)).await?;
+            }
+            Some(event) = rx.recv() => {
+                match event {
+                    AllPayAuctionEvent::BidPlaced { bidder_id, bid_amount, item_id } => {
+                        // If we receive an event about an item that is not the one we're selling, ignore it.
+                        if item_id != item.id { continue; }
+
+                        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                        let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                        if !users.iter().any(|u| u.id == bidder_id) {
+                            warn!("Received all-pay auction bid with user ID={bidder_id}; no such user: hacking detected?");
+                            continue;
+                        }
+
+                        // The bidder will pay this whether they win or not,
+                        // so their sponsorship group must be able to pay it.
+                        let accessible_amount = Sponsorship::resolve_available_balance(bidder_id, &users, &sponsorships, Some(&item));
+                        if accessible_amount < bid_amount {
+                            warn!("Received all-pay auction bid with user ID={bidder_id} and bid_amount={bid_amount}; user only has funds {accessible_amount}: hacking detected?");
+                            continue;
+                        }
+                        let minimum_bid = minimum_bid(&item, &bids);
+                        if bid_amount < minimum_bid {
+                            warn!("Received all-pay auction bid with user ID={bidder_id} and bid_amount={bid_amount}; minimum bid is {minimum_bid}: hacking detected?");
+                            continue;
+                        }
+
+                        // The new bid replaces the member's previous one, and resets the timer.
+                        bids.retain(|(id, _)| *id != bidder_id);
+                        bids.push((bidder_id, bid_amount));
+                        time_when_bidding_over = Instant::now() + bidding_duration;
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// The smallest amount that the next bid can be: the initial price, or just above the highest bid.
+fn minimum_bid(item: &AuctionItem, bids: &[(i64, Money)]) -> Money {
+    match bids.last() {
+        None => item.initial_price,
+        Some((_, highest)) => highest.saturating_add(1),
+    }
+}
+
+/// Take the payment for their last bid from each bidder, and sell the item to the highest bidder.
+async fn settle(
+    pool: &SqlitePool,
+    item: &AuctionItem,
+    bids: &[(i64, Money)],
+    state_tx: &mpsc::Sender<AuctionState>,
+) -> anyhow::Result<()> {
+    // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+    let mut users = forget_user_secrets(get_user_state(pool).await?);
+    let mut sponsorships = get_sponsorship_state(pool).await?;
+    let split = get_contribution_split(pool).await?.record();
+
+    // Highest bid first.
+    let ranked_bids: Vec<_> = bids.iter().rev().copied().collect();
+
+    // The winner is the highest bidder whose sponsorship group can still pay their whole bid.
+    // If there is none, this returns to the item selection state, and nobody pays anything.
+    let winner = ranked_bids.iter().copied().find(|(id, amount)| {
+        users.iter().any(|u| u.id == *id)
+            && Sponsorship::resolve_available_balance(*id, &users, &sponsorships, Some(item))
+                >= *amount
+    });
+    let (winner_id, winning_bid) = match winner {
+        None => {
+            state_tx.send(AuctionState::WaitingForItem).await?;
+            return Ok(());
+        }
+        Some(w) => w,
+    };
+    if item
+        .reserve_price
+        .is_some_and(|reserve| winning_bid < reserve)
+    {
+        mark_reserve_not_met(pool, item.id).await?;
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    // The winner pays first, and then the others, from the highest bid down.
+    // Sponsorships may have changed since the bids were placed,
+    // so a bidder who did not win pays as much of their bid as their sponsorship group still can.
+    // The payments are worked out one bidder after another,
+    // so that a sponsor of several bidders is not charged more than they have.
+    let payment_order = std::iter::once((winner_id, winning_bid)).chain(
+        ranked_bids
+            .iter()
+            .copied()
+            .filter(|(id, _)| *id != winner_id),
+    );
+    let mut payments = vec![];
+    for (bidder_id, bid) in payment_order {
+        if !users.iter().any(|u| u.id == bidder_id) {
+            continue;
+        }
+        let accessible_amount =
+            Sponsorship::resolve_available_balance(bidder_id, &users, &sponsorships, Some(item));
+        let amount = bid.min(accessible_amount);
+        if amount < bid {
+            warn!("All-pay bidder {bidder_id} can only pay {amount} of their bid of {bid} anymore");
+        }
+        if amount == 0 {
+            continue;
+        }
+
+        let contributions = Sponsorship::calculate_contributions(
+            bidder_id,
+            amount,
+            &users,
+            &sponsorships,
+            item,
+            &split,
+        );
+        for (payer, amount) in contributions.iter() {
+            if let Some(u) = users.iter_mut().find(|u| u.id == payer.id) {
+                u.balance -= amount;
+            }
+            if let Some(s) = sponsorships.iter_mut().find(|s| {
+                s.status == SponsorshipStatus::Active
+                    && s.donor_id == payer.id
+                    && s.recepient_id == bidder_id
+            }) {
+                s.balance_remaining = s.balance_remaining.saturating_sub(*amount);
+            }
+        }
+        payments.push((bidder_id, contributions));
+    }
+
+    let payment_ids: Vec<_> = payments
+        .iter()
+        .map(|(id, contributions)| (*id, contributions.iter().map(|(u, b)| (u.id, *b)).collect()))
+        .collect();
+    apply_all_pay_sale(pool, item.id, winner_id, &payment_ids, split).await?;
+
+    // Everyone who paid is shown as a contributor to the sale.
+    let mut contributions: Vec<(_, Money)> = vec![];
+    for (payer, amount) in payments.into_iter().flat_map(|(_, c)| c) {
+        match contributions.iter_mut().find(|(u, _)| *u == payer.id) {
+            Some((_, total)) => *total += amount,
+            None => contributions.push((payer.id, amount)),
+        }
+    }
+    let users = forget_user_secrets(get_user_state(pool).await?);
+    let contributions: Vec<_> = contributions
+        .into_iter()
+        .filter_map(|(id, amount)| {
+            users
+                .iter()
+                .find(|u| u.id == id)
+                .map(|u| (u.clone(), amount))
+        })
+        .collect();
+    let sold_for = contributions.iter().map(|(_, amount)| amount).sum();
+
+    let confirmation_code = confirmation_code();
+
+    let sold_to = users
+        .iter()
+        .find(|u| u.id == winner_id)
+        .expect("Winner not in user list?")
+        .clone();
+    let state = AuctionState::SoldToMember {
+        item: item.clone(),
+        sold_for,
+        sold_to,
+        confirmation_code,
+        contributions,
+        revealed_bids: Some(
+            ranked_bids
+                .iter()
+                .filter_map(|(id, bid)| {
+                    users
+                        .iter()
+                        .find(|u| u.id == *id)
+                        .map(|u| (u.clone(), *bid))
+                })
+                .collect(),
+        ),
+        other_units: vec![],
+    };
+    state_tx.send(state).await?;
+    Ok(())
+}
@@ -1,6 +1,17 @@
-use communication::{auction::state::SponsorshipStatus, Money, UserSaleMode};
+use std::time::Duration;
 
-use super::{EnglishAuctionEvent, JapaneseAuctionEvent};
+use communication::{
+    auction::state::{
+        ContributionSplit, JapaneseAuctionSettings, RerunPolicy, SealedBidPricing, SoftClosePolicy,
+        SponsorshipScope, SponsorshipStatus,
+    },
+    Money, UserSaleMode,
+};
+
+use super::{
+    AllPayAuctionEvent, DutchAuctionEvent, EnglishAuctionEvent, JapaneseAuctionEvent, RaffleEvent,
+    SealedBidAuctionEvent, SilentAuctionEvent,
+};
 
 /// Represents events that can change the progress of the auction.
 #[derive(Debug)]
@@ -11,11 +22,53 @@ pub enum AuctionEvent {
     /// An admin has requested that an item be selected for auctioning.
     PrepareAuctioning(i64),
 
-    /// An admin has requested that an English auction be used to sell the given item.
-    RunEnglishAuction(i64),
+    /// An admin has requested that an English auction be used to sell the given item,
+    /// with the given soft-close policy, and auctioned again according to the re-run policy if it is not sold.
+    /// If an initial price is given, it is used instead of the item's own, as when the item is auctioned again.
+    RunEnglishAuction {
+        item_id: i64,
+        soft_close_policy: SoftClosePolicy,
+        initial_price: Option<Money>,
+        rerun: Option<RerunPolicy>,
+    },
+
+    /// An admin has requested that a Japanese auction be used to sell the given item,
+    /// with its clock running according to the given settings,
+    /// and auctioned again according to the re-run policy if it is not sold.
+    /// If an initial price is given, it is used instead of the item's own, as when the item is auctioned again.
+    RunJapaneseAuction {
+        item_id: i64,
+        settings: JapaneseAuctionSettings,
+        initial_price: Option<Money>,
+        rerun: Option<RerunPolicy>,
+    },
+
+    /// An admin has requested that a Dutch auction be used to sell the given item,
+    /// with the clock starting at the given price.
+    RunDutchAuction { item_id: i64, starting_price: Money },
+
+    /// An admin has requested that a sealed-bid auction be used to sell the given item,
+    /// with the winner paying according to the given pricing rule.
+    RunSealedBidAuction {
+        item_id: i64,
+        pricing: SealedBidPricing,
+    },
+
+    /// An admin has requested that the given item be raffled,
+    /// with tickets at the given price on sale for the given time.
+    RunRaffle {
+        item_id: i64,
+        ticket_price: Money,
+        entry_window: Duration,
+    },
 
-    /// An admin has requested that a Japanese auction be used to sell the given item.
-    RunJapaneseAuction(i64),
+    /// An admin has requested that an all-pay auction be used to sell the given item.
+    RunAllPayAuction(i64),
+
+    /// The running English or Japanese auction ended without selling the given item,
+    /// because nobody bid on it, or its arena closed empty.
+    /// The item is auctioned again if it has a re-run policy.
+    ItemGotNoBids(i64),
 
     /// A user has done an action on the English auction.
     EnglishAuctionAction(EnglishAuctionEvent),
@@ -23,6 +76,30 @@ pub enum AuctionEvent {
     /// A user has entered or exited the Japanese auction's arena.
     JapaneseAuctionAction(JapaneseAuctionEvent),
 
+    /// A user has claimed the item in the Dutch auction, or the admin has changed its clock.
+    DutchAuctionAction(DutchAuctionEvent),
+
+    /// A user has submitted a sealed bid, or the admin has closed the bidding.
+    SealedBidAuctionAction(SealedBidAuctionEvent),
+
+    /// A user has bought raffle tickets, or the admin has closed the entries.
+    RaffleAction(RaffleEvent),
+
+    /// A user has bid in the all-pay auction.
+    AllPayAuctionAction(AllPayAuctionEvent),
+
+    /// A user has bid in the silent auction, or the admin has opened, closed or removed one of its items.
+    SilentAuctionAction(SilentAuctionEvent),
+
+    /// An admin has requested that the running English or Japanese auction be paused.
+    PauseAuction,
+
+    /// An admin has requested that the paused English or Japanese auction be resumed.
+    ResumeAuction,
+
+    /// An admin has chosen how the price of each sale from now on is split between the buyer and their sponsors.
+    SetContributionSplit(ContributionSplit),
+
     /// An admin has requested entering the "auction over" state
     FinishAuction,
 
@@ -54,6 +131,30 @@ pub enum AuctionEvent {
         initial_price: Option<Money>,
     },
 
+    /// An admin has requested that an item's reserve price be set, or removed if None.
+    SetItemReservePrice {
+        id: i64,
+        reserve_price: Option<Money>,
+    },
+
+    /// An admin has requested that an item's buy-now price be set, or removed if None.
+    SetItemBuyNowPrice {
+        id: i64,
+        buy_now_price: Option<Money>,
+    },
+
+    /// An admin has requested that the given items be grouped into a new lot with this name.
+    CreateLot { name: String, item_ids: Vec<i64> },
+
+    /// An admin has requested that an item's quantity (number of units) be changed.
+    SetItemQuantity { id: i64, quantity: u32 },
+
+    /// An admin has requested that an item's category be set, or removed if None.
+    SetItemCategory { id: i64, category: Option<String> },
+
+    /// A user wants to buy the given item immediately at its buy-now price.
+    BuyNow { user_id: i64, item_id: i64 },
+
     /// Change the value in the holding account in relation to a user account:
     /// either add or subtract the balance there,
     /// so that the user has the given amount of money,
@@ -78,6 +179,12 @@ pub enum AuctionEvent {
         sponsorship_id: i64,
         new_status: Option<SponsorshipStatus>,
         new_amount: Option<Money>,
+
+        /// If set, the new percentage cap, which may be removed with `Some(None)`.
+        new_max_percent: Option<Option<u32>>,
+
+        /// If set, the new items and categories that the sponsorship is limited to.
+        new_only_for: Option<Vec<SponsorshipScope>>,
     },
 
     /// A user, if has sponsorships turned on, is requesting a new code for joining the sponsorship.
@@ -86,4 +193,29 @@ pub enum AuctionEvent {
     /// A user is trying to create a sponsorship in which they are the donor, using the given code.
     /// If the code doesn't exist, nothing happens.
     TryActivateSponsorshipCode { user_id: i64, code: String },
+
+    /// A user is asking another user to sponsor them with the given amount.
+    /// This creates a pending sponsorship, replacing any earlier request from the user to the same donor.
+    RequestSponsorship {
+        user_id: i64,
+        donor_id: i64,
+        amount: Money,
+    },
+}
+
+impl AuctionEvent {
+    /// The item that this event shows before auctioning it, or starts an auction of,
+    /// if it is one of the events that do.
+    pub fn item_to_auction(&self) -> Option<i64> {
+        match self {
+            AuctionEvent::PrepareAuctioning(item_id)
+            | AuctionEvent::RunEnglishAuction { item_id, .. }
+            | AuctionEvent::RunJapaneseAuction { item_id, .. }
+            | AuctionEvent::RunDutchAuction { item_id, .. }
+            | AuctionEvent::RunSealedBidAuction { item_id, .. }
+            | AuctionEvent::RunRaffle { item_id, .. }
+            | AuctionEvent::RunAllPayAuction(item_id) => Some(*item_id),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,84 @@
+use communication::{
+    auction::state::{AuctionItem, Sponsorship, UnitSale},
+    forget_user_secrets,
+};
+use sqlx::{query, SqlitePool};
+use tracing::warn;
+
+use crate::auction::db_actions::{
+    apply_contributions, confirmation_code, get_contribution_split, get_sponsorship_state,
+    get_units_left, get_user_state,
+};
+
+/// Try to sell the item to the given member at its buy-now price.
+///
+/// If the sale went through, returns the sale of the unit.
+/// Returns None if the item has no buy-now price, has no units left, belongs to a lot,
+/// or the member's sponsorship group cannot pay for it.
+///
+/// The caller must make sure that this is not run concurrently with another sale of the same item.
+pub async fn try_buy_now(
+    pool: &SqlitePool,
+    item: &AuctionItem,
+    buyer_id: i64,
+) -> anyhow::Result<Option<UnitSale>> {
+    let price = match item.buy_now_price {
+        None => return Ok(None),
+        Some(p) => p,
+    };
+
+    // An item that belongs to a lot is only ever sold as part of the lot.
+    let lot_id = query!("SELECT lot_id FROM auction_item WHERE id=?", item.id)
+        .fetch_one(pool)
+        .await?
+        .lot_id;
+    if let Some(lot_id) = lot_id {
+        warn!(
+            "Received buy-now request for item {}, which belongs to lot {lot_id}; ignoring",
+            item.id
+        );
+        return Ok(None);
+    }
+
+    // If every unit of this item has already been bought, it cannot be bought again.
+    if get_units_left(pool, item).await? == 0 {
+        return Ok(None);
+    }
+
+    // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+    let users = forget_user_secrets(get_user_state(pool).await?);
+    let sponsorships = get_sponsorship_state(pool).await?;
+
+    let buyer = match users.iter().find(|u| u.id == buyer_id) {
+        None => {
+            warn!(
+                "Received buy-now request with user ID={buyer_id}; no such user: hacking detected?"
+            );
+            return Ok(None);
+        }
+        Some(u) => u.clone(),
+    };
+
+    let accessible_amount =
+        Sponsorship::resolve_available_balance(buyer_id, &users, &sponsorships, Some(item));
+    if accessible_amount < price {
+        warn!("Received buy-now request with user ID={buyer_id} and price={price}; user only has funds {accessible_amount}: hacking detected?");
+        return Ok(None);
+    }
+
+    let split = get_contribution_split(pool).await?.record();
+    let contributions =
+        Sponsorship::calculate_contributions(buyer_id, price, &users, &sponsorships, item, &split);
+    let contributions_ids: Vec<_> = contributions.iter().map(|(u, b)| (u.id, *b)).collect();
+
+    apply_contributions(pool, item.id, buyer_id, &contributions_ids, split).await?;
+
+    let confirmation_code = confirmation_code();
+
+    Ok(Some(UnitSale {
+        sold_to: buyer,
+        sold_for: price,
+        confirmation_code,
+        contributions,
+    }))
+}
@@ -1,8 +1,20 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use communication::{
-    auction::state::{AuctionItem, Sponsorship, SponsorshipStatus},
-    ItemState, ItemStateValue, Money, UserAccountData, UserAccountDataWithSecrets,
+    auction::state::{
+        AuctionItem, ContributionSplit, ContributionSplitRecord, RaffleDraw, Sponsorship,
+        SponsorshipLogEntry, SponsorshipLogEvent, SponsorshipScope, SponsorshipStatus,
+        TieBreakRecord, UnitSale,
+    },
+    forget_user_secrets, ItemSale, ItemState, ItemStateValue, Money, UserAccountData,
+    UserAccountDataWithSecrets,
 };
-use sqlx::{query, SqlitePool};
+use rand::Rng;
+use sqlx::{query, Sqlite, SqlitePool, Transaction};
+use tracing::warn;
 
 pub async fn get_user_state(pool: &SqlitePool) -> anyhow::Result<Vec<UserAccountDataWithSecrets>> {
     let user_rows = query!("SELECT * FROM auction_user").fetch_all(pool).await?;
@@ -20,7 +32,123 @@ pub async fn get_user_state(pool: &SqlitePool) -> anyhow::Result<Vec<UserAccount
     Ok(user_data)
 }
 
+/// Get the history of all the sponsorships, oldest first.
+pub async fn get_sponsorship_log(pool: &SqlitePool) -> anyhow::Result<Vec<SponsorshipLogEntry>> {
+    let rows = query!(
+        r#"
+        SELECT
+            sponsorship_log.sponsorship_id, sponsorship_log.kind, sponsorship_log.amount, sponsorship_log.item_name, sponsorship_log.happened_at_ms,
+            sponsorship.donor_id, sponsorship.recepient_id
+        FROM sponsorship_log
+        INNER JOIN sponsorship ON sponsorship.id = sponsorship_log.sponsorship_id
+        ORDER BY sponsorship_log.id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut log = vec![];
+    for row in rows {
+        let amount = row.amount.map(|a| a as Money);
+        match SponsorshipLogEvent::from_db_vals(row.kind as u8, amount, row.item_name) {
+            None => warn!(
+                "Sponsorship log entry for sponsorship {} has invalid kind {}; skipping",
+                row.sponsorship_id, row.kind
+            ),
+            Some(event) => log.push(SponsorshipLogEntry {
+                sponsorship_id: row.sponsorship_id,
+                donor_id: row.donor_id,
+                recepient_id: row.recepient_id,
+                event,
+                happened_at_ms: row.happened_at_ms,
+            }),
+        }
+    }
+    Ok(log)
+}
+
+/// Record in the history of the sponsorship that the event happened to it just now.
+pub async fn log_sponsorship_event(
+    tx: &mut Transaction<'_, Sqlite>,
+    sponsorship_id: i64,
+    event: &SponsorshipLogEvent,
+) -> anyhow::Result<()> {
+    let kind = event.to_db_val();
+    let amount = event.amount();
+    let item_name = match event {
+        SponsorshipLogEvent::UsedFor { item_name, .. } => Some(item_name.as_str()),
+        _ => None,
+    };
+    let happened_at_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+    query!(
+        "INSERT INTO sponsorship_log (sponsorship_id, kind, amount, item_name, happened_at_ms) VALUES (?,?,?,?,?)",
+        sponsorship_id,
+        kind,
+        amount,
+        item_name,
+        happened_at_ms
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
+/// Change the status of all the sponsorships from the donor to the recepient that have the status `from`,
+/// and record the change in their history.
+pub async fn replace_sponsorship_status(
+    tx: &mut Transaction<'_, Sqlite>,
+    donor_id: i64,
+    recepient_id: i64,
+    from: SponsorshipStatus,
+    to: SponsorshipStatus,
+) -> anyhow::Result<()> {
+    let from_val = from.to_db_val();
+    let to_val = to.to_db_val();
+    let rows = query!(
+        "SELECT id, remaining_balance FROM sponsorship WHERE status=? AND donor_id=? AND recepient_id=?",
+        from_val,
+        donor_id,
+        recepient_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    for row in rows {
+        query!("UPDATE sponsorship SET status=? WHERE id=?", to_val, row.id)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(event) = status_change_event(to, row.remaining_balance as Money) {
+            log_sponsorship_event(tx, row.id, &event).await?;
+        }
+    }
+    Ok(())
+}
+
+/// The event to record in the history of a sponsorship when it changes to the given status,
+/// if the change is worth recording.
+pub fn status_change_event(to: SponsorshipStatus, balance: Money) -> Option<SponsorshipLogEvent> {
+    match to {
+        SponsorshipStatus::Pending => None,
+        SponsorshipStatus::Active => Some(SponsorshipLogEvent::Created { balance }),
+        SponsorshipStatus::Rejected => Some(SponsorshipLogEvent::Rejected),
+        SponsorshipStatus::Retracted => Some(SponsorshipLogEvent::Retracted),
+    }
+}
+
 pub async fn get_sponsorship_state(pool: &SqlitePool) -> anyhow::Result<Vec<Sponsorship>> {
+    // Collect the items and categories that each sponsorship is limited to.
+    let scope_rows = query!("SELECT * FROM sponsorship_scope ORDER BY rowid")
+        .fetch_all(pool)
+        .await?;
+    let mut scopes: HashMap<i64, Vec<SponsorshipScope>> = HashMap::new();
+    for row in scope_rows {
+        let scope = match (row.item_id, row.category) {
+            (Some(item_id), _) => SponsorshipScope::Item(item_id),
+            (None, Some(category)) => SponsorshipScope::Category(category),
+            (None, None) => continue,
+        };
+        scopes.entry(row.sponsorship_id).or_default().push(scope);
+    }
+
     let sponsorship_rows = query!("SELECT * FROM sponsorship").fetch_all(pool).await?;
     Ok(sponsorship_rows
         .iter()
@@ -30,28 +158,66 @@ pub async fn get_sponsorship_state(pool: &SqlitePool) -> anyhow::Result<Vec<Spon
             recepient_id: row.recepient_id,
             status: row.status.into(),
             balance_remaining: row.remaining_balance as Money,
+            max_percent: row.max_percent.map(|p| p as u32),
+            only_for: scopes.remove(&row.id).unwrap_or_default(),
         })
         .collect::<Vec<_>>())
 }
 
 pub async fn get_item_state(pool: &SqlitePool) -> anyhow::Result<Vec<ItemState>> {
+    // In an all-pay auction or a raffle, members who did not win also pay toward the sale:
+    // collect how much each of them paid, by sale.
+    let payment_rows = query!(r#"
+        SELECT
+            sale_contribution.sale_id, sale_contribution.bidder_id AS "bidder_id!", SUM(sale_contribution.amount) AS "amount!: i64", auction_user.name AS username, auction_user.balance, auction_user.sale_mode, auction_user.sponsorship_code
+        FROM sale_contribution
+        INNER JOIN auction_item_sale ON auction_item_sale.id = sale_contribution.sale_id
+        INNER JOIN auction_user ON auction_user.id = sale_contribution.bidder_id
+        WHERE sale_contribution.bidder_id != auction_item_sale.buyer_id
+        GROUP BY sale_contribution.sale_id, sale_contribution.bidder_id
+        ORDER BY sale_contribution.sale_id, sale_contribution.bidder_id
+        "#).fetch_all(pool).await?;
+    let mut other_payments: HashMap<i64, Vec<(UserAccountData, Money)>> = HashMap::new();
+    for row in payment_rows {
+        other_payments.entry(row.sale_id).or_default().push((
+            UserAccountData {
+                id: row.bidder_id,
+                user_name: row.username,
+                balance: row.balance as Money,
+                sale_mode: row.sale_mode.into(),
+                is_accepting_sponsorships: row.sponsorship_code.is_some(),
+            },
+            row.amount as Money,
+        ));
+    }
+
     let item_rows = query!(r#"
         SELECT
-            auction_item.id, auction_item.name, auction_item.initial_price, auction_item_sale.buyer_id, auction_item_sale.sale_price, auction_user.name AS username, auction_user.balance, auction_user.sale_mode, auction_user.sponsorship_code
+            auction_item_sale.id AS "sale_id?", auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.reserve_not_met, auction_item.buy_now_price, auction_item.quantity, auction_item.category, auction_item.lot_id, auction_item_sale.buyer_id, auction_item_sale.sale_price, auction_item_sale.tie_break, auction_item_sale.tie_break_seed, auction_item_sale.contribution_split, auction_item_sale.contribution_split_seed, auction_user.name AS username, auction_user.balance, auction_user.sale_mode, auction_user.sponsorship_code
         FROM auction_item
         LEFT OUTER JOIN auction_item_sale ON auction_item_sale.item_id = auction_item.id
         LEFT OUTER JOIN auction_user ON auction_item_sale.buyer_id = auction_user.id
+        ORDER BY auction_item.id, auction_item_sale.id
         "#).fetch_all(pool).await?;
-    let mut item_data = vec![];
+
+    // There is one row per sale, so first collect all the sales of each item.
+    let mut items: Vec<(AuctionItem, Option<i64>, bool, Vec<ItemSale>)> = vec![];
     for row in item_rows {
-        let item = AuctionItem {
-            id: row.id,
-            name: row.name,
-            initial_price: row.initial_price as Money,
-        };
-        let state = match row.buyer_id {
-            None => ItemStateValue::Sellable,
-            Some(id) => ItemStateValue::AlreadySold {
+        if items.last().map(|(item, ..)| item.id) != Some(row.id) {
+            let item = AuctionItem {
+                id: row.id,
+                name: row.name,
+                initial_price: row.initial_price as Money,
+                reserve_price: row.reserve_price.map(|p| p as Money),
+                buy_now_price: row.buy_now_price.map(|p| p as Money),
+                quantity: row.quantity as u32,
+                category: row.category,
+            };
+            items.push((item, row.lot_id, row.reserve_not_met != 0, vec![]));
+        }
+        if let Some(id) = row.buyer_id {
+            let (.., sales) = items.last_mut().unwrap();
+            sales.push(ItemSale {
                 buyer: UserAccountData {
                     id,
                     user_name: row.username,
@@ -60,16 +226,347 @@ pub async fn get_item_state(pool: &SqlitePool) -> anyhow::Result<Vec<ItemState>>
                     is_accepting_sponsorships: row.sponsorship_code.is_some(),
                 },
                 sale_price: row.sale_price.unwrap() as Money,
-            },
+                other_payments: row
+                    .sale_id
+                    .and_then(|id| other_payments.remove(&id))
+                    .unwrap_or_default(),
+                tie_break: row.tie_break.map(|policy| TieBreakRecord {
+                    policy: policy.into(),
+                    seed: row.tie_break_seed.map(|seed| seed as u64),
+                }),
+                contribution_split: row.contribution_split.map(|split| ContributionSplitRecord {
+                    split: split.into(),
+                    seed: row.contribution_split_seed.map(|seed| seed as u64),
+                }),
+            });
+        }
+    }
+
+    // The items in a lot are sold together with the lot, and each has a share of its sale price.
+    let share_rows = query!(r#"
+        SELECT
+            lot_sale_share.item_id, lot_sale_share.amount, auction_item_sale.buyer_id, auction_user.name AS username, auction_user.balance, auction_user.sale_mode, auction_user.sponsorship_code
+        FROM lot_sale_share
+        INNER JOIN auction_item_sale ON auction_item_sale.id = lot_sale_share.sale_id
+        INNER JOIN auction_user ON auction_item_sale.buyer_id = auction_user.id
+        ORDER BY lot_sale_share.sale_id
+        "#).fetch_all(pool).await?;
+    for row in share_rows {
+        if let Some((.., sales)) = items.iter_mut().find(|(item, ..)| item.id == row.item_id) {
+            sales.push(ItemSale {
+                buyer: UserAccountData {
+                    id: row.buyer_id,
+                    user_name: row.username,
+                    balance: row.balance as Money,
+                    sale_mode: row.sale_mode.into(),
+                    is_accepting_sponsorships: row.sponsorship_code.is_some(),
+                },
+                sale_price: row.amount as Money,
+                other_payments: vec![],
+                tie_break: None,
+                contribution_split: None,
+            });
+        }
+    }
+
+    let mut item_data = vec![];
+    for (item, lot_id, reserve_not_met, sales) in items {
+        let state = if sales.len() as u32 >= item.quantity {
+            ItemStateValue::AlreadySold { sales }
+        } else if !sales.is_empty() {
+            ItemStateValue::PartiallySold { sales }
+        } else if reserve_not_met {
+            ItemStateValue::ReserveNotMet
+        } else {
+            ItemStateValue::Sellable
         };
-        item_data.push(ItemState { item, state });
+        item_data.push(ItemState {
+            item,
+            state,
+            lot_id,
+        });
     }
     Ok(item_data)
 }
 
+/// Get how the admin has chosen to split the price of each sale between the buyer and their sponsors.
+pub async fn get_contribution_split(pool: &SqlitePool) -> anyhow::Result<ContributionSplit> {
+    let split = query!("SELECT value FROM kv_data_int WHERE key='contribution_split'")
+        .fetch_optional(pool)
+        .await?;
+    Ok(split.map(|row| row.value.into()).unwrap_or_default())
+}
+
+/// Get how many units of the item have not been sold yet.
+pub async fn get_units_left(pool: &SqlitePool, item: &AuctionItem) -> anyhow::Result<u32> {
+    let units_sold = query!(
+        "SELECT COUNT(*) AS units_sold FROM auction_item_sale WHERE item_id=?",
+        item.id
+    )
+    .fetch_one(pool)
+    .await?
+    .units_sold;
+    Ok(item.quantity.saturating_sub(units_sold as u32))
+}
+
+/// Record that the item was auctioned, but the bidding did not reach its reserve price.
+pub async fn mark_reserve_not_met(pool: &SqlitePool, item_id: i64) -> anyhow::Result<()> {
+    query!(
+        "UPDATE auction_item SET reserve_not_met=1 WHERE id=?",
+        item_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Split the sale price of a lot across its items, in proportion to the given weights
+/// (or equally, if they are all zero).
+/// The parts always add up to the price: the remainder of the rounding goes to the first items.
+fn split_lot_price(price: Money, weights: &[Money]) -> Vec<Money> {
+    let total_weight: u64 = weights.iter().map(|w| *w as u64).sum();
+    let mut shares: Vec<Money> = if total_weight == 0 {
+        vec![price / weights.len().max(1) as Money; weights.len()]
+    } else {
+        weights
+            .iter()
+            .map(|w| (price as u64 * *w as u64 / total_weight) as Money)
+            .collect()
+    };
+    let mut remainder = price - shares.iter().sum::<Money>();
+    for share in shares.iter_mut() {
+        if remainder == 0 {
+            break;
+        }
+        *share += 1;
+        remainder -= 1;
+    }
+    shares
+}
+
+/// Create a lot, which is an item that groups the given items, so that they are auctioned together.
+/// The lot's initial price starts as the sum of its items' initial prices.
+///
+/// Items that do not exist, are already in a lot, are lots themselves, or have been sold are left out,
+/// and if fewer than two items are left, no lot is created.
+pub async fn create_lot(pool: &SqlitePool, name: &str, item_ids: &[i64]) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let mut lot_item_ids = vec![];
+    let mut initial_price: Money = 0;
+    for id in item_ids {
+        let row = query!(
+            r#"
+        SELECT
+            auction_item.initial_price, auction_item.lot_id,
+            (SELECT COUNT(*) FROM auction_item AS lot_item WHERE lot_item.lot_id = auction_item.id) AS "lot_item_count!: i64",
+            (SELECT COUNT(*) FROM auction_item_sale WHERE auction_item_sale.item_id = auction_item.id) AS "sale_count!: i64"
+        FROM auction_item
+        WHERE id=?
+        "#,
+            id
+        )
+        .fetch_optional(&mut tx)
+        .await?;
+        match row {
+            Some(row) if row.lot_id.is_none() && row.lot_item_count == 0 && row.sale_count == 0 => {
+                if !lot_item_ids.contains(id) {
+                    lot_item_ids.push(*id);
+                    initial_price = initial_price.saturating_add(row.initial_price as Money);
+                }
+            }
+            _ => warn!("Item {id} cannot be put into a lot; leaving it out"),
+        }
+    }
+    if lot_item_ids.len() < 2 {
+        warn!("A lot needs at least two items; not creating it");
+        return Ok(());
+    }
+
+    let name = name.trim();
+    let name = if name.is_empty() { "Unnamed Lot" } else { name };
+    let lot_id = query!(
+        "INSERT INTO auction_item (name, initial_price) VALUES (?,?)",
+        name,
+        initial_price
+    )
+    .execute(&mut tx)
+    .await?
+    .last_insert_rowid();
+    for id in lot_item_ids {
+        query!("UPDATE auction_item SET lot_id=? WHERE id=?", lot_id, id)
+            .execute(&mut tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Create a sale record for one unit of the item, for the given price, split between the payers in the given way,
+/// and if the item is a lot, record the share of the price of each item in it.
+///
+/// Returns the ID of the sale record.
+async fn record_sale(
+    tx: &mut Transaction<'_, Sqlite>,
+    item_id: i64,
+    buyer_id: i64,
+    sale_price: Money,
+    split: ContributionSplitRecord,
+) -> anyhow::Result<i64> {
+    let split_val = split.split.to_db_val();
+    let split_seed = split.seed.map(|seed| seed as i64);
+    let sale_id = query!(
+        "INSERT INTO auction_item_sale (item_id, buyer_id, sale_price, contribution_split, contribution_split_seed) VALUES (?,?,?,?,?)",
+        item_id,
+        buyer_id,
+        sale_price,
+        split_val,
+        split_seed
+    )
+    .execute(&mut *tx)
+    .await?
+    .last_insert_rowid();
+
+    // If the item is a lot, record how the sale price is split across the items in it.
+    let lot_items = query!(
+        "SELECT id, initial_price FROM auction_item WHERE lot_id=? ORDER BY id",
+        item_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let weights: Vec<_> = lot_items.iter().map(|i| i.initial_price as Money).collect();
+    for (lot_item, share) in lot_items.iter().zip(split_lot_price(sale_price, &weights)) {
+        query!(
+            "INSERT INTO lot_sale_share (sale_id, item_id, amount) VALUES (?,?,?)",
+            sale_id,
+            lot_item.id,
+            share
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    Ok(sale_id)
+}
+
+/// Add a contribution record to the sale, paying for the bid of the given recepient,
+/// and subtract the contributed amount from the payer's balance,
+/// and from their sponsorship of the recepient, if it exists.
+async fn record_contribution(
+    tx: &mut Transaction<'_, Sqlite>,
+    sale_id: i64,
+    recepient_id: i64,
+    payer_id: i64,
+    amount: Money,
+) -> anyhow::Result<()> {
+    // TODO: If the contribution is zero, ignore it?
+    // or keep it around as evidence of who took part?
+
+    // Create contribution
+    query!(
+        "INSERT INTO sale_contribution (sale_id, user_id, amount, bidder_id) VALUES (?,?,?,?)",
+        sale_id,
+        payer_id,
+        amount,
+        recepient_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Deduct amount
+    query!(
+        "UPDATE auction_user SET balance=balance-? WHERE id=?",
+        amount,
+        payer_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // Deduct amount from sponsorship, if the sponsorship exists.
+    let active = SponsorshipStatus::Active.to_db_val();
+    query!("UPDATE sponsorship SET remaining_balance=remaining_balance-? WHERE status=? AND recepient_id=? AND donor_id=?",
+        amount, active, recepient_id, payer_id).execute(&mut *tx).await?;
+
+    // Record in the sponsorship's history what it was used for, so that the donor can see where their money went.
+    if payer_id != recepient_id && amount > 0 {
+        let item_name = query!(
+            "SELECT auction_item.name FROM auction_item_sale INNER JOIN auction_item ON auction_item.id = auction_item_sale.item_id WHERE auction_item_sale.id=?",
+            sale_id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .name;
+        let sponsorships = query!(
+            "SELECT id FROM sponsorship WHERE status=? AND recepient_id=? AND donor_id=?",
+            active,
+            recepient_id,
+            payer_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for s in sponsorships {
+            let event = SponsorshipLogEvent::UsedFor {
+                item_name: item_name.clone(),
+                amount,
+            };
+            log_sponsorship_event(tx, s.id, &event).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Create a sale record for one unit of the item, to the winner, for the total of the payments,
+/// and record each member's payment, which may have been for a bid that did not win.
+///
+/// `payments` holds, for each member who pays, the contributions to their payment from their sponsorship group,
+/// which were split in the given way.
+/// Returns the ID of the sale record.
+async fn record_payments(
+    tx: &mut Transaction<'_, Sqlite>,
+    item_id: i64,
+    winner_id: i64,
+    payments: &[(i64, Vec<(i64, Money)>)],
+    split: ContributionSplitRecord,
+) -> anyhow::Result<i64> {
+    let total_amount: Money = payments
+        .iter()
+        .flat_map(|(_, contributions)| contributions.iter().map(|(_, b)| b))
+        .sum();
+    let sale_id = record_sale(tx, item_id, winner_id, total_amount, split).await?;
+
+    for (bidder_id, contributions) in payments.iter() {
+        for (uid, amt) in contributions.iter() {
+            record_contribution(tx, sale_id, *bidder_id, *uid, *amt).await?;
+        }
+    }
+
+    Ok(sale_id)
+}
+
+/// Record on the sale how the tie that decided it was broken.
+async fn record_tie_break(
+    tx: &mut Transaction<'_, Sqlite>,
+    sale_id: i64,
+    tie_break: TieBreakRecord,
+) -> anyhow::Result<()> {
+    let policy = tie_break.policy.to_db_val();
+    let seed = tie_break.seed.map(|seed| seed as i64);
+    query!(
+        "UPDATE auction_item_sale SET tie_break=?, tie_break_seed=? WHERE id=?",
+        policy,
+        seed,
+        sale_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    Ok(())
+}
+
 /// Transactionally apply an item sale:
 ///
-/// - create a sale record for the item
+/// - create a sale record for one unit of the item, with how its price was split
+/// - if the item is a lot, record the share of the price of each item in it
 /// - from each user's balance, subtract the contributed amount
 /// - add a contribution record for each user
 ///
@@ -80,51 +577,290 @@ pub async fn apply_contributions(
     item_id: i64,
     buyer_id: i64,
     contributions: &[(i64, Money)],
+    split: ContributionSplitRecord,
 ) -> anyhow::Result<()> {
     let mut tx = pool.begin().await?;
 
     // Create a sale record
     let total_amount: Money = contributions.iter().map(|(_, b)| b).sum();
-    query!(
-        "INSERT INTO auction_item_sale (item_id, buyer_id, sale_price) VALUES (?,?,?)",
-        item_id,
-        buyer_id,
-        total_amount
-    )
-    .execute(&mut tx)
-    .await?;
+    let sale_id = record_sale(&mut tx, item_id, buyer_id, total_amount, split).await?;
 
     // To that sale record, add the contributions and subtract the amounts.
     for (uid, amt) in contributions.iter() {
-        // TODO: If the contribution is zero, ignore it?
-        // or keep it around as evidence of who took part?
+        record_contribution(&mut tx, sale_id, buyer_id, *uid, *amt).await?;
+    }
+
+    tx.commit().await?;
 
-        // Create contribution
+    Ok(())
+}
+
+/// Transactionally apply the sale of a raffled item:
+///
+/// - create a sale record for one unit of the item, to the winner, for the total price of all the tickets
+/// - record the seed of the draw, the ticket price, and how many tickets each entrant had,
+///   so that the draw can be repeated to check it
+/// - for each entrant, take their payment for the tickets out of their own sponsorship group
+///
+/// `payments` holds, for each entrant, the contributions to their tickets' price, split in the given way.
+/// The balances must have been checked previously, like with [`apply_contributions`].
+pub async fn apply_raffle_sale(
+    pool: &SqlitePool,
+    item_id: i64,
+    winner_id: i64,
+    draw: &RaffleDraw,
+    payments: &[(i64, Vec<(i64, Money)>)],
+    split: ContributionSplitRecord,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let sale_id = record_payments(&mut tx, item_id, winner_id, payments, split).await?;
+
+    let seed = draw.seed as i64;
+    query!(
+        "INSERT INTO raffle_draw (sale_id, seed, ticket_price) VALUES (?,?,?)",
+        sale_id,
+        seed,
+        draw.ticket_price
+    )
+    .execute(&mut tx)
+    .await?;
+    for (user_id, tickets) in draw.entries.iter() {
         query!(
-            "INSERT INTO sale_contribution (sale_id, user_id, amount) VALUES (?,?,?)",
-            item_id,
-            uid,
-            amt
+            "INSERT INTO raffle_entry (sale_id, user_id, tickets) VALUES (?,?,?)",
+            sale_id,
+            user_id,
+            tickets
         )
         .execute(&mut tx)
         .await?;
+    }
 
-        // Deduct amount
-        query!(
-            "UPDATE auction_user SET balance=balance-? WHERE id=?",
-            amt,
-            uid
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Transactionally apply the sale of an item in an all-pay auction:
+///
+/// - create a sale record for one unit of the item, to the winner, for the total of all the payments
+/// - for each bidder, take their payment for their last bid out of their own sponsorship group
+///
+/// `payments` holds, for each bidder, the contributions to their payment, split in the given way.
+/// The balances must have been checked previously, like with [`apply_contributions`].
+pub async fn apply_all_pay_sale(
+    pool: &SqlitePool,
+    item_id: i64,
+    winner_id: i64,
+    payments: &[(i64, Vec<(i64, Money)>)],
+    split: ContributionSplitRecord,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    record_payments(&mut tx, item_id, winner_id, payments, split).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// A random 4-digit code, which the buyer shows to the auctioneer to collect the item.
+pub fn confirmation_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..4).map(|_| rng.gen_range(0..=9).to_string()).collect()
+}
+
+/// Sell one unit of the item to each of the winners, in order, for the price of their bid.
+///
+/// The latest balances and sponsorships are fetched before each unit is sold,
+/// and a winner who cannot afford their price anymore is skipped.
+/// A winner whose price is below the item's reserve price is also skipped,
+/// and if no unit was sold because of that, this is recorded.
+/// If the winners were decided by breaking a tie, this is recorded with each of their sales.
+///
+/// Returns the sales of the units that were sold.
+pub async fn sell_units(
+    pool: &SqlitePool,
+    item: &AuctionItem,
+    winners: &[(i64, Money)],
+    tie_break: Option<TieBreakRecord>,
+) -> anyhow::Result<Vec<UnitSale>> {
+    let mut sales = vec![];
+    let mut reserve_not_met = false;
+    for (winner_id, price) in winners.iter().copied() {
+        if item.reserve_price.is_some_and(|reserve| price < reserve) {
+            reserve_not_met = true;
+            continue;
+        }
+
+        let users = forget_user_secrets(get_user_state(pool).await?);
+        let sponsorships = get_sponsorship_state(pool).await?;
+        if Sponsorship::resolve_available_balance(winner_id, &users, &sponsorships, Some(item))
+            < price
+        {
+            warn!("Member {winner_id} won a unit of item {} for {price}, but cannot afford it anymore; skipping", item.id);
+            continue;
+        }
+
+        let split = get_contribution_split(pool).await?.record();
+        let contributions = Sponsorship::calculate_contributions(
+            winner_id,
+            price,
+            &users,
+            &sponsorships,
+            item,
+            &split,
+        );
+        let contributions_ids: Vec<_> = contributions.iter().map(|(u, b)| (u.id, *b)).collect();
+        let mut tx = pool.begin().await?;
+        let sale_id = record_payments(
+            &mut tx,
+            item.id,
+            winner_id,
+            &[(winner_id, contributions_ids)],
+            split,
         )
-        .execute(&mut tx)
         .await?;
+        if let Some(tie_break) = tie_break {
+            record_tie_break(&mut tx, sale_id, tie_break).await?;
+        }
+        tx.commit().await?;
+
+        let confirmation_code = confirmation_code();
+
+        let sold_to = users
+            .iter()
+            .find(|u| u.id == winner_id)
+            .expect("Winner not in user list?")
+            .clone();
+        sales.push(UnitSale {
+            sold_to,
+            sold_for: price,
+            confirmation_code,
+            contributions,
+        });
+    }
+
+    if sales.is_empty() && reserve_not_met {
+        mark_reserve_not_met(pool, item.id).await?;
+    }
+    Ok(sales)
+}
+
+/// Share units of the item between the members who tied for them, given in the order that they entered the arena.
+/// Each of them pays an equal part of the price of all of those units, or as much of it as they can.
+///
+/// The members who can pay anything are shared out between the units in turn, and each unit is sold to the first of them,
+/// with the payments of the others recorded with it, so that the admin can see who shares which unit, and settle it with them.
+/// A unit whose payments do not reach the item's reserve price is not sold.
+///
+/// Returns the sales of the units that were sold.
+pub async fn sell_shared_units(
+    pool: &SqlitePool,
+    item: &AuctionItem,
+    price: Money,
+    tied: &[i64],
+    units: u32,
+    tie_break: TieBreakRecord,
+) -> anyhow::Result<Vec<UnitSale>> {
+    let units = (units as usize).min(tied.len());
+    if units == 0 {
+        return Ok(vec![]);
+    }
+
+    // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+    let mut users = forget_user_secrets(get_user_state(pool).await?);
+    let mut sponsorships = get_sponsorship_state(pool).await?;
+    let split = get_contribution_split(pool).await?.record();
 
-        // Deduct amount from sponsorship, if the sponsorship exists.
-        let active = SponsorshipStatus::Active.to_db_val();
-        query!("UPDATE sponsorship SET remaining_balance=remaining_balance-? WHERE status=? AND recepient_id=? AND donor_id=?",
-            amt, active, buyer_id, uid).execute(&mut tx).await?;
+    // The payments are worked out one member after another,
+    // so that a sponsor of several tied members is not charged more than they have.
+    let shares = split_lot_price(price.saturating_mul(units as Money), &vec![0; tied.len()]);
+    let mut unit_payments = vec![vec![]; units];
+    let mut paying = 0;
+    for (member_id, share) in tied.iter().copied().zip(shares) {
+        let accessible_amount =
+            Sponsorship::resolve_available_balance(member_id, &users, &sponsorships, Some(item));
+        let amount = share.min(accessible_amount);
+        if amount == 0 {
+            continue;
+        }
+
+        let contributions = Sponsorship::calculate_contributions(
+            member_id,
+            amount,
+            &users,
+            &sponsorships,
+            item,
+            &split,
+        );
+        for (payer, amount) in contributions.iter() {
+            if let Some(u) = users.iter_mut().find(|u| u.id == payer.id) {
+                u.balance -= amount;
+            }
+            if let Some(s) = sponsorships.iter_mut().find(|s| {
+                s.status == SponsorshipStatus::Active
+                    && s.donor_id == payer.id
+                    && s.recepient_id == member_id
+            }) {
+                s.balance_remaining = s.balance_remaining.saturating_sub(*amount);
+            }
+        }
+        unit_payments[paying % units].push((member_id, contributions));
+        paying += 1;
     }
 
+    let mut tx = pool.begin().await?;
+    let mut sales = vec![];
+    let mut reserve_not_met = false;
+    for payments in unit_payments {
+        let buyer_id = match payments.first() {
+            None => continue,
+            Some((id, _)) => *id,
+        };
+        let total: Money = payments
+            .iter()
+            .flat_map(|(_, contributions)| contributions.iter().map(|(_, amount)| amount))
+            .sum();
+        if item.reserve_price.is_some_and(|reserve| total < reserve) {
+            reserve_not_met = true;
+            continue;
+        }
+
+        let payment_ids: Vec<_> = payments
+            .iter()
+            .map(|(id, contributions)| {
+                (*id, contributions.iter().map(|(u, b)| (u.id, *b)).collect())
+            })
+            .collect();
+        let sale_id = record_payments(&mut tx, item.id, buyer_id, &payment_ids, split).await?;
+        record_tie_break(&mut tx, sale_id, tie_break).await?;
+
+        // Everyone who shares the unit is shown as a contributor to its sale.
+        let mut contributions: Vec<(UserAccountData, Money)> = vec![];
+        for (payer, amount) in payments.into_iter().flat_map(|(_, c)| c) {
+            match contributions.iter_mut().find(|(u, _)| u.id == payer.id) {
+                Some((_, total)) => *total += amount,
+                None => contributions.push((payer, amount)),
+            }
+        }
+
+        let confirmation_code = confirmation_code();
+
+        let sold_to = users
+            .iter()
+            .find(|u| u.id == buyer_id)
+            .expect("Buyer not in user list?")
+            .clone();
+        sales.push(UnitSale {
+            sold_to,
+            sold_for: total,
+            confirmation_code,
+            contributions,
+        });
+    }
     tx.commit().await?;
 
-    Ok(())
+    if sales.is_empty() && reserve_not_met {
+        mark_reserve_not_met(pool, item.id).await?;
+    }
+    Ok(sales)
 }
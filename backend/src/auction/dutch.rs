@@ -0,0 +1,153 @@
+use std::{sync::Arc, time::Duration};
+
+use communication::{
+    auction::state::{ActiveBidState, AuctionItem, AuctionState, BiddingState, Sponsorship},
+    forget_user_secrets, Money,
+};
+use sqlx::{query, SqlitePool};
+use tokio::{
+    sync::*,
+    time::{interval, interval_at, Instant},
+};
+use tracing::warn;
+
+use crate::auction::db_actions::{
+    get_sponsorship_state, get_units_left, get_user_state, sell_units,
+};
+
+#[derive(Debug)]
+pub enum DutchAuctionEvent {
+    /// A user has claimed the item at the current price.
+    Claim { user_id: i64, item_id: i64 },
+
+    /// An admin has requested that the price decrease interval be changed.
+    NewPriceClockInterval {
+        price_decrease_per_100_seconds: Money,
+    },
+}
+
+pub async fn run_dutch_auction(
+    item_id: i64,
+    starting_price: Money,
+    pool: SqlitePool,
+    rx: Arc<Mutex<mpsc::Receiver<DutchAuctionEvent>>>,
+    state_tx: mpsc::Sender<AuctionState>,
+) -> anyhow::Result<()> {
+    let pool = &pool;
+    let mut rx = rx.lock().await;
+
+    // Discard any claims left over from a previous Dutch auction,
+    // so that they cannot buy this item.
+    while rx.try_recv().is_ok() {}
+
+    let row = query!(
+        r#"
+    SELECT
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
+    FROM auction_item
+    WHERE id=?
+    "#,
+        item_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let item = AuctionItem {
+        id: row.id,
+        name: row.name,
+        initial_price: row.initial_price as Money,
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
+    };
+
+    // A Dutch auction sells a single unit.
+    if get_units_left(pool, &item).await? == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    // The clock never goes below the item's initial price.
+    let minimum_price = item.initial_price;
+    let mut current_price = starting_price.max(minimum_price);
+
+    let mut current_price_decrease_per_100_seconds = 100;
+    // The first tick is delayed by one period, so that the starting price is visible for a full step.
+    let period = Duration::from_secs_f32(100.0 / current_price_decrease_per_100_seconds as f32);
+    let mut price_decrease_interval = interval_at(Instant::now() + period, period);
+
+    // This sends redundant copies of the auction state,
+    // in case a client has missed one.
+    let mut update_interval = interval(Duration::from_millis(500));
+
+    loop {
+        tokio::select! {
+            Some(event) = rx.recv() => {
+                match event {
+                    DutchAuctionEvent::Claim { user_id, item_id } => {
+                        // If the item ID does not match, ignore this
+                        if item_id != item.id { continue; }
+
+                        // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+                        let users = get_user_state(pool).await?;
+                        let sponsorships = get_sponsorship_state(pool).await?;
+                        let users = forget_user_secrets(users);
+
+                        if !users.iter().any(|u| u.id == user_id) {
+                            warn!("User ID {user_id} tried to claim Dutch auction item, but does not exist; hacking detected?");
+                            continue;
+                        }
+
+                        // If the user's sponsorship group cannot afford the current price, ignore the claim.
+                        let accessible_amount = Sponsorship::resolve_available_balance(user_id, &users, &sponsorships, Some(&item));
+                        if accessible_amount < current_price {
+                            warn!("User ID {user_id} tried to claim Dutch auction item at {current_price}, but only has funds {accessible_amount}: hacking detected?");
+                            continue;
+                        }
+
+                        // The first valid claim wins the item at the current price.
+                        let sales = sell_units(pool, &item, &[(user_id, current_price)], None).await?;
+                        state_tx.send(AuctionState::after_units_sold(item, sales)).await?;
+
+                        return Ok(());
+                    },
+                    DutchAuctionEvent::NewPriceClockInterval { price_decrease_per_100_seconds } => {
+                        // A zero rate would mean an infinite period, so the slowest allowed rate is 1.
+                        let price_decrease_per_100_seconds = price_decrease_per_100_seconds.max(1);
+
+                        // As in the Japanese auction, the first tick of the new interval happens
+                        // half of the previous interval into the future.
+                        let new_period = Duration::from_secs_f32(100.0 / price_decrease_per_100_seconds as f32);
+                        price_decrease_interval = interval_at(Instant::now() + (price_decrease_interval.period()/2), new_period);
+                        current_price_decrease_per_100_seconds = price_decrease_per_100_seconds;
+                    },
+                }
+            }
+
+            _ = price_decrease_interval.tick() => {
+                // If the clock has already reached the minimum price, and nobody claimed the item,
+                // it cannot be sold. Return to the item selection state.
+                if current_price <= minimum_price {
+                    state_tx.send(AuctionState::WaitingForItem).await?;
+                    return Ok(());
+                }
+
+                current_price -= 1;
+            }
+
+            _ = update_interval.tick() => {}
+        }
+
+        // Publish the current state (price and rate)
+        let bid_state = BiddingState {
+            item: item.clone(),
+            active_bid: ActiveBidState::DutchAuctionBid {
+                current_price,
+                current_price_decrease_per_100_seconds,
+                minimum_price,
+            },
+            is_paused: false,
+        };
+        state_tx.send(AuctionState::Bidding(bid_state)).await?;
+    }
+}
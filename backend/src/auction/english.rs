@@ -1,7 +1,13 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use communication::{
-    auction::state::{ActiveBidState, AuctionItem, AuctionState, BiddingState, Sponsorship},
+    auction::state::{
+        ActiveBidState, AuctionItem, AuctionState, BiddingState, IncrementRule, SoftClosePolicy,
+        Sponsorship, UnitSale,
+    },
     forget_user_secrets, Money, UserAccountData,
 };
 use rand::Rng;
@@ -10,11 +16,11 @@ use tokio::{
     sync::*,
     time::{interval, Instant},
 };
-use tracing::warn;
+use tracing::{info, warn};
 
-use crate::auction::db_actions::{apply_contributions, get_sponsorship_state, get_user_state};
+use crate::auction::db_actions::{get_units_left, sell_units};
 
-use super::sync_handle;
+use super::{buy_now::try_buy_now, sync_handle, AuctionEvent};
 
 #[derive(Debug)]
 pub enum EnglishAuctionEvent {
@@ -24,12 +30,193 @@ pub enum EnglishAuctionEvent {
         item_id: i64,
     },
 
+    /// A user has set (or, with `None`, removed) their secret maximum bid.
+    ProxyBidSet {
+        bidder_id: i64,
+        max_bid: Option<Money>,
+        item_id: i64,
+    },
+
     /// Change the time until the bid is committed
     SetCommitPeriod { new_period: Duration },
+
+    /// Change the rule for the minimum bid increment
+    SetIncrementRule(IncrementRule),
+
+    /// A user wants to buy the item immediately at its buy-now price.
+    BuyNow { buyer_id: i64, item_id: i64 },
+
+    /// An admin has paused the auction: the timer stops, and no bids are accepted.
+    Pause,
+
+    /// An admin has resumed the auction, with the time that was left when it was paused.
+    Resume,
+}
+
+/// The minimum amount by which the next bid must exceed the current one.
+/// The first bid only needs to be the initial price,
+/// which is one more than the null bid's amount.
+fn minimum_increment(rule: &IncrementRule, current_bidder_id: i64, current_bid: Money) -> Money {
+    if current_bidder_id == 0 {
+        1
+    } else {
+        rule.increment_for(current_bid)
+    }
+}
+
+/// The time when bidding should be over, after a new bid is placed.
+fn deadline_after_bid(
+    policy: SoftClosePolicy,
+    current_deadline: Instant,
+    first_bid_at: Option<Instant>,
+    bidding_duration: Duration,
+) -> Instant {
+    // A candle auction's end time is fixed when it starts.
+    if let SoftClosePolicy::Candle { .. } = policy {
+        return current_deadline;
+    }
+
+    let now = Instant::now();
+    let full_period = now + bidding_duration;
+    let first_bid_at = match first_bid_at {
+        // The first bid always starts the timer with the full commit period.
+        None => return full_period,
+        Some(t) => t,
+    };
+
+    match policy {
+        SoftClosePolicy::ResetOnEveryBid => full_period,
+        SoftClosePolicy::ResetInFinalWindow { window_ms } => {
+            if current_deadline.saturating_duration_since(now)
+                < Duration::from_millis(window_ms as u64)
+            {
+                full_period
+            } else {
+                current_deadline
+            }
+        }
+        SoftClosePolicy::ExtendUpToHardCap {
+            extension_ms,
+            hard_cap_ms,
+        } => (current_deadline + Duration::from_millis(extension_ms as u64))
+            .min(full_period)
+            .min(first_bid_at + Duration::from_millis(hard_cap_ms as u64))
+            .max(current_deadline),
+        SoftClosePolicy::Candle { .. } => current_deadline,
+    }
+}
+
+/// The bidder that stands in for "no bid yet", whose bid is one less than the initial price.
+fn null_bidder() -> UserAccountData {
+    UserAccountData {
+        id: 0,
+        user_name: String::from("∅"), // null symbol U+2205
+        balance: 0,
+        sale_mode: communication::UserSaleMode::Bidding,
+        is_accepting_sponsorships: false,
+    }
+}
+
+/// Work out the bids that would win the units if the bidding ended now, from highest to lowest,
+/// and the bid that a new bid needs to beat:
+/// the lowest winning bid, or the null bid if some units do not have a winning bid yet.
+///
+/// Only the highest bid of each member counts, since each member can win at most one unit.
+/// Among equal bids, the one placed earlier wins.
+/// The first entry in the bid history must be the null bid.
+fn standings(bid_history: &[(i64, Money)], units: u32) -> (Vec<(i64, Money)>, (i64, Money)) {
+    let mut best_bids: Vec<(i64, Money)> = vec![];
+    for &(id, amount) in bid_history.iter().skip(1) {
+        if best_bids
+            .iter()
+            .any(|(bidder, best)| *bidder == id && *best >= amount)
+        {
+            continue;
+        }
+        best_bids.retain(|(bidder, _)| *bidder != id);
+        best_bids.push((id, amount));
+    }
+
+    // The bids are in the order they were placed, and the sort is stable,
+    // so earlier bids stay ahead of equal later ones.
+    best_bids.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+    best_bids.truncate(units as usize);
+
+    let to_beat = if best_bids.len() < units as usize {
+        bid_history[0]
+    } else {
+        *best_bids
+            .last()
+            .expect("English auction has no units to sell?")
+    };
+    (best_bids, to_beat)
+}
+
+/// Work out the bids that the proxy bids would place against the current bids,
+/// in the order that they should be applied.
+///
+/// The best proxy bid of a member who is not winning outbids the bid to beat by the minimum increment,
+/// or by as much as is needed to beat that bidder's own maximum.
+/// On equal maximums, the bidder who is already winning keeps their place.
+fn resolve_proxy_bids(
+    bid_history: &[(i64, Money)],
+    units: u32,
+    increment_rule: &IncrementRule,
+    proxy_bids: &[(i64, Money)],
+) -> Vec<(i64, Money)> {
+    let mut bid_history = bid_history.to_vec();
+    let mut new_bids = vec![];
+    loop {
+        let (winning_bids, (leader, price)) = standings(&bid_history, units);
+        let next_price = price.saturating_add(minimum_increment(increment_rule, leader, price));
+        let leader_max = proxy_bids
+            .iter()
+            .find(|(id, _)| *id == leader)
+            .map_or(price, |(_, max)| *max)
+            .max(price);
+
+        // The challenger with the highest maximum; among equal ones, the earliest registered.
+        let challenger = proxy_bids
+            .iter()
+            .filter(|(id, max)| {
+                !winning_bids.iter().any(|(winner, _)| winner == id) && *max >= next_price
+            })
+            .fold(None, |best: Option<(i64, Money)>, c| match best {
+                Some((_, best_max)) if best_max >= c.1 => best,
+                _ => Some(*c),
+            });
+        let (challenger_id, challenger_max) = match challenger {
+            None => break,
+            Some(c) => c,
+        };
+
+        let new_bid = if challenger_max > leader_max {
+            // The challenger takes the place, paying just enough to beat the leader's maximum.
+            let beats_leader_max =
+                leader_max.saturating_add(minimum_increment(increment_rule, leader, leader_max));
+            (
+                challenger_id,
+                challenger_max.min(next_price.max(beats_leader_max)),
+            )
+        } else {
+            // The leader's proxy bid defends, going just above the challenger's maximum.
+            let beats_challenger_max = challenger_max.saturating_add(minimum_increment(
+                increment_rule,
+                challenger_id,
+                challenger_max,
+            ));
+            (leader, leader_max.min(beats_challenger_max))
+        };
+        bid_history.push(new_bid);
+        new_bids.push(new_bid);
+    }
+    new_bids
 }
 
 pub async fn run_english_auction(
     item_id: i64,
+    soft_close_policy: SoftClosePolicy,
+    initial_price: Option<Money>,
     pool: SqlitePool,
     rx: Arc<Mutex<mpsc::Receiver<EnglishAuctionEvent>>>,
     state_tx: mpsc::Sender<AuctionState>,
@@ -41,7 +228,7 @@ pub async fn run_english_auction(
     let row = query!(
         r#"
     SELECT
-        auction_item.id, auction_item.name, auction_item.initial_price
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
     FROM auction_item
     WHERE id=?
     "#,
@@ -52,75 +239,92 @@ pub async fn run_english_auction(
     let item = AuctionItem {
         id: row.id,
         name: row.name,
-        initial_price: row.initial_price as Money,
+        initial_price: initial_price.unwrap_or(row.initial_price as Money),
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
     };
 
     let mut bidding_duration = Duration::from_secs(15);
     let mut time_when_bidding_over = Instant::now() + Duration::from_secs(u64::MAX / 8); // initial time is basically infinite, but needs to be inside the allowable range.
     let mut check_interval = interval(Duration::from_millis(100));
+    let mut first_bid_at = None;
+    let mut increment_rule = IncrementRule::default();
 
-    let mut current_bid = item.initial_price - 1;
-    let mut current_bidder = UserAccountData {
-        id: 0,
-        user_name: String::from("∅"), // null symbol U+2205
-        balance: 0,
-        sale_mode: communication::UserSaleMode::Bidding,
-        is_accepting_sponsorships: false,
-    };
-    let mut current_bidder_id = 0;
-    let mut bid_history = vec![];
-    bid_history.push((current_bidder_id, item.initial_price - 1));
+    // Each unit goes to a different member; the units that were already sold are not auctioned again.
+    let mut units = get_units_left(pool, &item).await?;
+    if units == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
 
-    loop {
-        // First check if the bidding has expired
-        if time_when_bidding_over < Instant::now() {
-            // Bidding over: item is sold
-
-            // Special case: when nobody placed any bids (bidder_id=0),
-            // just return to the item selection state
-            // The auction admin can then try to re-sell the item.
-            if current_bidder_id == 0 {
-                state_tx.send(AuctionState::WaitingForItem).await?;
-                return Ok(());
-            }
+    // In a candle auction, the end time is chosen now, and kept secret from the members.
+    // It is recorded, so that it can be checked after the auction that it was not tampered with.
+    if let SoftClosePolicy::Candle {
+        min_duration_ms,
+        max_duration_ms,
+    } = soft_close_policy
+    {
+        let duration_ms = rand::thread_rng()
+            .gen_range(min_duration_ms..=max_duration_ms.max(min_duration_ms))
+            as u64;
+        time_when_bidding_over = Instant::now() + Duration::from_millis(duration_ms);
 
-            // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
-            let users = get_user_state(pool).await?;
-            let sponsorships = get_sponsorship_state(pool).await?;
+        let started_at_ms = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
+        let ends_at_ms = started_at_ms + duration_ms as i64;
+        let (min_duration_ms, max_duration_ms) = (min_duration_ms as i64, max_duration_ms as i64);
+        query!(
+            "INSERT INTO candle_auction_log (item_id, min_duration_ms, max_duration_ms, started_at_ms, ends_at_ms) VALUES (?,?,?,?,?)",
+            item.id,
+            min_duration_ms,
+            max_duration_ms,
+            started_at_ms,
+            ends_at_ms,
+        )
+        .execute(pool)
+        .await?;
+        info!(
+            "Candle auction for item {} will end {duration_ms}ms after starting",
+            item.id
+        );
+    }
 
-            let current_bidder = users
-                .iter()
-                .find(|u| u.id == current_bidder_id)
-                .expect("Final bidder not in users?");
+    // The current bid is the one that a new bid needs to beat.
+    let mut current_bid = item.initial_price.saturating_sub(1);
+    let mut current_bidder = null_bidder();
+    let mut current_bidder_id = 0;
+    let mut bid_history = vec![];
+    bid_history.push((current_bidder_id, current_bid));
 
-            let contributions = Sponsorship::calculate_contributions(
-                current_bidder_id,
-                current_bid,
-                &forget_user_secrets(users.clone()),
-                &sponsorships,
-            );
+    // The secret maximum bids, in the order they were registered.
+    let mut proxy_bids: Vec<(i64, Money)> = vec![];
 
-            let contributions_ids: Vec<_> = contributions.iter().map(|(u, b)| (u.id, *b)).collect();
+    // The units bought at the buy-now price while the bidding is going on.
+    // These are announced together with the units sold by bidding.
+    let mut bought_now = vec![];
 
-            apply_contributions(pool, item_id, current_bidder_id, &contributions_ids).await?;
+    // If the auction is paused, when it was paused.
+    let mut paused_at: Option<Instant> = None;
 
-            // Publish the state
-            let mut confirmation_code = String::new();
-            {
-                let mut rng = rand::thread_rng();
-                for _ in 0..4 {
-                    confirmation_code.push_str(&rng.gen_range(0..9).to_string());
-                }
+    loop {
+        // First check if the bidding has expired (which it cannot while paused)
+        if paused_at.is_none() && time_when_bidding_over < Instant::now() {
+            // Bidding over: each of the winning bids buys a unit.
+            // If nobody placed any bids, or nobody could pay,
+            // this returns to the item selection state,
+            // and the auction admin can then try to re-sell the item.
+            let (winning_bids, _) = standings(&bid_history, units);
+            if winning_bids.is_empty() && bought_now.is_empty() {
+                sync_handle
+                    .send_event(AuctionEvent::ItemGotNoBids(item.id))
+                    .await;
             }
-
-            let state = AuctionState::SoldToMember {
-                item,
-                sold_for: current_bid,
-                sold_to: current_bidder.into(),
-                confirmation_code,
-                contributions,
-            };
-            state_tx.send(state).await?;
+            let mut sales = sell_units(pool, &item, &winning_bids, None).await?;
+            sales.extend(bought_now);
+            state_tx
+                .send(AuctionState::after_units_sold(item, sales))
+                .await?;
 
             return Ok(());
         }
@@ -128,20 +332,39 @@ pub async fn run_english_auction(
         tokio::select! {
             _ = check_interval.tick() => {
                 // Construct an AuctionState and publish it
+                let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
                 let bid_state = BiddingState {
                     item: item.clone(),
                     active_bid: ActiveBidState::EnglishAuctionBid {
                         current_bid_amount: current_bid,
                         current_bidder: current_bidder.clone(),
-                        minimum_increment: 1, // TODO: add rule for increasing this
-                        seconds_until_commit: time_when_bidding_over.duration_since(Instant::now()).as_secs_f32(),
-                        max_millis_until_commit: bidding_duration.as_millis()
-                    }
+                        units,
+                        winning_bids: standings(&bid_history, units).0
+                            .iter()
+                            .filter_map(|(id, bid)| users.iter().find(|u| u.id == *id).map(|u| (u.clone(), *bid)))
+                            .collect(),
+                        minimum_increment: minimum_increment(&increment_rule, current_bidder_id, current_bid),
+                        increment_rule: increment_rule.clone(),
+                        soft_close_policy,
+                        // While paused, the time left stays as it was when the auction was paused.
+                        seconds_until_commit: time_when_bidding_over.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32(),
+                        max_millis_until_commit: bidding_duration.as_millis(),
+                        proxy_bids: proxy_bids
+                            .iter()
+                            .filter_map(|(id, max)| users.iter().find(|u| u.id == *id).map(|u| (u.clone(), *max)))
+                            .collect(),
+                    },
+                    is_paused: paused_at.is_some(),
                 };
                 let state = AuctionState::Bidding(bid_state);
                 state_tx.send(state).await?;
             }
             Some(event) = rx.recv() => {
+                // While paused, the members' actions are rejected.
+                if paused_at.is_some() && matches!(event, EnglishAuctionEvent::BidPlaced { .. } | EnglishAuctionEvent::ProxyBidSet { .. } | EnglishAuctionEvent::BuyNow { .. }) {
+                    warn!("Received {event:?} while the English auction is paused; ignoring");
+                    continue;
+                }
                 match event {
                     EnglishAuctionEvent::BidPlaced { bidder_id, bid_amount, item_id } => {
                         // If we receive an event about an item that is not the one we're selling, ignore it.
@@ -161,95 +384,179 @@ pub async fn run_english_auction(
                                 // Get the amount that the user's sponsorship group has access to.
                                 let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
                                 let sponsorships = sync_handle.sponsorship_state.borrow();
-                                println!("{users:?}");
-                                println!("{sponsorships:?}");
-                                let accessible_amount = Sponsorship::resolve_available_balance(row.id, &users, &sponsorships);
+                                let accessible_amount = Sponsorship::resolve_available_balance(row.id, &users, &sponsorships, Some(&item));
 
                                 // If the user does not have sufficient funds, ignore the request
                                 if accessible_amount < bid_amount {
                                     warn!("Received English auction bid with user ID={bidder_id} and bid_amount={bid_amount}; user only has funds {}: hacking detected?", accessible_amount);
                                     continue;
                                 }
-                                // TODO: verify that the increment rule is followed
-                                // For now, just verify that the new bid is greater than the past one.
-                                if bid_amount <= current_bid { continue; }
+                                // The new bid must exceed the past one by at least the minimum increment.
+                                let minimum_bid = current_bid.saturating_add(minimum_increment(&increment_rule, current_bidder_id, current_bid));
+                                if bid_amount < minimum_bid {
+                                    warn!("Received English auction bid with user ID={bidder_id} and bid_amount={bid_amount}; minimum bid is {minimum_bid}: hacking detected?");
+                                    continue;
+                                }
+
+                                // A member who has bought a unit at the buy-now price cannot win another.
+                                if bought_now.iter().any(|s: &UnitSale| s.sold_to.id == row.id) {
+                                    continue;
+                                }
 
                                 // Now record the bid
-                                current_bid = bid_amount;
-                                current_bidder_id = row.id;
-                                current_bidder = UserAccountData {
-                                    id: row.id, user_name: row.name, balance: row.balance as Money,
-                                    sale_mode: row.sale_mode.into(),
-                                    is_accepting_sponsorships: row.sponsorship_code.is_some(),
-                                };
-                                bid_history.push((current_bidder_id, current_bid));
-
-                                // and reset the timer
-                                time_when_bidding_over = Instant::now() + bidding_duration;
+                                bid_history.push((row.id, bid_amount));
+
+                                // and extend the timer according to the policy
+                                time_when_bidding_over = deadline_after_bid(soft_close_policy, time_when_bidding_over, first_bid_at, bidding_duration);
+                                first_bid_at.get_or_insert_with(Instant::now);
                             }
                         };
                     },
+                    EnglishAuctionEvent::ProxyBidSet { bidder_id, max_bid, item_id } => {
+                        // If we receive an event about an item that is not the one we're selling, ignore it.
+                        if item_id != item.id {continue;}
+
+                        // Any previous maximum bid by this user is replaced.
+                        proxy_bids.retain(|(id, _)| *id != bidder_id);
+                        let max_bid = match max_bid {
+                            None => continue,
+                            Some(m) => m,
+                        };
+                        if bought_now.iter().any(|s: &UnitSale| s.sold_to.id == bidder_id) {
+                            continue;
+                        }
+
+                        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                        let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                        if !users.iter().any(|u| u.id == bidder_id) {
+                            warn!("Received English auction proxy bid with user ID={bidder_id}; no such user: hacking detected?");
+                            continue;
+                        }
+
+                        // The maximum must be payable by the user's sponsorship group, just like a normal bid.
+                        let accessible_amount = Sponsorship::resolve_available_balance(bidder_id, &users, &sponsorships, Some(&item));
+                        if accessible_amount < max_bid {
+                            warn!("Received English auction proxy bid with user ID={bidder_id} and max_bid={max_bid}; user only has funds {accessible_amount}: hacking detected?");
+                            continue;
+                        }
+                        if max_bid <= current_bid { continue; }
+
+                        proxy_bids.push((bidder_id, max_bid));
+                    },
                     EnglishAuctionEvent::SetCommitPeriod{ new_period } => {
                         // If the new duration is longer than the previous one,
                         // shift the timer deadline by that.
-                        if new_period > bidding_duration {
+                        // (A candle auction's end time is never changed.)
+                        if new_period > bidding_duration && !matches!(soft_close_policy, SoftClosePolicy::Candle { .. }) {
                             let diff = new_period - bidding_duration;
                             time_when_bidding_over = time_when_bidding_over + diff;
                         }
 
                         bidding_duration = new_period;
                     },
+                    EnglishAuctionEvent::BuyNow { buyer_id, item_id } => {
+                        // If we receive an event about an item that is not the one we're selling, ignore it.
+                        if item_id != item.id {continue;}
+
+                        // Once the bidding has reached the buy-now price, the item can no longer be bought instantly.
+                        if item.buy_now_price.is_none_or(|price| current_bid >= price) { continue; }
+
+                        if let Some(sale) = try_buy_now(pool, &item, buyer_id).await? {
+                            bought_now.push(sale);
+                            units -= 1;
+
+                            // The buyer has their unit, so their bids no longer compete for the other units.
+                            bid_history.retain(|(id, _)| *id != buyer_id);
+                            proxy_bids.retain(|(id, _)| *id != buyer_id);
+
+                            if units == 0 {
+                                state_tx.send(AuctionState::after_units_sold(item.clone(), bought_now)).await?;
+                                return Ok(());
+                            }
+                        }
+                    },
+                    EnglishAuctionEvent::SetIncrementRule(rule) => {
+                        // This only affects the bids placed from now on.
+                        increment_rule = rule;
+                    },
+                    EnglishAuctionEvent::Pause => {
+                        paused_at.get_or_insert_with(Instant::now);
+                    },
+                    EnglishAuctionEvent::Resume => {
+                        // Shift the deadline (and the start of the bidding, which a hard cap counts from)
+                        // by how long the auction was paused, so that the same time is left as when it was paused.
+                        if let Some(paused_at) = paused_at.take() {
+                            let paused_for = paused_at.elapsed();
+                            info!("English auction for item {} resumed after being paused for {}ms", item.id, paused_for.as_millis());
+                            time_when_bidding_over += paused_for;
+                            first_bid_at = first_bid_at.map(|t| t + paused_for);
+
+                            // A candle auction's record is moved along with its end time, so that the end time can still be checked against it.
+                            if matches!(soft_close_policy, SoftClosePolicy::Candle { .. }) {
+                                let paused_ms = paused_for.as_millis() as i64;
+                                query!("UPDATE candle_auction_log SET ends_at_ms = ends_at_ms + ?, paused_ms = paused_ms + ? WHERE id = (SELECT MAX(id) FROM candle_auction_log WHERE item_id=?)", paused_ms, paused_ms, item.id).execute(pool).await?;
+                            }
+                        }
+                    },
                 }
             },
 
             _ = sync_handle.sponsorship_state.changed() => {
-                // If sponsorships have changed, then the current bid may have become invalid.
-                // Unwind the bids history, checking each along the way.
-                // For each bid that turns out to be invalid, discard it,
-                // until we come up to the null bid.
+                // If sponsorships have changed, then some bids may have become invalid:
+                // discard every bid that the bidder's sponsorship group cannot pay anymore.
+                // (The null bid is always valid.)
 
                 let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
                 let sponsorships = sync_handle.sponsorship_state.borrow().clone();
 
-                loop {
-                    // Check if the current bid is still allowable:
-                    let accessible_amount = Sponsorship::resolve_available_balance(current_bidder_id, &users, &sponsorships);
-                    if current_bid > accessible_amount {
-                        // The current bid is not allowed
-                        // Remove it from the stack,
-                        // then apply the one following it.
-                        bid_history.pop();
-                        let to_apply =  bid_history.last().unwrap();
-                        current_bid = to_apply.1;
-                        current_bidder_id = to_apply.0;
-                        // Reset the timer
-                        time_when_bidding_over = Instant::now() + bidding_duration;
-
-                        // Special case: if the only bid remaining is the null bid, restore the auction to its initial state,
-                        // and stop the unwinding.
-                        if to_apply.0 == 0 {
-                            current_bidder = UserAccountData {
-                                id: 0,
-                                user_name: String::from("∅"), // null symbol U+2205
-                                balance: 0,
-                                sale_mode: communication::UserSaleMode::Bidding,
-                                is_accepting_sponsorships: false,
-                            };
-                            time_when_bidding_over = Instant::now() + Duration::from_secs(u64::MAX / 8);
-                            break;
-                        }
+                // The maximum bids may also have become unaffordable: discard those.
+                proxy_bids.retain(|(id, max)| Sponsorship::resolve_available_balance(*id, &users, &sponsorships, Some(&item)) >= *max);
+
+                let bid_count = bid_history.len();
+                bid_history.retain(|(id, bid)| Sponsorship::resolve_available_balance(*id, &users, &sponsorships, Some(&item)) >= *bid);
+                if bid_history.len() < bid_count && !matches!(soft_close_policy, SoftClosePolicy::Candle { .. }) {
+                    // Reset the timer (if paused, as of when it was paused)
+                    time_when_bidding_over = paused_at.unwrap_or_else(Instant::now) + bidding_duration;
 
-                        // If the bid is by a normal user,
-                        // then apply the user's current state (which may have changed).
-                        current_bidder = users.iter().find(|u| u.id == current_bidder_id).expect("User disappeared during English auction?").clone();
-                    } else {
-                        // The current bid is acceptable, so stop the unwinding.
-                        // (This case also happens when the very first bid is acceptable,
-                        //  and no unwinding actually took place.)
-                        break;
+                    // Special case: if the only bid remaining is the null bid, restore the auction to its initial state.
+                    if bid_history.len() == 1 {
+                        time_when_bidding_over = Instant::now() + Duration::from_secs(u64::MAX / 8);
+                        first_bid_at = None;
                     }
                 }
             },
         }
+
+        // After any change, let the proxy bids respond to the current bids
+        // (but not while paused: they respond once the auction is resumed),
+        let new_bids = if paused_at.is_none() {
+            resolve_proxy_bids(&bid_history, units, &increment_rule, &proxy_bids)
+        } else {
+            vec![]
+        };
+        if !new_bids.is_empty() {
+            bid_history.extend(new_bids);
+
+            // and extend the timer according to the policy
+            time_when_bidding_over = deadline_after_bid(
+                soft_close_policy,
+                time_when_bidding_over,
+                first_bid_at,
+                bidding_duration,
+            );
+            first_bid_at.get_or_insert_with(Instant::now);
+        }
+
+        // then work out which bid the next one needs to beat.
+        (current_bidder_id, current_bid) = standings(&bid_history, units).1;
+        current_bidder = if current_bidder_id == 0 {
+            null_bidder()
+        } else {
+            let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+            users
+                .into_iter()
+                .find(|u| u.id == current_bidder_id)
+                .expect("Bidder disappeared during English auction?")
+        };
     }
 }
@@ -5,22 +5,23 @@ use communication::{
         actions::JapaneseAuctionAction,
         state::{
             ActiveBidState, ArenaVisibilityMode, AuctionItem, AuctionState, BiddingState,
-            JapaneseAuctionBidState, Sponsorship,
+            JapaneseAuctionBidState, JapaneseAuctionSettings, JapaneseTieBreak, Sponsorship,
+            TieBreakRecord,
         },
     },
     forget_user_secrets, Money, UserAccountData,
 };
-use rand::Rng;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use sqlx::{query, SqlitePool};
 use tokio::{
     sync::*,
     time::{interval_at, Instant},
 };
-use tracing::warn;
+use tracing::{info, warn};
 
-use crate::auction::db_actions::apply_contributions;
+use crate::auction::db_actions::{get_units_left, sell_shared_units, sell_units};
 
-use super::sync_handle;
+use super::{sync_handle, AuctionEvent};
 
 #[derive(Debug)]
 pub enum JapaneseAuctionEvent {
@@ -36,15 +37,111 @@ pub enum JapaneseAuctionEvent {
         price_increase_per_100_seconds: Money,
     },
 
+    /// An admin has requested that the price increase on each tick of the clock be changed.
+    NewPriceStep(Money),
+
+    /// An admin has requested that the time for which the arena stays open after it starts closing be changed.
+    NewArenaClosingDuration(Duration),
+
     /// An admin has changed the arena visibility mode.
     NewArenaVisibilityMode(ArenaVisibilityMode),
 
     /// An admin has started the arena closing.
     StartClosingArena,
+
+    /// An admin has paused the auction: the arena countdown and the price clock stop,
+    /// and nobody can enter or leave the arena.
+    Pause,
+
+    /// An admin has resumed the auction, with the same time left as when it was paused.
+    Resume,
+}
+
+/// The time between ticks of the price clock, so that the price increases by the step on every tick,
+/// and by the rate every 100 seconds.
+fn clock_tick_period(price_increase_per_100_seconds: Money, price_step: Money) -> Duration {
+    Duration::from_secs_f32(100.0 * price_step as f32 / price_increase_per_100_seconds as f32)
+}
+
+/// How much an arena member can pay: what their sponsorship group has available,
+/// but no more than their drop-out price, if they have set one.
+fn available_until_limit(
+    user_id: i64,
+    users: &[UserAccountData],
+    sponsorships: &[Sponsorship],
+    drop_out_prices: &[(UserAccountData, Money)],
+    item: &AuctionItem,
+) -> Money {
+    let available =
+        Sponsorship::resolve_available_balance(user_id, users, sponsorships, Some(item));
+    match drop_out_prices.iter().find(|(u, _)| u.id == user_id) {
+        Some((_, limit)) => available.min(*limit),
+        None => available,
+    }
+}
+
+/// Remove the arena members who cannot pay the current price anymore.
+///
+/// If the arena is closed, and fewer members than units would be left in it, the members who cannot pay are tied for the units left.
+/// Then the tie-break policy decides which of them stay in the arena to win those units,
+/// and this returns how the tie was broken.
+/// When the tie is split, they all stay in the arena.
+///
+/// `available` gives how much a member can pay: see [`available_until_limit`].
+fn remove_members_who_cannot_pay(
+    arena: &mut Vec<UserAccountData>,
+    arena_is_closed: bool,
+    current_price: Money,
+    units: u32,
+    tie_break: JapaneseTieBreak,
+    available: impl Fn(i64) -> Money,
+) -> Option<TieBreakRecord> {
+    // The arena is in the order that the members entered it.
+    let leaving: Vec<i64> = arena
+        .iter()
+        .map(|u| u.id)
+        .filter(|id| available(*id) < current_price)
+        .collect();
+    let units_left = (units as usize).saturating_sub(arena.len() - leaving.len());
+
+    // If there are enough units left for all of them, they all stay, and win one each.
+    if arena_is_closed && units_left > 0 && leaving.len() <= units_left {
+        return None;
+    }
+    if !arena_is_closed || units_left == 0 {
+        arena.retain(|u| !leaving.contains(&u.id));
+        return None;
+    }
+
+    let (staying, seed) = match tie_break {
+        JapaneseTieBreak::EarliestEntry => (leaving[..units_left].to_vec(), None),
+        JapaneseTieBreak::RandomDraw => {
+            let seed: u64 = rand::random();
+            let mut drawn = leaving.clone();
+            drawn.shuffle(&mut StdRng::seed_from_u64(seed));
+            drawn.truncate(units_left);
+            (drawn, Some(seed))
+        }
+        JapaneseTieBreak::HighestBalance => {
+            // The sort is stable, so members with the same balance stay in the order they entered.
+            let mut ranked = leaving.clone();
+            ranked.sort_by_key(|id| std::cmp::Reverse(available(*id)));
+            ranked.truncate(units_left);
+            (ranked, None)
+        }
+        JapaneseTieBreak::Split => (leaving.clone(), None),
+    };
+    arena.retain(|u| !leaving.contains(&u.id) || staying.contains(&u.id));
+    Some(TieBreakRecord {
+        policy: tie_break,
+        seed,
+    })
 }
 
 pub async fn run_japanese_auction(
     item_id: i64,
+    settings: JapaneseAuctionSettings,
+    initial_price: Option<Money>,
     pool: SqlitePool,
     rx: Arc<Mutex<mpsc::Receiver<JapaneseAuctionEvent>>>,
     state_tx: mpsc::Sender<AuctionState>,
@@ -56,7 +153,7 @@ pub async fn run_japanese_auction(
     let row = query!(
         r#"
     SELECT
-        auction_item.id, auction_item.name, auction_item.initial_price
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
     FROM auction_item
     WHERE id=?
     "#,
@@ -67,15 +164,32 @@ pub async fn run_japanese_auction(
     let item = AuctionItem {
         id: row.id,
         name: row.name,
-        initial_price: row.initial_price as Money,
+        initial_price: initial_price.unwrap_or(row.initial_price as Money),
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
     };
 
     let mut current_price = item.initial_price;
 
-    let mut current_price_increase_per_100_seconds = 100;
-    let mut price_increase_interval = tokio::time::interval(Duration::from_secs_f32(
-        100.0 / current_price_increase_per_100_seconds as f32,
+    // A zero rate or step would stop the clock, so these are at least 1.
+    let mut current_price_increase_per_100_seconds = settings.price_increase_per_100_seconds.max(1);
+    let mut price_step = settings.price_step.max(1);
+    let mut price_increase_interval = tokio::time::interval(clock_tick_period(
+        current_price_increase_per_100_seconds,
+        price_step,
     ));
+    // When the price clock should next tick, so that a pause can keep the time until then.
+    let mut next_price_tick_at = Instant::now();
+    let mut arena_closing_secs = settings.arena_closing_secs;
+    let tie_break = settings.tie_break;
+
+    // The schedule by which the rate changes by itself, until the admin changes it by hand.
+    let mut rate_schedule = settings.rate_schedule;
+    // When the rate last changed (or the clock started running), and whether the schedule has already slowed it down.
+    let mut rate_changed_at = Instant::now();
+    let mut rate_slowed_down = false;
 
     // This interval is used so that the `tokio::select!` does not get stuck for too long,
     // so that the arena closing logic can process,
@@ -85,7 +199,13 @@ pub async fn run_japanese_auction(
 
     let mut arena = vec![];
 
-    // This value is irrelevant as long as `arena_is_closing` is false.
+    // The prices at which arena members leave the arena automatically,
+    // and the members who have left that way, with the price they left at.
+    let mut drop_out_prices: Vec<(UserAccountData, Money)> = vec![];
+    let mut left_on_limit: Vec<(UserAccountData, Money)> = vec![];
+
+    // These values are irrelevant as long as `arena_is_closing` is false.
+    let mut arena_started_closing = tokio::time::Instant::now();
     let mut arena_closes_for_entry = tokio::time::Instant::now();
 
     let mut arena_is_closed = false;
@@ -94,12 +214,27 @@ pub async fn run_japanese_auction(
 
     let mut arena_is_closing = false;
 
+    // If the auction is paused, when it was paused.
+    let mut paused_at: Option<Instant> = None;
+
+    // Each unit goes to a different member; the units that were already sold are not auctioned again.
+    let units = get_units_left(pool, &item).await?;
+    if units == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
     // This returns an Err when the item is successfully sold.
-    // Just call this with ? whenever arena changes.
+    // Just call this with ? whenever arena changes,
+    // with how a tie was broken if the change was removing members who could not pay.
+    #[allow(clippy::too_many_arguments)]
     async fn run_sold_check(
         arena_is_closed: bool,
         current_price: u32,
+        units: u32,
+        tie_break: Option<TieBreakRecord>,
         arena: &mut Vec<UserAccountData>,
+        drop_out_prices: &[(UserAccountData, Money)],
         state_tx: &mpsc::Sender<AuctionState>,
         pool: &SqlitePool,
         item: &AuctionItem,
@@ -112,63 +247,74 @@ pub async fn run_japanese_auction(
         // If the arena is closed, and has 0 members, then item cannot be sold. Resetting auction.
         if arena_is_closed {
             if arena.is_empty() {
+                sync_handle
+                    .send_event(AuctionEvent::ItemGotNoBids(item.id))
+                    .await;
                 state_tx.send(AuctionState::WaitingForItem).await?;
                 return e?;
             }
-            // If the arena has 1 member, then that is who won the auction.
+            // If the arena has no more members than there are units, then those are who won the auction,
+            // each getting one unit.
             //
             // NOTE: when multiple members have the same balance, and the money clock exceeds that balance,
-            // the members are removed from the arena one by one, and this function is run at each step.
-            // That way, there will be one definite winner.
-            // That winner pays the value on the money clock,
+            // they are tied, and the tie-break policy has decided which of them stay in the arena.
+            // That way, there will be definite winners.
+            // Each winner pays the value on the money clock,
             // or their total balance if it is smaller
             // This may undercount the item price by at most 1,
             // and ensures that no balance is negative.
+            // If that price did not reach the reserve price, that unit is not sold.
+            //
+            // If the tie is split, the tied members are all still in the arena, and share the units left.
 
-            if arena.len() == 1 {
-                let winner: &UserAccountData = arena.first().unwrap();
+            let is_split = tie_break.is_some_and(|t| t.policy == JapaneseTieBreak::Split);
+            if arena.len() <= units as usize || is_split {
                 let sponsorships = sync_handle.sponsorship_state.borrow().clone();
                 let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
 
-                let winner_has_access_to =
-                    Sponsorship::resolve_available_balance(winner.id, &users, &sponsorships);
-                let winner_pays = current_price.min(winner_has_access_to);
-
-                // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
-
-                let contributions = Sponsorship::calculate_contributions(
-                    winner.id,
-                    winner_pays,
-                    &users,
-                    &sponsorships,
-                );
-
-                let contributions_ids: Vec<_> =
-                    contributions.iter().map(|(u, b)| (u.id, *b)).collect();
-
-                apply_contributions(pool, item.id, winner.id, &contributions_ids).await?;
-
-                // Publish the state
-                let mut confirmation_code = String::new();
-                {
-                    let mut rng = rand::thread_rng();
-                    for _ in 0..4 {
-                        confirmation_code.push_str(&rng.gen_range(0..9).to_string());
+                // The members who could pay the price win their units outright,
+                // and the others won theirs in the tie.
+                let (outright, tied): (Vec<_>, Vec<_>) = arena
+                    .iter()
+                    .map(|winner| {
+                        let winner_has_access_to = available_until_limit(
+                            winner.id,
+                            &users,
+                            &sponsorships,
+                            drop_out_prices,
+                            item,
+                        );
+                        (winner.id, current_price.min(winner_has_access_to))
+                    })
+                    .partition(|(_, price)| *price == current_price);
+
+                // This fetches the latest states of users and sponsorships before each sale,
+                // so that the info is not outdated.
+                let mut sales = sell_units(pool, item, &outright, None).await?;
+                match tie_break {
+                    Some(tie_break) if is_split => {
+                        let tied: Vec<_> = tied.iter().map(|(id, _)| *id).collect();
+                        // The units that the members who could pay did not buy after all go to the tied members too.
+                        let units_left = units.saturating_sub(sales.len() as u32);
+                        sales.extend(
+                            sell_shared_units(
+                                pool,
+                                item,
+                                current_price,
+                                &tied,
+                                units_left,
+                                tie_break,
+                            )
+                            .await?,
+                        );
                     }
+                    _ => sales.extend(sell_units(pool, item, &tied, tie_break).await?),
                 }
 
-                let current_bidder = users
-                    .iter()
-                    .find(|u| u.id == winner.id)
-                    .expect("Final bidder not in user list?");
-                let state = AuctionState::SoldToMember {
-                    item: item.clone(),
-                    sold_for: winner_pays,
-                    sold_to: current_bidder.clone(),
-                    confirmation_code,
-                    contributions,
-                };
-                state_tx.send(state).await?;
+                // Publish the state
+                state_tx
+                    .send(AuctionState::after_units_sold(item.clone(), sales))
+                    .await?;
                 return e?;
             }
         }
@@ -177,8 +323,10 @@ pub async fn run_japanese_auction(
     }
 
     loop {
-        // If the arena is currently open, but it's past closing time, close it.
-        if !arena_is_closed
+        // If the arena is currently open, but it's past closing time, close it
+        // (this cannot happen while paused).
+        if paused_at.is_none()
+            && !arena_is_closed
             && arena_closes_for_entry
                 .duration_since(Instant::now())
                 .is_zero()
@@ -186,17 +334,22 @@ pub async fn run_japanese_auction(
         // only close the arena after request, and a timeout
         {
             arena_is_closed = true;
+            rate_changed_at = Instant::now();
             // Also, tell the system about this
             let bid_state = JapaneseAuctionBidState::ClockRunning {
                 currently_in_arena: arena.clone(),
                 current_price,
                 current_price_increase_per_100_seconds,
+                price_step,
                 arena_visibility_mode,
+                drop_out_prices: drop_out_prices.clone(),
+                left_on_limit: left_on_limit.clone(),
             };
             state_tx
                 .send(AuctionState::Bidding(BiddingState {
                     item: item.clone(),
                     active_bid: ActiveBidState::JapaneseAuctionBid(bid_state),
+                    is_paused: false,
                 }))
                 .await?;
         }
@@ -204,7 +357,10 @@ pub async fn run_japanese_auction(
         run_sold_check(
             arena_is_closed,
             current_price,
+            units,
+            None,
             &mut arena,
+            &drop_out_prices,
             &state_tx,
             pool,
             &item,
@@ -217,6 +373,11 @@ pub async fn run_japanese_auction(
                     JapaneseAuctionEvent::UserAction { user_id, item_id, action } => {
                         // If the item ID does not match, ignore this
                         if item_id != item.id { continue; }
+                        // While paused, nobody can enter or leave the arena (including by being kicked).
+                        if paused_at.is_some() {
+                            warn!("User ID {user_id} tried to {action:?} while the Japanese auction is paused; ignoring");
+                            continue;
+                        }
                         match action {
                             JapaneseAuctionAction::EnterArena => {
                                 // If the arena is closed, ignore this.
@@ -236,25 +397,37 @@ pub async fn run_japanese_auction(
 
                                 // Publish the current state (price, mode and arena members)
                                 let bid_state = if arena_is_closed {
-                                    JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                                    JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                                 } else {
-                                    JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                                    JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                                 };
-                                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
 
                             },
+                            JapaneseAuctionAction::SetDropOutPrice(limit) => {
+                                // Only a member in the arena can set their drop-out price.
+                                let user = match arena.iter().find(|u| u.id == user_id) {
+                                    None => {warn!("User ID {user_id} tried to set a Japanese drop-out price, but is not in the arena; ignoring"); continue;}
+                                    Some(user) => user.clone(),
+                                };
+                                drop_out_prices.retain(|(u, _)| u.id != user_id);
+                                if let Some(limit) = limit {
+                                    drop_out_prices.push((user, limit));
+                                }
+                            },
                             JapaneseAuctionAction::ExitArena => {
                                 // Remove the user from the arena, regardless of whether it's in there or not.
                                 arena.retain(|u| u.id != user_id);
-                                run_sold_check(arena_is_closed, current_price, &mut arena, &state_tx, pool, &item, &mut sync_handle,).await?;
+                                drop_out_prices.retain(|(u, _)| u.id != user_id);
+                                run_sold_check(arena_is_closed, current_price, units, None, &mut arena, &drop_out_prices, &state_tx, pool, &item, &mut sync_handle,).await?;
 
                                 // Publish the current state (price, mode and arena members)
                                 let bid_state = if arena_is_closed {
-                                    JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                                    JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                                 } else {
-                                    JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                                    JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                                 };
-                                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
 
                             },
                         }
@@ -262,82 +435,177 @@ pub async fn run_japanese_auction(
                     JapaneseAuctionEvent::NewPriceClockInterval { price_increase_per_100_seconds } => {
                         // The new interval is set immediately, but its first tick will happen half of the previous interval into the future.
                         // This ensures that a new tick will happen quickly, but also that a rapid sequence of updates doesn't cause many quick ticks.
-                        let new_period = Duration::from_secs_f32(100.0 / price_increase_per_100_seconds as f32);
-                        price_increase_interval = interval_at(Instant::now() + (price_increase_interval.period()/2), new_period);
+                        let price_increase_per_100_seconds = price_increase_per_100_seconds.max(1);
+                        if rate_schedule.take().is_some() {
+                            info!("Japanese auction for item {} has its rate changed by hand; stopping its rate schedule", item.id);
+                        }
+                        rate_changed_at = paused_at.unwrap_or_else(Instant::now);
+                        let new_period = clock_tick_period(price_increase_per_100_seconds, price_step);
+                        next_price_tick_at = paused_at.unwrap_or_else(Instant::now) + (price_increase_interval.period()/2);
+                        price_increase_interval = interval_at(next_price_tick_at, new_period);
                         current_price_increase_per_100_seconds = price_increase_per_100_seconds;
 
                         // Also, we need to send an update of the state now, so that the button receives the new change
                         let bid_state = if arena_is_closed {
-                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        } else {
+                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        };
+                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
+
+                    },
+                    JapaneseAuctionEvent::NewPriceStep(new_step) => {
+                        // The clock's interval changes like when the rate changes, so that the rate stays the same.
+                        price_step = new_step.max(1);
+                        let new_period = clock_tick_period(current_price_increase_per_100_seconds, price_step);
+                        next_price_tick_at = paused_at.unwrap_or_else(Instant::now) + (price_increase_interval.period()/2);
+                        price_increase_interval = interval_at(next_price_tick_at, new_period);
+
+                        let bid_state = if arena_is_closed {
+                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                         } else {
-                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                         };
-                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
+                    },
+                    JapaneseAuctionEvent::NewArenaClosingDuration(duration) => {
+                        arena_closing_secs = duration.as_secs() as u32;
 
+                        // If the arena is already closing, it closes this long after it started closing
+                        // (which may be right away).
+                        if arena_is_closing && !arena_is_closed {
+                            arena_closes_for_entry = arena_started_closing + duration;
+                        }
                     },
                     JapaneseAuctionEvent::NewArenaVisibilityMode(mode) => {
                         arena_visibility_mode = mode;
                         let bid_state = if arena_is_closed {
-                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                         } else {
-                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, arena_visibility_mode }
+                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
                         };
-                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
+                    },
+                    JapaneseAuctionEvent::Pause => {
+                        if paused_at.is_some() { continue; }
+                        paused_at = Some(Instant::now());
+                        let bid_state = if arena_is_closed {
+                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        } else {
+                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        };
+                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: true })).await?;
+                    },
+                    JapaneseAuctionEvent::Resume => {
+                        let paused_since = match paused_at.take() {
+                            None => continue,
+                            Some(t) => t,
+                        };
+                        let paused_for = paused_since.elapsed();
+                        // Shift the arena's closing time by how long the auction was paused,
+                        // so that the same time is left until it as when it was paused.
+                        arena_started_closing += paused_for;
+                        rate_changed_at += paused_for;
+                        arena_closes_for_entry += paused_for;
+
+                        // The price clock's next tick is as far away as it was when the auction was paused.
+                        // (While paused, the ticks do not move it, but a change of the rate or step may have.)
+                        let remaining = next_price_tick_at.saturating_duration_since(paused_since);
+                        next_price_tick_at = Instant::now() + remaining;
+                        price_increase_interval = interval_at(next_price_tick_at, price_increase_interval.period());
+
+                        let bid_state = if arena_is_closed {
+                            JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        } else {
+                            JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() }
+                        };
+                        state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: false })).await?;
                     },
                     JapaneseAuctionEvent::StartClosingArena => {
                         arena_is_closing = true;
-                        arena_closes_for_entry = Instant::now() + Duration::from_secs(10);
+                        arena_started_closing = paused_at.unwrap_or_else(Instant::now);
+                        arena_closes_for_entry = arena_started_closing + Duration::from_secs(arena_closing_secs as u64);
                     },
                 }
             }
 
             _ = price_increase_interval.tick() => {
-                // The price only increases when the arena is closed.
-                if !arena_is_closed {continue;}
+                // The price only increases when the arena is closed, and the auction is not paused.
+                // While paused, the time of the next tick is kept, so that it can be restored when resuming.
+                if !arena_is_closed || paused_at.is_some() {continue;}
+                next_price_tick_at = Instant::now() + price_increase_interval.period();
 
-                current_price += 1;
+                current_price = current_price.saturating_add(price_step);
                 let sponsorships = sync_handle.sponsorship_state.borrow().clone();
                 let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
 
                 // Remove members from the arena who have less than the money clock in their balance,
-                // in reverse order of the array, and check for the winner every time.
-                // This ensures that the member who entered first is the winner.
-                while let Some(member) = arena.iter().rev().find(|u| Sponsorship::resolve_available_balance(u.id, &users, &sponsorships) < current_price) {
-                    let id = member.id;
-                    arena.retain(|i| i.id != id);
-                    run_sold_check(arena_is_closed, current_price, &mut arena, &state_tx, pool, &item, &mut sync_handle).await?;
+                // or whose drop-out price the clock has passed,
+                // breaking a tie between them according to the policy, and check for the winner.
+                let tie = remove_members_who_cannot_pay(&mut arena, arena_is_closed, current_price, units, tie_break, |id| available_until_limit(id, &users, &sponsorships, &drop_out_prices, &item));
+
+                // The members who left because of their drop-out price are shown to the admin,
+                // but not those who would have had to leave anyway, because they ran out of balance.
+                for (user, limit) in drop_out_prices.iter().filter(|(u, limit)| *limit < current_price && !arena.iter().any(|a| a.id == u.id) && Sponsorship::resolve_available_balance(u.id, &users, &sponsorships, Some(&item)) >= current_price) {
+                    info!("Member {} left the Japanese arena for item {} automatically, at their drop-out price {limit}", user.id, item.id);
+                    left_on_limit.push((user.clone(), *limit));
                 }
+                drop_out_prices.retain(|(u, _)| arena.iter().any(|a| a.id == u.id));
+
+                run_sold_check(arena_is_closed, current_price, units, tie, &mut arena, &drop_out_prices, &state_tx, pool, &item, &mut sync_handle).await?;
 
                 // Publish the current auction state.
                 // It is ClockRunning, because we are increasing the price.
-                let bid_state = JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, arena_visibility_mode };
+                let bid_state = JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() };
 
-                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
             }
 
             _ = update_interval.tick() => {
+                // While the clock is running, change its rate if the schedule says so.
+                let schedule = rate_schedule.filter(|_| arena_is_closed && paused_at.is_none());
+                if let Some(schedule) = schedule {
+                    if let Some(new_rate) = schedule.next_rate(current_price_increase_per_100_seconds, rate_changed_at.elapsed().as_secs_f32(), arena.len(), rate_slowed_down) {
+                        rate_slowed_down = arena.len() <= schedule.slow_down_at as usize;
+                        rate_changed_at = Instant::now();
+                        if new_rate != current_price_increase_per_100_seconds {
+                            info!("Japanese auction for item {} changing its rate from {} to {} by schedule", item.id, current_price_increase_per_100_seconds, new_rate);
+
+                            // As when the admin changes the rate, the first tick at the new rate is half of the previous interval into the future.
+                            next_price_tick_at = Instant::now() + (price_increase_interval.period()/2);
+                            price_increase_interval = interval_at(next_price_tick_at, clock_tick_period(new_rate, price_step));
+                            current_price_increase_per_100_seconds = new_rate;
+
+                            let bid_state = JapaneseAuctionBidState::ClockRunning { currently_in_arena: arena.clone(), current_price, current_price_increase_per_100_seconds, price_step, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() };
+                            state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: false })).await?;
+                        }
+                    }
+                }
+
                 // Publish the current state (price, mode and arena members)
                 // ONLY IF the arena is currently open -> arena closing timer is counting down
                 // (if the arena is closed, this is handled in the price_increase_interval tick, where we send a message on every price change)
                 if !arena_is_closed {
-                    let bid_state = JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(Instant::now()).as_secs_f32()), current_price, current_price_increase_per_100_seconds, arena_visibility_mode };
-                    state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state) })).await?;
+                    let bid_state = JapaneseAuctionBidState::EnterArena { currently_in_arena: arena.clone(), seconds_until_arena_closes: arena_is_closing.then(||arena_closes_for_entry.duration_since(paused_at.unwrap_or_else(Instant::now)).as_secs_f32()), current_price, current_price_increase_per_100_seconds, price_step, arena_closing_secs, arena_visibility_mode, drop_out_prices: drop_out_prices.clone(), left_on_limit: left_on_limit.clone() };
+                    state_tx.send(AuctionState::Bidding(BiddingState { item: item.clone(), active_bid: ActiveBidState::JapaneseAuctionBid(bid_state), is_paused: paused_at.is_some() })).await?;
                 }
             }
 
             _ = sync_handle.sponsorship_state.changed() => {
+                // While paused, nobody leaves the arena:
+                // once the auction is resumed, the price clock's next tick removes those who cannot pay anymore.
+                if paused_at.is_some() { continue; }
+
                 // For all arena members, check whether they have access to enough money.
                 // If not, the member is removed.
                 let sponsorships = sync_handle.sponsorship_state.borrow().clone();
                 let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
 
-                // As above, we remove the members in sequence, and check for sale completion at every step.
-                // This is so that there is a definite winner in a tie.
-                while let Some(member) = arena.iter().rev().find(|u| Sponsorship::resolve_available_balance(u.id, &users, &sponsorships) < current_price) {
-                    let id = member.id;
-                    arena.retain(|i| i.id != id);
-                    run_sold_check(arena_is_closed, current_price, &mut arena, &state_tx, pool, &item, &mut sync_handle).await?;
-                }
+                // As above, a tie between the removed members is broken according to the policy,
+                // so that there is a definite winner.
+                let tie = remove_members_who_cannot_pay(&mut arena, arena_is_closed, current_price, units, tie_break, |id| available_until_limit(id, &users, &sponsorships, &drop_out_prices, &item));
+                drop_out_prices.retain(|(u, _)| arena.iter().any(|a| a.id == u.id));
+                run_sold_check(arena_is_closed, current_price, units, tie, &mut arena, &drop_out_prices, &state_tx, pool, &item, &mut sync_handle).await?;
             }
         }
     }
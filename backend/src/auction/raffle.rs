@@ -0,0 +1,281 @@
+use std::{sync::Arc, time::Duration};
+
+use communication::{
+    auction::state::{
+        ActiveBidState, AuctionItem, AuctionState, BiddingState, RaffleDraw, Sponsorship,
+        SponsorshipStatus, UnitSale,
+    },
+    forget_user_secrets, Money,
+};
+use sqlx::{query, SqlitePool};
+use tokio::{
+    sync::*,
+    time::{interval, Instant},
+};
+use tracing::{info, warn};
+
+use crate::auction::db_actions::{
+    apply_raffle_sale, confirmation_code, get_contribution_split, get_sponsorship_state,
+    get_units_left, get_user_state, mark_reserve_not_met,
+};
+
+use super::sync_handle;
+
+#[derive(Debug)]
+pub enum RaffleEvent {
+    /// A user is buying more tickets.
+    TicketsBought {
+        user_id: i64,
+        count: u32,
+        item_id: i64,
+    },
+
+    /// An admin has closed the entries before the end of the entry window.
+    CloseEntries,
+}
+
+pub async fn run_raffle(
+    item_id: i64,
+    ticket_price: Money,
+    entry_window: Duration,
+    pool: SqlitePool,
+    rx: Arc<Mutex<mpsc::Receiver<RaffleEvent>>>,
+    state_tx: mpsc::Sender<AuctionState>,
+    sync_handle: sync_handle::AuctionSyncHandle,
+) -> anyhow::Result<()> {
+    let pool = &pool;
+    let mut rx = rx.lock().await;
+
+    // Discard any tickets left over from a previous raffle.
+    while rx.try_recv().is_ok() {}
+
+    let row = query!(
+        r#"
+    SELECT
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
+    FROM auction_item
+    WHERE id=?
+    "#,
+        item_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let item = AuctionItem {
+        id: row.id,
+        name: row.name,
+        initial_price: row.initial_price as Money,
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
+    };
+
+    // A raffle gives away a single unit.
+    if get_units_left(pool, &item).await? == 0 || ticket_price == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    // How many tickets each member has bought, in the order they first bought any.
+    let mut tickets: Vec<(i64, u32)> = vec![];
+
+    let entries_close_at = Instant::now() + entry_window;
+
+    // This sends redundant copies of the auction state,
+    // in case a client has missed one, and checks whether the entries should close.
+    let mut update_interval = interval(Duration::from_millis(500));
+
+    loop {
+        tokio::select! {
+            Some(event) = rx.recv() => {
+                match event {
+                    RaffleEvent::TicketsBought { user_id, count, item_id } => {
+                        // If we receive an event about an item that is not the one we're raffling, ignore it.
+                        if item_id != item.id { continue; }
+
+                        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                        let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                        if !users.iter().any(|u| u.id == user_id) {
+                            warn!("Received raffle tickets with user ID={user_id}; no such user: hacking detected?");
+                            continue;
+                        }
+
+                        // The user's sponsorship group must be able to pay for all of their tickets together.
+                        let already_bought = tickets.iter().find(|(id, _)| *id == user_id).map_or(0, |(_, t)| *t);
+                        let total_tickets = already_bought.saturating_add(count);
+                        let total_price = (total_tickets as u64) * (ticket_price as u64);
+                        let accessible_amount = Sponsorship::resolve_available_balance(user_id, &users, &sponsorships, Some(&item));
+                        if total_price > accessible_amount as u64 {
+                            warn!("Received {count} raffle tickets with user ID={user_id}; user only has funds {accessible_amount}: hacking detected?");
+                            continue;
+                        }
+
+                        match tickets.iter_mut().find(|(id, _)| *id == user_id) {
+                            Some((_, t)) => *t = total_tickets,
+                            None => tickets.push((user_id, total_tickets)),
+                        }
+                    },
+                    RaffleEvent::CloseEntries => {
+                        draw_winner(pool, &item, ticket_price, &tickets, &state_tx).await?;
+                        return Ok(());
+                    },
+                }
+            }
+
+            _ = update_interval.tick() => {
+                if entries_close_at <= Instant::now() {
+                    draw_winner(pool, &item, ticket_price, &tickets, &state_tx).await?;
+                    return Ok(());
+                }
+            }
+        }
+
+        // Publish the current state
+        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+        let bid_state = BiddingState {
+            item: item.clone(),
+            active_bid: ActiveBidState::RaffleEntry {
+                ticket_price,
+                tickets: tickets
+                    .iter()
+                    .filter_map(|(id, t)| {
+                        users.iter().find(|u| u.id == *id).map(|u| (u.clone(), *t))
+                    })
+                    .collect(),
+                seconds_until_close: entries_close_at
+                    .saturating_duration_since(Instant::now())
+                    .as_secs_f32(),
+            },
+            is_paused: false,
+        };
+        state_tx.send(AuctionState::Bidding(bid_state)).await?;
+    }
+}
+
+/// Take the payment for the tickets from each entrant, draw the winning ticket, and record the sale.
+async fn draw_winner(
+    pool: &SqlitePool,
+    item: &AuctionItem,
+    ticket_price: Money,
+    tickets: &[(i64, u32)],
+    state_tx: &mpsc::Sender<AuctionState>,
+) -> anyhow::Result<()> {
+    // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+    let mut users = forget_user_secrets(get_user_state(pool).await?);
+    let mut sponsorships = get_sponsorship_state(pool).await?;
+    let split = get_contribution_split(pool).await?.record();
+
+    // The entrants are ordered by ID, so that the draw can be repeated from the stored record.
+    let mut tickets = tickets.to_vec();
+    tickets.sort_by_key(|(id, _)| *id);
+
+    // Sponsorships may have changed since the tickets were bought,
+    // so each entrant keeps only as many tickets as their sponsorship group can still pay for.
+    // The payments are worked out one entrant after another,
+    // so that a sponsor of several entrants is not charged more than they have.
+    let mut entries = vec![];
+    let mut payments = vec![];
+    for (user_id, count) in tickets {
+        if !users.iter().any(|u| u.id == user_id) {
+            continue;
+        }
+        let accessible_amount =
+            Sponsorship::resolve_available_balance(user_id, &users, &sponsorships, Some(item));
+        let count = count.min(accessible_amount / ticket_price);
+        if count == 0 {
+            warn!("Raffle entrant {user_id} cannot pay for any of their tickets anymore; leaving them out");
+            continue;
+        }
+
+        let contributions = Sponsorship::calculate_contributions(
+            user_id,
+            count * ticket_price,
+            &users,
+            &sponsorships,
+            item,
+            &split,
+        );
+        for (payer, amount) in contributions.iter() {
+            if let Some(u) = users.iter_mut().find(|u| u.id == payer.id) {
+                u.balance -= amount;
+            }
+            if let Some(s) = sponsorships.iter_mut().find(|s| {
+                s.status == SponsorshipStatus::Active
+                    && s.donor_id == payer.id
+                    && s.recepient_id == user_id
+            }) {
+                s.balance_remaining = s.balance_remaining.saturating_sub(*amount);
+            }
+        }
+        entries.push((user_id, count));
+        payments.push((user_id, contributions));
+    }
+
+    // If the tickets do not add up to the reserve price, nobody pays, and the item is not sold.
+    let total_price: Money = entries.iter().map(|(_, count)| count * ticket_price).sum();
+    if entries.is_empty()
+        || item
+            .reserve_price
+            .is_some_and(|reserve| total_price < reserve)
+    {
+        if !entries.is_empty() {
+            mark_reserve_not_met(pool, item.id).await?;
+        }
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    let draw = RaffleDraw {
+        seed: rand::random(),
+        ticket_price,
+        entries,
+    };
+    let winner_id = draw.winner().expect("Raffle with tickets has no winner?");
+    info!(
+        "Raffle for item {} drawn with seed {}: winner is {winner_id}",
+        item.id, draw.seed
+    );
+
+    let payment_ids: Vec<_> = payments
+        .iter()
+        .map(|(id, contributions)| (*id, contributions.iter().map(|(u, b)| (u.id, *b)).collect()))
+        .collect();
+    apply_raffle_sale(pool, item.id, winner_id, &draw, &payment_ids, split).await?;
+
+    // Everyone who paid for tickets is shown as a contributor to the sale.
+    let mut contributions: Vec<(_, Money)> = vec![];
+    for (payer, amount) in payments.into_iter().flat_map(|(_, c)| c) {
+        match contributions.iter_mut().find(|(u, _)| *u == payer.id) {
+            Some((_, total)) => *total += amount,
+            None => contributions.push((payer.id, amount)),
+        }
+    }
+    let users = forget_user_secrets(get_user_state(pool).await?);
+    let contributions = contributions
+        .into_iter()
+        .filter_map(|(id, amount)| {
+            users
+                .iter()
+                .find(|u| u.id == id)
+                .map(|u| (u.clone(), amount))
+        })
+        .collect();
+
+    let confirmation_code = confirmation_code();
+
+    let sold_to = users
+        .iter()
+        .find(|u| u.id == winner_id)
+        .expect("Winner not in user list?")
+        .clone();
+    let sale = UnitSale {
+        sold_to,
+        sold_for: total_price,
+        confirmation_code,
+        contributions,
+    };
+    state_tx
+        .send(AuctionState::after_units_sold(item.clone(), vec![sale]))
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::{sync::Arc, time::Duration};
+
+use communication::{
+    auction::state::{
+        ActiveBidState, AuctionItem, AuctionState, BiddingState, SealedBidPricing, Sponsorship,
+    },
+    forget_user_secrets, Money, UserAccountData,
+};
+use sqlx::{query, SqlitePool};
+use tokio::{sync::*, time::interval};
+use tracing::warn;
+
+use crate::auction::db_actions::{
+    get_sponsorship_state, get_units_left, get_user_state, sell_units,
+};
+
+use super::sync_handle;
+
+#[derive(Debug)]
+pub enum SealedBidAuctionEvent {
+    /// A user has submitted their hidden bid.
+    BidPlaced {
+        bidder_id: i64,
+        bid_amount: Money,
+        item_id: i64,
+    },
+
+    /// An admin has closed the bidding, so the bids should be revealed.
+    CloseBidding,
+}
+
+pub async fn run_sealed_bid_auction(
+    item_id: i64,
+    pricing: SealedBidPricing,
+    pool: SqlitePool,
+    rx: Arc<Mutex<mpsc::Receiver<SealedBidAuctionEvent>>>,
+    state_tx: mpsc::Sender<AuctionState>,
+    sync_handle: sync_handle::AuctionSyncHandle,
+) -> anyhow::Result<()> {
+    let pool = &pool;
+    let mut rx = rx.lock().await;
+
+    // Discard any bids left over from a previous sealed-bid auction.
+    while rx.try_recv().is_ok() {}
+
+    let row = query!(
+        r#"
+    SELECT
+        auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category
+    FROM auction_item
+    WHERE id=?
+    "#,
+        item_id
+    )
+    .fetch_one(pool)
+    .await?;
+    let item = AuctionItem {
+        id: row.id,
+        name: row.name,
+        initial_price: row.initial_price as Money,
+        reserve_price: row.reserve_price.map(|p| p as Money),
+        buy_now_price: row.buy_now_price.map(|p| p as Money),
+        quantity: row.quantity as u32,
+        category: row.category,
+    };
+
+    // A sealed-bid auction sells a single unit.
+    if get_units_left(pool, &item).await? == 0 {
+        state_tx.send(AuctionState::WaitingForItem).await?;
+        return Ok(());
+    }
+
+    // The bids, in the order that they were submitted.
+    // These must never be published before the bidding is closed.
+    let mut bids: Vec<(UserAccountData, Money)> = vec![];
+
+    // This sends redundant copies of the auction state,
+    // in case a client has missed one.
+    let mut update_interval = interval(Duration::from_millis(500));
+
+    loop {
+        tokio::select! {
+            Some(event) = rx.recv() => {
+                match event {
+                    SealedBidAuctionEvent::BidPlaced { bidder_id, bid_amount, item_id } => {
+                        // If we receive an event about an item that is not the one we're selling, ignore it.
+                        if item_id != item.id { continue; }
+
+                        // Every member can only submit one bid.
+                        if bids.iter().any(|(u, _)| u.id == bidder_id) { continue; }
+
+                        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                        let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                        let bidder = match users.iter().find(|u| u.id == bidder_id) {
+                            None => {
+                                warn!("Received sealed bid with user ID={bidder_id}; no such user: hacking detected?");
+                                continue;
+                            }
+                            Some(u) => u.clone(),
+                        };
+
+                        // The bid must be at least the initial price, and the user's sponsorship group must be able to pay it.
+                        if bid_amount < item.initial_price { continue; }
+                        let accessible_amount = Sponsorship::resolve_available_balance(bidder_id, &users, &sponsorships, Some(&item));
+                        if accessible_amount < bid_amount {
+                            warn!("Received sealed bid with user ID={bidder_id} and bid_amount={bid_amount}; user only has funds {accessible_amount}: hacking detected?");
+                            continue;
+                        }
+
+                        bids.push((bidder, bid_amount));
+                    },
+                    SealedBidAuctionEvent::CloseBidding => {
+                        // Fetch the latest states of users and sponsorships: important so that the info is not outdated.
+                        let users = forget_user_secrets(get_user_state(pool).await?);
+                        let sponsorships = get_sponsorship_state(pool).await?;
+
+                        // Sponsorships may have changed since the bids were placed,
+                        // so discard the bids that can no longer be paid.
+                        let mut ranked_bids: Vec<_> = bids
+                            .iter()
+                            .filter(|(u, amount)| Sponsorship::resolve_available_balance(u.id, &users, &sponsorships, Some(&item)) >= *amount)
+                            .filter_map(|(u, amount)| users.iter().find(|i| i.id == u.id).map(|u| (u.clone(), *amount)))
+                            .collect();
+
+                        // Highest bid first. The sort is stable, so among equal bids the earliest one wins.
+                        ranked_bids.sort_by_key(|(_, amount)| std::cmp::Reverse(*amount));
+
+                        // Special case: when nobody placed any bids,
+                        // just return to the item selection state.
+                        let (winner, winning_bid) = match ranked_bids.first() {
+                            None => {
+                                state_tx.send(AuctionState::WaitingForItem).await?;
+                                return Ok(());
+                            }
+                            Some(w) => w.clone(),
+                        };
+
+                        // In a second-price auction, the winner pays the runner-up's bid,
+                        // but never less than the initial price,
+                        // nor less than the reserve price if their own bid reached it.
+                        let price = match pricing {
+                            SealedBidPricing::FirstPrice => winning_bid,
+                            SealedBidPricing::SecondPrice => {
+                                let second_price = ranked_bids
+                                    .get(1)
+                                    .map(|(_, amount)| *amount)
+                                    .unwrap_or(item.initial_price)
+                                    .max(item.initial_price);
+                                match item.reserve_price {
+                                    Some(reserve) if winning_bid >= reserve => second_price.max(reserve),
+                                    _ => second_price,
+                                }
+                            }
+                        };
+
+                        let state = match sell_units(pool, &item, &[(winner.id, price)], None).await?.pop() {
+                            None => AuctionState::WaitingForItem,
+                            Some(sale) => AuctionState::SoldToMember {
+                                item,
+                                sold_for: sale.sold_for,
+                                sold_to: sale.sold_to,
+                                confirmation_code: sale.confirmation_code,
+                                contributions: sale.contributions,
+                                revealed_bids: Some(ranked_bids),
+                                other_units: vec![],
+                            },
+                        };
+                        state_tx.send(state).await?;
+
+                        return Ok(());
+                    },
+                }
+            }
+
+            _ = update_interval.tick() => {}
+        }
+
+        // Publish the current state: only who has bid, not how much.
+        let bid_state = BiddingState {
+            item: item.clone(),
+            active_bid: ActiveBidState::SealedBidAuctionBid {
+                bids_submitted_by: bids.iter().map(|(u, _)| u.clone()).collect(),
+                pricing,
+            },
+            is_paused: false,
+        };
+        state_tx.send(AuctionState::Bidding(bid_state)).await?;
+    }
+}
@@ -0,0 +1,332 @@
+use std::time::Duration;
+
+use communication::{
+    auction::state::{
+        AuctionItem, SilentAuctionItemState, SilentAuctionOutcome, SilentAuctionState, Sponsorship,
+    },
+    forget_user_secrets, Money, UserAccountData,
+};
+use sqlx::{query, SqlitePool};
+use tokio::{
+    sync::*,
+    time::{interval, Instant},
+};
+use tracing::warn;
+
+use crate::auction::db_actions::{get_units_left, sell_units};
+
+use super::sync_handle;
+
+#[derive(Debug)]
+pub enum SilentAuctionEvent {
+    /// An admin has opened an item for bidding, to close after the given duration.
+    /// If the item's bidding is still open, only its closing time is changed.
+    OpenItem { item_id: i64, duration: Duration },
+
+    /// An admin has closed the bidding on an item before its closing time.
+    CloseItem(i64),
+
+    /// An admin has taken an item out of the silent auction.
+    RemoveItem(i64),
+
+    /// A user has bid on one of the open items.
+    BidPlaced {
+        bidder_id: i64,
+        bid_amount: Money,
+        item_id: i64,
+    },
+}
+
+struct SilentBid {
+    bidder_id: i64,
+    amount: Money,
+
+    /// Increases with every bid in the silent auction, across all the items,
+    /// so that a member's most recent bid can be found.
+    seq: u64,
+}
+
+struct SilentItem {
+    item: AuctionItem,
+
+    /// The bids on this item, in the order that they were placed.
+    /// Each bid is higher than the one before it, so the last one is leading.
+    bids: Vec<SilentBid>,
+
+    closes_at: Instant,
+
+    /// Set once the bidding has closed.
+    outcome: Option<SilentAuctionOutcome>,
+}
+
+impl SilentItem {
+    fn is_open(&self) -> bool {
+        self.outcome.is_none()
+    }
+
+    fn leading_bid(&self) -> Option<&SilentBid> {
+        if self.is_open() {
+            self.bids.last()
+        } else {
+            None
+        }
+    }
+
+    fn minimum_bid(&self) -> Money {
+        match self.bids.last() {
+            None => self.item.initial_price,
+            Some(bid) => bid.amount.saturating_add(1),
+        }
+    }
+}
+
+/// How much the member has committed to the open items on which they are leading,
+/// not counting the item with the given ID.
+fn committed_by(items: &[SilentItem], bidder_id: i64, except_item_id: Option<i64>) -> Money {
+    items
+        .iter()
+        .filter(|i| Some(i.item.id) != except_item_id)
+        .filter_map(|i| i.leading_bid())
+        .filter(|b| b.bidder_id == bidder_id)
+        .map(|b| b.amount)
+        .fold(0, |a, b| a.saturating_add(b))
+}
+
+/// Withdraw the leading bids of members who can no longer pay for all of them at once.
+///
+/// While a member's leading bids add up to more than their sponsorship group can pay,
+/// their most recent leading bid is withdrawn, so that the bid before it on that item leads again.
+/// Returns whether any bid was withdrawn.
+fn withdraw_overcommitted_bids(
+    items: &mut [SilentItem],
+    users: &[UserAccountData],
+    sponsorships: &[Sponsorship],
+) -> bool {
+    let mut did_withdraw = false;
+    loop {
+        let mut leaders: Vec<i64> = items
+            .iter()
+            .filter_map(|i| i.leading_bid())
+            .map(|b| b.bidder_id)
+            .collect();
+        leaders.sort();
+        leaders.dedup();
+
+        // As when a bid is placed, each leading bid must be payable, together with the member's other leading bids,
+        // out of what their sponsorship group can spend on that item.
+        let overcommitted = leaders.into_iter().find(|bidder_id| {
+            let committed = committed_by(items, *bidder_id, None);
+            items
+                .iter()
+                .filter(|i| i.leading_bid().is_some_and(|b| b.bidder_id == *bidder_id))
+                .any(|i| {
+                    // A member who was deleted cannot pay for anything.
+                    let available = if users.iter().any(|u| u.id == *bidder_id) {
+                        Sponsorship::resolve_available_balance(
+                            *bidder_id,
+                            users,
+                            sponsorships,
+                            Some(&i.item),
+                        )
+                    } else {
+                        0
+                    };
+                    committed > available
+                })
+        });
+        let bidder_id = match overcommitted {
+            None => return did_withdraw,
+            Some(id) => id,
+        };
+
+        let most_recent = items
+            .iter_mut()
+            .filter(|i| i.leading_bid().is_some_and(|b| b.bidder_id == bidder_id))
+            .max_by_key(|i| i.leading_bid().map(|b| b.seq))
+            .expect("Overcommitted member is not leading on any item?");
+        let withdrawn = most_recent.bids.pop().expect("Leading bid disappeared?");
+        warn!(
+            "Member {bidder_id} can no longer pay for all of their silent auction bids; withdrew their bid of {} on item {}",
+            withdrawn.amount, most_recent.item.id
+        );
+        did_withdraw = true;
+    }
+}
+
+/// Close the bidding on the item, selling one unit to the highest bidder, if they can still pay.
+async fn close_item(pool: &SqlitePool, item: &mut SilentItem) -> anyhow::Result<()> {
+    let winners: Vec<_> = item
+        .leading_bid()
+        .map(|b| (b.bidder_id, b.amount))
+        .into_iter()
+        .collect();
+
+    // The item may have been sold out in the live auction while it was open here.
+    let sales = if get_units_left(pool, &item.item).await? == 0 {
+        vec![]
+    } else {
+        sell_units(pool, &item.item, &winners, None).await?
+    };
+
+    item.outcome = Some(match sales.into_iter().next() {
+        Some(sale) => SilentAuctionOutcome::Sold(sale),
+        None => SilentAuctionOutcome::NotSold,
+    });
+    Ok(())
+}
+
+fn silent_auction_state(items: &[SilentItem], users: &[UserAccountData]) -> SilentAuctionState {
+    let now = Instant::now();
+    SilentAuctionState {
+        items: items
+            .iter()
+            .map(|i| SilentAuctionItemState {
+                item: i.item.clone(),
+                current_bid: i.bids.last().and_then(|b| {
+                    users
+                        .iter()
+                        .find(|u| u.id == b.bidder_id)
+                        .map(|u| (u.clone(), b.amount))
+                }),
+                minimum_bid: i.minimum_bid(),
+                seconds_until_close: if i.is_open() {
+                    i.closes_at.saturating_duration_since(now).as_secs_f32()
+                } else {
+                    0.0
+                },
+                outcome: i.outcome.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Run the silent auction, which lives alongside the live auction
+/// for as long as the auction manager does.
+///
+/// Returns when the manager drops its end of the event channel.
+pub async fn run_silent_auction(
+    pool: SqlitePool,
+    mut rx: mpsc::Receiver<SilentAuctionEvent>,
+    state_tx: mpsc::Sender<SilentAuctionState>,
+    mut sync_handle: sync_handle::AuctionSyncHandle,
+) -> anyhow::Result<()> {
+    let pool = &pool;
+    let mut items: Vec<SilentItem> = vec![];
+    let mut bid_seq = 0;
+
+    // This closes the items whose time is up, and sends redundant copies of the state
+    // (which also keeps the time left up to date), in case a client has missed one.
+    let mut update_interval = interval(Duration::from_millis(500));
+
+    loop {
+        tokio::select! {
+            event = rx.recv() => {
+                let event = match event {
+                    None => return Ok(()),
+                    Some(e) => e,
+                };
+                match event {
+                    SilentAuctionEvent::OpenItem { item_id, duration } => {
+                        if let Some(open_item) = items.iter_mut().find(|i| i.item.id == item_id && i.is_open()) {
+                            open_item.closes_at = Instant::now() + duration;
+                        } else {
+                            let row = query!(
+                                r#"
+                            SELECT
+                                auction_item.id, auction_item.name, auction_item.initial_price, auction_item.reserve_price, auction_item.buy_now_price, auction_item.quantity, auction_item.category, auction_item.lot_id
+                            FROM auction_item
+                            WHERE id=?
+                            "#,
+                                item_id
+                            )
+                            .fetch_optional(pool)
+                            .await?;
+                            let row = match row {
+                                None => continue,
+                                Some(r) => r,
+                            };
+                            // Items in a lot are only sold with the lot.
+                            if row.lot_id.is_some() { continue; }
+                            let item = AuctionItem {
+                                id: row.id,
+                                name: row.name,
+                                initial_price: row.initial_price as Money,
+                                reserve_price: row.reserve_price.map(|p| p as Money),
+                                buy_now_price: row.buy_now_price.map(|p| p as Money),
+                                quantity: row.quantity as u32,
+                                category: row.category,
+                            };
+                            if get_units_left(pool, &item).await? == 0 { continue; }
+
+                            items.retain(|i| i.item.id != item_id);
+                            items.push(SilentItem { item, bids: vec![], closes_at: Instant::now() + duration, outcome: None });
+                        }
+                    },
+                    SilentAuctionEvent::CloseItem(item_id) => {
+                        match items.iter_mut().find(|i| i.item.id == item_id && i.is_open()) {
+                            None => continue,
+                            Some(item) => close_item(pool, item).await?,
+                        }
+                    },
+                    SilentAuctionEvent::RemoveItem(item_id) => {
+                        items.retain(|i| i.item.id != item_id);
+                    },
+                    SilentAuctionEvent::BidPlaced { bidder_id, bid_amount, item_id } => {
+                        let idx = match items.iter().position(|i| i.item.id == item_id && i.is_open()) {
+                            None => continue,
+                            Some(idx) => idx,
+                        };
+
+                        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                        let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                        if !users.iter().any(|u| u.id == bidder_id) {
+                            warn!("Received silent auction bid with user ID={bidder_id}; no such user: hacking detected?");
+                            continue;
+                        }
+
+                        if bid_amount < items[idx].minimum_bid() { continue; }
+
+                        // The bid must be payable out of what the member has not already committed to their leading bids on other items.
+                        // (If they are raising their own leading bid on this item, that bid is replaced, so it does not count.)
+                        let accessible_amount = Sponsorship::resolve_available_balance(bidder_id, &users, &sponsorships, Some(&items[idx].item))
+                            .saturating_sub(committed_by(&items, bidder_id, Some(item_id)));
+                        if accessible_amount < bid_amount {
+                            warn!("Received silent auction bid with user ID={bidder_id} and bid_amount={bid_amount}; user only has uncommitted funds {accessible_amount}");
+                            continue;
+                        }
+
+                        bid_seq += 1;
+                        items[idx].bids.push(SilentBid { bidder_id, amount: bid_amount, seq: bid_seq });
+                    },
+                }
+            },
+
+            _ = update_interval.tick() => {
+                let now = Instant::now();
+                let mut did_close = false;
+                for item in items.iter_mut().filter(|i| i.is_open() && i.closes_at <= now) {
+                    close_item(pool, item).await?;
+                    did_close = true;
+                }
+                // With nothing open, there is no countdown to keep up to date.
+                if !did_close && !items.iter().any(|i| i.is_open()) { continue; }
+            },
+
+            // A member's balance or sponsorships may shrink (for example, by buying something in the live auction),
+            // so that they cannot pay for all of their leading bids anymore.
+            Ok(()) = sync_handle.auction_members.changed() => {
+                let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                if !withdraw_overcommitted_bids(&mut items, &users, &sponsorships) { continue; }
+            },
+            Ok(()) = sync_handle.sponsorship_state.changed() => {
+                let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+                let sponsorships = sync_handle.sponsorship_state.borrow().clone();
+                if !withdraw_overcommitted_bids(&mut items, &users, &sponsorships) { continue; }
+            },
+        }
+
+        let users = forget_user_secrets(sync_handle.auction_members.borrow().clone());
+        state_tx.send(silent_auction_state(&items, &users)).await?;
+    }
+}
@@ -2,7 +2,9 @@ use std::{collections::HashMap, sync::Arc, sync::Weak};
 
 use communication::{
     admin_state::AdminState,
-    auction::state::{AuctionState, Sponsorship},
+    auction::state::{
+        AuctionState, ContributionSplit, SilentAuctionState, Sponsorship, SponsorshipLogEntry,
+    },
     ItemState, UserAccountDataWithSecrets,
 };
 use sqlx::SqlitePool;
@@ -41,6 +43,13 @@ pub struct AuctionSyncHandle {
     /// No processing is applied to these. Figure it out yourself.
     pub sponsorship_state: watch::Receiver<Vec<Sponsorship>>,
 
+    /// Holds the history of all the sponsorships, oldest first.
+    /// Each user should only be shown the entries about their own sponsorships.
+    pub sponsorship_log: watch::Receiver<Vec<SponsorshipLogEntry>>,
+
+    /// Stores the state of the silent auction, which runs alongside the live auction.
+    pub silent_auction_state: watch::Receiver<SilentAuctionState>,
+
     /// Holds weak references to values held by user threads.
     ///
     /// When a user thread begins, it obtains an Rc to this value,
@@ -111,8 +120,11 @@ impl AuctionSyncHandle {
         let (adstx, adsrx) = watch::channel(AdminState {
             holding_account_balance: 0,
             connected_users: vec![],
+            contribution_split: ContributionSplit::default(),
         });
         let (sptx, sprx) = watch::channel(vec![]);
+        let (spltx, splrx) = watch::channel(vec![]);
+        let (sastx, sasrx) = watch::channel(SilentAuctionState::default());
 
         let sync_handle = AuctionSyncHandle {
             auction_members: amrx,
@@ -123,6 +135,8 @@ impl AuctionSyncHandle {
             admin_state: adsrx,
             connection_drop_handles: Arc::new(Mutex::new(HashMap::new())),
             sponsorship_state: sprx,
+            sponsorship_log: splrx,
+            silent_auction_state: sasrx,
             connection_active_handles: Arc::new(Mutex::new(HashMap::new())),
         };
 
@@ -135,6 +149,8 @@ impl AuctionSyncHandle {
             isstx,
             adstx,
             sptx,
+            spltx,
+            sastx,
             sync_handle.clone(),
         ));
         sync_handle
@@ -153,6 +169,8 @@ async fn auction_manager(
     mut item_sale_state_tx: watch::Sender<Vec<ItemState>>,
     mut admin_state_tx: watch::Sender<AdminState>,
     mut sponsorship_state: watch::Sender<Vec<Sponsorship>>,
+    mut sponsorship_log: watch::Sender<Vec<SponsorshipLogEntry>>,
+    mut silent_auction_state: watch::Sender<SilentAuctionState>,
     sync_handle: AuctionSyncHandle,
 ) -> () {
     loop {
@@ -165,6 +183,8 @@ async fn auction_manager(
             &mut item_sale_state_tx,
             &mut admin_state_tx,
             &mut sponsorship_state,
+            &mut sponsorship_log,
+            &mut silent_auction_state,
             sync_handle.clone(),
         )
         .await;
@@ -3,8 +3,12 @@ use std::{sync::Arc, time::Duration};
 use axum::extract::ws::{close_code, Message, WebSocket};
 
 use communication::{
-    auction::state::AuctionState, decode, encode, forget_user_secrets, ServerMessage,
-    UserAccountData, UserAccountDataWithSecrets, UserClientMessage, WithTimestamp,
+    auction::state::{
+        ActiveBidState, AuctionState, BiddingState, SilentAuctionOutcome, SilentAuctionState,
+        SoftClosePolicy, SponsorshipLogEntry, UnitSale,
+    },
+    decode, encode, forget_user_secrets, ServerMessage, UserAccountData,
+    UserAccountDataWithSecrets, UserClientMessage, WithTimestamp,
 };
 use tokio::time::interval;
 #[allow(unused_imports)]
@@ -15,6 +19,121 @@ use crate::{
     close_socket,
 };
 
+/// Adjust the auction state for sending to the given user:
+/// map SoldToMember to SoldToYou (if the user bought any of the units) or SoldToSomeoneElse,
+/// and hide the other members' secret maximum bids, the end time of a candle auction, and the items' reserve prices.
+fn personalize_auction_state(state: AuctionState, user_id: i64) -> AuctionState {
+    let mut state = match state {
+        AuctionState::SoldToMember {
+            item,
+            sold_for,
+            sold_to,
+            confirmation_code,
+            contributions,
+            revealed_bids,
+            other_units,
+        } => {
+            // If several units were sold, the user may have bought any one of them.
+            let mut units = vec![UnitSale {
+                sold_to,
+                sold_for,
+                confirmation_code,
+                contributions,
+            }];
+            units.extend(other_units);
+            match units.iter().position(|u| u.sold_to.id == user_id) {
+                Some(mine) => {
+                    let mine = units.remove(mine);
+                    AuctionState::SoldToYou {
+                        item,
+                        sold_for: mine.sold_for,
+                        confirmation_code: mine.confirmation_code,
+                        contributions: mine.contributions,
+                        revealed_bids,
+                        other_units: units.into_iter().map(|u| (u.sold_to, u.sold_for)).collect(),
+                    }
+                }
+                None => {
+                    let first = units.remove(0);
+                    AuctionState::SoldToSomeoneElse {
+                        item,
+                        sold_to: first.sold_to,
+                        sold_for: first.sold_for,
+                        contributions: first.contributions,
+                        revealed_bids,
+                        other_units: units.into_iter().map(|u| (u.sold_to, u.sold_for)).collect(),
+                    }
+                }
+            }
+        }
+        AuctionState::Bidding(mut bid_state) => {
+            match &mut bid_state.active_bid {
+                ActiveBidState::EnglishAuctionBid {
+                    proxy_bids,
+                    soft_close_policy,
+                    seconds_until_commit,
+                    ..
+                } => {
+                    proxy_bids.retain(|(u, _)| u.id == user_id);
+
+                    // The end of a candle auction is a secret.
+                    if let SoftClosePolicy::Candle { .. } = soft_close_policy {
+                        *seconds_until_commit = 0.0;
+                    }
+                }
+                // The drop-out prices are a secret.
+                ActiveBidState::JapaneseAuctionBid(state) => state.retain_only_for(user_id),
+                _ => {}
+            }
+            AuctionState::Bidding(bid_state)
+        }
+        other => other,
+    };
+
+    match &mut state {
+        AuctionState::ShowingItemBeforeBidding(item)
+        | AuctionState::Bidding(BiddingState { item, .. })
+        | AuctionState::SoldToSomeoneElse { item, .. }
+        | AuctionState::SoldToYou { item, .. }
+        | AuctionState::SoldToMember { item, .. } => item.reserve_price = None,
+        AuctionState::AuctionOver(report) => {
+            for item_state in report.items.iter_mut() {
+                item_state.item.reserve_price = None;
+            }
+        }
+        AuctionState::WaitingForAuction | AuctionState::WaitingForItem => {}
+    }
+    state
+}
+
+/// Adjust the silent auction state for sending to the given user:
+/// hide the items' reserve prices, and the confirmation codes of items that someone else bought.
+fn personalize_silent_auction_state(
+    mut state: SilentAuctionState,
+    user_id: i64,
+) -> SilentAuctionState {
+    for item_state in state.items.iter_mut() {
+        item_state.item.reserve_price = None;
+        if let Some(SilentAuctionOutcome::Sold(sale)) = &mut item_state.outcome {
+            if sale.sold_to.id != user_id {
+                sale.confirmation_code.clear();
+            }
+        }
+    }
+    state
+}
+
+/// Keep only the history of the sponsorships in which the user is the donor or the recepient.
+fn personalize_sponsorship_log(
+    log: &[SponsorshipLogEntry],
+    user_id: i64,
+) -> Vec<SponsorshipLogEntry> {
+    log.iter()
+        .filter(|e| e.donor_id == user_id || e.recepient_id == user_id)
+        .cloned()
+        .collect()
+}
+
 macro_rules! send {
     ($s:expr, $v:expr) => {
         $s.send(Message::Binary(encode::<ServerMessage>(&$v.into())))
@@ -88,9 +207,30 @@ pub async fn handle_socket(
                                     UserClientMessage::BidInEnglishAuction { item_id, bid_amount } => {
                                         sync_handle.send_event(AuctionEvent::EnglishAuctionAction(crate::auction::EnglishAuctionEvent::BidPlaced { bidder_id: user.id, bid_amount, item_id})).await;
                                     },
+                                    UserClientMessage::SetProxyBidInEnglishAuction { item_id, max_bid } => {
+                                        sync_handle.send_event(AuctionEvent::EnglishAuctionAction(crate::auction::EnglishAuctionEvent::ProxyBidSet { bidder_id: user.id, max_bid, item_id })).await;
+                                    },
                                     UserClientMessage::JapaneseAuctionAction { item_id, action } => {
                                         sync_handle.send_event(AuctionEvent::JapaneseAuctionAction(crate::auction::JapaneseAuctionEvent::UserAction { user_id: user.id, item_id, action })).await;
                                     },
+                                    UserClientMessage::ClaimInDutchAuction { item_id } => {
+                                        sync_handle.send_event(AuctionEvent::DutchAuctionAction(crate::auction::DutchAuctionEvent::Claim { user_id: user.id, item_id })).await;
+                                    },
+                                    UserClientMessage::BidInSealedBidAuction { item_id, bid_amount } => {
+                                        sync_handle.send_event(AuctionEvent::SealedBidAuctionAction(crate::auction::SealedBidAuctionEvent::BidPlaced { bidder_id: user.id, bid_amount, item_id })).await;
+                                    },
+                                    UserClientMessage::BidInAllPayAuction { item_id, bid_amount } => {
+                                        sync_handle.send_event(AuctionEvent::AllPayAuctionAction(crate::auction::AllPayAuctionEvent::BidPlaced { bidder_id: user.id, bid_amount, item_id })).await;
+                                    },
+                                    UserClientMessage::BuyRaffleTickets { item_id, count } => {
+                                        sync_handle.send_event(AuctionEvent::RaffleAction(crate::auction::RaffleEvent::TicketsBought { user_id: user.id, count, item_id })).await;
+                                    },
+                                    UserClientMessage::BidInSilentAuction { item_id, bid_amount } => {
+                                        sync_handle.send_event(AuctionEvent::SilentAuctionAction(crate::auction::SilentAuctionEvent::BidPlaced { bidder_id: user.id, bid_amount, item_id })).await;
+                                    },
+                                    UserClientMessage::BuyNow { item_id } => {
+                                        sync_handle.send_event(AuctionEvent::BuyNow { user_id: user.id, item_id }).await;
+                                    },
                                     UserClientMessage::SetIsAcceptingSponsorships(state) => {
                                         sync_handle.send_event(AuctionEvent::SetIsAcceptingSponsorships { user_id: user.id, is_accepting_sponsorships: state }).await;
                                     },
@@ -100,12 +240,17 @@ pub async fn handle_socket(
                                     UserClientMessage::TryActivateSponsorshipCode(code) => {
                                         sync_handle.send_event(AuctionEvent::TryActivateSponsorshipCode { user_id: user.id, code }).await;
                                     },
+                                    UserClientMessage::RequestSponsorship { donor_id, amount } => {
+                                        sync_handle.send_event(AuctionEvent::RequestSponsorship { user_id: user.id, donor_id, amount }).await;
+                                    },
                                     UserClientMessage::SetSponsorshipBalance { sponsorship_id, balance } => {
                                         sync_handle.send_event(AuctionEvent::UpdateSponsorship{
                                             actor_id: user.id,
                                             sponsorship_id,
                                             new_amount: Some(balance),
                                             new_status: None,
+                                            new_max_percent: None,
+                                            new_only_for: None,
                                         }).await;
                                     },
                                     UserClientMessage::SetSponsorshipStatus { sponsorship_id, status } => {
@@ -114,6 +259,28 @@ pub async fn handle_socket(
                                             sponsorship_id,
                                             new_amount: None,
                                             new_status: Some(status),
+                                            new_max_percent: None,
+                                            new_only_for: None,
+                                        }).await;
+                                    },
+                                    UserClientMessage::SetSponsorshipPercentCap { sponsorship_id, max_percent } => {
+                                        sync_handle.send_event(AuctionEvent::UpdateSponsorship{
+                                            actor_id: user.id,
+                                            sponsorship_id,
+                                            new_amount: None,
+                                            new_status: None,
+                                            new_max_percent: Some(max_percent),
+                                            new_only_for: None,
+                                        }).await;
+                                    },
+                                    UserClientMessage::SetSponsorshipScope { sponsorship_id, only_for } => {
+                                        sync_handle.send_event(AuctionEvent::UpdateSponsorship{
+                                            actor_id: user.id,
+                                            sponsorship_id,
+                                            new_amount: None,
+                                            new_status: None,
+                                            new_max_percent: None,
+                                            new_only_for: Some(only_for),
                                         }).await;
                                     },
 
@@ -130,17 +297,7 @@ pub async fn handle_socket(
             },
             _ = sync_handle.auction_state.changed() => {
                 let latest_state = sync_handle.auction_state.borrow().clone();
-                // Map SoldToMember to SoldToYou or SoldToSomeoneElse
-                let latest_state = match latest_state {
-                    AuctionState::SoldToMember{ item, sold_for, sold_to, confirmation_code, contributions } => {
-                        if sold_to.id == user.id {
-                            AuctionState::SoldToYou { item, sold_for, confirmation_code, contributions }
-                        } else {
-                            AuctionState::SoldToSomeoneElse { item, sold_to, sold_for, contributions }
-                        }
-                    },
-                    other => other
-                };
+                let latest_state = personalize_auction_state(latest_state, user.id);
                 send!(socket, ServerMessage::AuctionState(latest_state.into()));
             },
             _ = sync_handle.auction_members.changed() => {
@@ -155,6 +312,15 @@ pub async fn handle_socket(
                 let latest_state = sync_handle.sponsorship_state.borrow().clone();
                 send!(socket, ServerMessage::SponsorshipState(latest_state.into()));
             },
+            _ = sync_handle.sponsorship_log.changed() => {
+                let latest_log = personalize_sponsorship_log(&sync_handle.sponsorship_log.borrow(), user.id);
+                send!(socket, ServerMessage::SponsorshipLog(latest_log.into()));
+            },
+            _ = sync_handle.silent_auction_state.changed() => {
+                let latest_state = sync_handle.silent_auction_state.borrow().clone();
+                let latest_state = personalize_silent_auction_state(latest_state, user.id);
+                send!(socket, ServerMessage::SilentAuctionState(latest_state.into()));
+            },
 
 
             _ = refresh_interval.tick() => {
@@ -179,22 +345,16 @@ pub async fn handle_socket(
                 send!(socket, ServerMessage::AuctionMembers(members));
 
                 // Also resend the auction state, just in case it were lost.
-                // This is copy-pasted from the case above when the auction state changes
                 let latest_state = sync_handle.auction_state.borrow().clone();
-                // Map SoldToMember to SoldToYou or SoldToSomeoneElse
-                let latest_state = match latest_state {
-                    AuctionState::SoldToMember{ item, sold_for, sold_to, confirmation_code, contributions } => {
-                        if sold_to.id == user.id {
-                            AuctionState::SoldToYou { item, sold_for, confirmation_code, contributions }
-                        } else {
-                            AuctionState::SoldToSomeoneElse { item, sold_to, sold_for, contributions }
-                        }
-                    },
-                    other => other
-                };
+                let latest_state = personalize_auction_state(latest_state, user.id);
                 send!(socket, ServerMessage::AuctionState(latest_state.into()));
                 let latest_state = sync_handle.sponsorship_state.borrow().clone();
                 send!(socket, ServerMessage::SponsorshipState(latest_state.into()));
+                let latest_state = sync_handle.silent_auction_state.borrow().clone();
+                let latest_state = personalize_silent_auction_state(latest_state, user.id);
+                send!(socket, ServerMessage::SilentAuctionState(latest_state.into()));
+                let latest_log = personalize_sponsorship_log(&sync_handle.sponsorship_log.borrow(), user.id);
+                send!(socket, ServerMessage::SponsorshipLog(latest_log.into()));
 
             },
         }
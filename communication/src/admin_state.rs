@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::Money;
+use crate::{auction::state::ContributionSplit, Money};
 
 /// State info only useful for the admin connection
 
@@ -11,4 +11,7 @@ pub struct AdminState {
 
     /// This is the list of user IDs who currently have an open connection to the server.
     pub connected_users: Vec<i64>,
+
+    /// How the price of each sale is currently split between the buyer and their sponsors.
+    pub contribution_split: ContributionSplit,
 }
@@ -14,4 +14,9 @@ pub enum JapaneseAuctionAction {
 
     /// Exit the arena, forfeiting the current bid
     ExitArena,
+
+    /// While in the arena, set the highest price to stay in the arena for,
+    /// so that the server exits the arena automatically when the clock passes it.
+    /// If None, remove the drop-out price.
+    SetDropOutPrice(Option<Money>),
 }
@@ -25,6 +25,10 @@ pub enum AuctionState {
         sold_to: UserAccountData,
         sold_for: Money,
         contributions: Vec<(UserAccountData, Money)>,
+        revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+
+        /// If several units of the item were sold, the other buyers and their prices.
+        other_units: Vec<(UserAccountData, Money)>,
     },
 
     /// Item was sold to you (client only)
@@ -33,6 +37,10 @@ pub enum AuctionState {
         sold_for: Money,
         confirmation_code: String, // show this to the auctioneer to retrieve item
         contributions: Vec<(UserAccountData, Money)>,
+        revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+
+        /// If several units of the item were sold, the other buyers and their prices.
+        other_units: Vec<(UserAccountData, Money)>,
     },
 
     /// Item was sold to an auction member, who will retrieve it (admin only)
@@ -42,14 +50,65 @@ pub enum AuctionState {
         sold_to: UserAccountData,
         confirmation_code: String,
         contributions: Vec<(UserAccountData, Money)>,
+
+        /// If the auction had hidden bids, these are all of them, ranked from highest to lowest.
+        revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+
+        /// If several units of the item were sold, the sales of the units other than the above one.
+        other_units: Vec<UnitSale>,
     },
 }
 
+impl AuctionState {
+    /// The state announcing that the given units of the item were sold,
+    /// or, if none were, that the auction is waiting for the next item.
+    pub fn after_units_sold(item: AuctionItem, mut sales: Vec<UnitSale>) -> Self {
+        if sales.is_empty() {
+            return AuctionState::WaitingForItem;
+        }
+        let first = sales.remove(0);
+        AuctionState::SoldToMember {
+            item,
+            sold_for: first.sold_for,
+            sold_to: first.sold_to,
+            confirmation_code: first.confirmation_code,
+            contributions: first.contributions,
+            revealed_bids: None,
+            other_units: sales,
+        }
+    }
+}
+
+/// The sale of a single unit of an item, to one member.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct UnitSale {
+    pub sold_to: UserAccountData,
+    pub sold_for: Money,
+    pub confirmation_code: String,
+    pub contributions: Vec<(UserAccountData, Money)>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct AuctionItem {
     pub id: i64,
     pub name: String,
     pub initial_price: Money,
+
+    /// If the bidding ends below this, the item is not sold.
+    /// This is only known to the admin: it is removed before sending the item to members.
+    pub reserve_price: Option<Money>,
+
+    /// Any member who can afford this can buy the item immediately,
+    /// before the bidding starts, or in an English auction until the bids reach this.
+    pub buy_now_price: Option<Money>,
+
+    /// How many identical units of this item there are.
+    /// Each unit is sold to a different member.
+    pub quantity: u32,
+
+    /// What kind of item this is, such as "artwork".
+    /// Sponsorships can be limited to the items in some categories.
+    pub category: Option<String>,
 }
 
 /// Structure representing the outcome of the auction, with the members' final balances and sales.
@@ -59,32 +118,405 @@ pub struct AuctionReport {
     pub items: Vec<ItemState>,
 }
 
+/// The silent auction runs alongside the live auction:
+/// several items are open for bidding at the same time, each until its own closing time.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct SilentAuctionState {
+    pub items: Vec<SilentAuctionItemState>,
+}
+
+impl SilentAuctionState {
+    /// How much money the member has committed to the open items on which they are the highest bidder,
+    /// not counting the item with the given ID.
+    ///
+    /// A new bid can only be paid out of what is left of the member's available balance after this.
+    pub fn committed_by(&self, user_id: i64, except_item_id: Option<i64>) -> Money {
+        self.items
+            .iter()
+            .filter(|i| i.outcome.is_none() && Some(i.item.id) != except_item_id)
+            .filter_map(|i| i.current_bid.as_ref())
+            .filter(|(u, _)| u.id == user_id)
+            .map(|(_, amount)| *amount)
+            .fold(0, |a, b| a.saturating_add(b))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SilentAuctionItemState {
+    pub item: AuctionItem,
+
+    /// The highest bid so far, and who placed it.
+    pub current_bid: Option<(UserAccountData, Money)>,
+
+    /// The smallest amount that the next bid can be.
+    pub minimum_bid: Money,
+
+    /// Amount of time until the bidding on this item closes (zero once it has closed)
+    pub seconds_until_close: f32,
+
+    /// Once the bidding has closed, what happened to the item.
+    pub outcome: Option<SilentAuctionOutcome>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum SilentAuctionOutcome {
+    /// The item was sold to the highest bidder.
+    /// Members other than the buyer are sent this without the confirmation code.
+    Sold(UnitSale),
+
+    /// Nobody bid, the highest bidder could no longer pay, or the reserve price was not met.
+    NotSold,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct BiddingState {
     pub item: AuctionItem,
     pub active_bid: ActiveBidState,
+
+    /// The admin has paused the auction: its timer or clock is stopped, and no actions are accepted.
+    /// Only English and Japanese auctions can be paused.
+    pub is_paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub enum ActiveBidState {
     /// The current auction is an [English Auction](https://en.wikipedia.org/wiki/English_auction)
     EnglishAuctionBid {
-        /// Current bid amount and person
+        /// Current bid amount and person.
+        /// If several units are being sold, this is the lowest of the winning bids,
+        /// which a new bid needs to beat.
         current_bid_amount: Money,
         current_bidder: UserAccountData,
 
+        /// How many units are being sold in this auction
+        units: u32,
+
+        /// The bids that would win the units if the auction ended now, from highest to lowest
+        winning_bids: Vec<(UserAccountData, Money)>,
+
         /// Currently allowed minimum increment
         minimum_increment: Money,
 
+        /// The rule by which the minimum increment is calculated from the current bid
+        increment_rule: IncrementRule,
+
         /// Amount of time until the current bid is locked in (resets on every bid)
         seconds_until_commit: f32,
 
         /// The maximum amount of time that a bid takes to lock in (to which it's reset each bid)
         max_millis_until_commit: u128,
+
+        /// How the time until the bid is locked in changes when bids are placed
+        soft_close_policy: SoftClosePolicy,
+
+        /// Secret maximum bids, on whose behalf the auction bids automatically.
+        /// The admin sees all of them, but a member is only sent their own.
+        proxy_bids: Vec<(UserAccountData, Money)>,
     },
 
     /// The current auction is an [ascending clock auction](https://en.wikipedia.org/wiki/Japanese_auction)
     JapaneseAuctionBid(JapaneseAuctionBidState),
+
+    /// The current auction is a [descending clock auction](https://en.wikipedia.org/wiki/Dutch_auction)
+    DutchAuctionBid {
+        /// The price at which the item can be claimed right now
+        current_price: Money,
+
+        /// How much the price decreases per 100 seconds
+        current_price_decrease_per_100_seconds: Money,
+
+        /// The price below which the clock will not go (the item's initial price).
+        /// If the clock reaches this and nobody claims the item, it is not sold.
+        minimum_price: Money,
+    },
+
+    /// The current auction is a [sealed-bid auction](https://en.wikipedia.org/wiki/First-price_sealed-bid_auction),
+    /// either first-price or second-price.
+    /// The bid amounts are kept secret until the admin closes the bidding.
+    SealedBidAuctionBid {
+        /// The members who have submitted a bid so far (but not how much they bid)
+        bids_submitted_by: Vec<UserAccountData>,
+
+        /// How the price that the winner pays is determined
+        pricing: SealedBidPricing,
+    },
+
+    /// The current auction is an [all-pay auction](https://en.wikipedia.org/wiki/All-pay_auction):
+    /// the highest bidder wins the item, but every bidder pays their last bid.
+    AllPayAuctionBid {
+        /// The last bid of each member who has bid, from highest to lowest
+        bids: Vec<(UserAccountData, Money)>,
+
+        /// The smallest amount that the next bid can be
+        minimum_bid: Money,
+
+        /// Amount of time until the bidding is over (resets on every bid)
+        seconds_until_commit: f32,
+
+        /// The amount of time to which the timer is reset on every bid
+        max_millis_until_commit: u128,
+    },
+
+    /// The item is being raffled: members buy tickets, and when the entries close,
+    /// one ticket is drawn at random to win the item. Every ticket is paid for, winning or not.
+    RaffleEntry {
+        /// The price of a single ticket
+        ticket_price: Money,
+
+        /// How many tickets each member has bought so far
+        tickets: Vec<(UserAccountData, u32)>,
+
+        /// Amount of time until the entries close and the winner is drawn
+        seconds_until_close: f32,
+    },
+}
+
+/// The record of a raffle draw, from which anyone can work out the winner again.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct RaffleDraw {
+    pub seed: u64,
+    pub ticket_price: Money,
+
+    /// How many tickets each entrant had, by user ID, in ascending order of ID.
+    pub entries: Vec<(i64, u32)>,
+}
+
+impl RaffleDraw {
+    /// Draw the winning ticket, giving every ticket the same chance, and return who held it.
+    /// The same seed and entries always give the same winner.
+    /// Returns None if there are no tickets.
+    #[cfg(feature = "rand")]
+    pub fn winner(&self) -> Option<i64> {
+        use rand::prelude::*;
+        let total_tickets: u64 = self.entries.iter().map(|(_, t)| *t as u64).sum();
+        if total_tickets == 0 {
+            return None;
+        }
+
+        // The tickets are numbered in the order of the entries.
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut winning_ticket = rng.gen_range(0..total_tickets);
+        for (user_id, tickets) in self.entries.iter() {
+            if winning_ticket < *tickets as u64 {
+                return Some(*user_id);
+            }
+            winning_ticket -= *tickets as u64;
+        }
+        unreachable!("Winning ticket is past the last ticket?")
+    }
+}
+
+/// How the winner of a sealed-bid auction pays for the item.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum SealedBidPricing {
+    /// The winner pays the amount of their own bid.
+    FirstPrice,
+
+    /// The winner pays the amount of the second-highest bid,
+    /// or the item's initial price if there was no other bid.
+    /// This is a [Vickrey auction](https://en.wikipedia.org/wiki/Vickrey_auction).
+    SecondPrice,
+}
+
+/// Rule for how much a new bid in an English auction must exceed the current bid by.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum IncrementRule {
+    /// The increment is always the same amount.
+    Fixed(Money),
+
+    /// The increment is this percentage of the current bid, rounded up.
+    Percent(u32),
+
+    /// The increment depends on the bracket that the current bid is in.
+    Tiered {
+        /// Pairs of (upper bound, increment), in ascending order of upper bound:
+        /// a bid below the upper bound uses that increment.
+        brackets: Vec<(Money, Money)>,
+
+        /// The increment for bids that are above all the brackets.
+        otherwise: Money,
+    },
+}
+
+impl Default for IncrementRule {
+    fn default() -> Self {
+        Self::Fixed(1)
+    }
+}
+
+impl IncrementRule {
+    /// The minimum amount by which the next bid must exceed the given current bid.
+    /// This is never less than 1, so that bids always go up.
+    pub fn increment_for(&self, current_bid: Money) -> Money {
+        let increment = match self {
+            IncrementRule::Fixed(amount) => *amount,
+            IncrementRule::Percent(percent) => {
+                let increment = (current_bid as u64 * *percent as u64).div_ceil(100);
+                increment.min(Money::MAX as u64) as Money
+            }
+            IncrementRule::Tiered {
+                brackets,
+                otherwise,
+            } => brackets
+                .iter()
+                .find(|(upper_bound, _)| current_bid < *upper_bound)
+                .map_or(*otherwise, |(_, increment)| *increment),
+        };
+        increment.max(1)
+    }
+}
+
+/// The rule is written as a fixed amount (`5`), a percentage (`10%`),
+/// or a list of brackets followed by the increment for above them (`100:1, 500:5, 10`).
+impl std::fmt::Display for IncrementRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IncrementRule::Fixed(amount) => write!(f, "{amount}"),
+            IncrementRule::Percent(percent) => write!(f, "{percent}%"),
+            IncrementRule::Tiered {
+                brackets,
+                otherwise,
+            } => {
+                for (upper_bound, increment) in brackets {
+                    write!(f, "{upper_bound}:{increment}, ")?;
+                }
+                write!(f, "{otherwise}")
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for IncrementRule {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(percent) = s.strip_suffix('%') {
+            return Ok(IncrementRule::Percent(percent.trim().parse()?));
+        }
+
+        let mut parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        let otherwise = parts.pop().unwrap_or_default().parse()?;
+        if parts.is_empty() {
+            return Ok(IncrementRule::Fixed(otherwise));
+        }
+
+        let mut brackets = vec![];
+        for part in parts {
+            let (upper_bound, increment) = part.split_once(':').unwrap_or((part, ""));
+            brackets.push((upper_bound.trim().parse()?, increment.trim().parse()?));
+        }
+        brackets.sort_by_key(|(upper_bound, _)| *upper_bound);
+        Ok(IncrementRule::Tiered {
+            brackets,
+            otherwise,
+        })
+    }
+}
+
+/// What to do when an English or Japanese auction ends without selling the item:
+/// show it again at a lower initial price, and then auction it again.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct RerunPolicy {
+    /// How much the item's initial price is lowered each time, in percent.
+    pub price_reduction_percent: u32,
+
+    /// How long the item is shown at its new price before the auction starts again, in seconds.
+    pub pause_secs: u32,
+
+    /// How many more times the item is auctioned again, at most.
+    pub max_reruns: u32,
+
+    /// If set, the item is auctioned again with this kind of auction,
+    /// with its default settings if the item was not already auctioned with it.
+    /// Otherwise, it is auctioned again in the same way.
+    pub switch_to: Option<RerunAuctionKind>,
+}
+
+impl Default for RerunPolicy {
+    fn default() -> Self {
+        Self {
+            price_reduction_percent: 20,
+            pause_secs: 10,
+            max_reruns: 2,
+            switch_to: None,
+        }
+    }
+}
+
+impl RerunPolicy {
+    /// The initial price for auctioning the item again.
+    /// It is never lowered below 1, so that the item is never given away for free.
+    pub fn reduced_price(&self, initial_price: Money) -> Money {
+        let reduction = initial_price as u64 * self.price_reduction_percent.min(100) as u64 / 100;
+        (initial_price - reduction as Money).max(1)
+    }
+}
+
+/// The kinds of auction that an unsold item can be auctioned again with.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RerunAuctionKind {
+    English,
+    Japanese,
+}
+
+/// How an English auction's deadline is extended by new bids, to stop last-second sniping.
+/// Except in a candle auction, the first bid always starts the timer with the full commit period.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum SoftClosePolicy {
+    /// Every bid resets the timer to the full commit period.
+    #[default]
+    ResetOnEveryBid,
+
+    /// A bid only resets the timer to the full commit period
+    /// if it arrives when less than this much time is left.
+    ResetInFinalWindow { window_ms: u128 },
+
+    /// Every bid adds this much time to the deadline (but never more than the full commit period),
+    /// and bidding closes no later than `hard_cap_ms` after the first bid.
+    ExtendUpToHardCap {
+        extension_ms: u128,
+        hard_cap_ms: u128,
+    },
+
+    /// A [candle auction](https://en.wikipedia.org/wiki/Candle_auction):
+    /// bidding ends at a secret time, chosen at random between these durations after the auction starts,
+    /// and bids do not change it. Members are not shown how much time is left.
+    Candle {
+        min_duration_ms: u128,
+        max_duration_ms: u128,
+    },
+}
+
+impl std::fmt::Display for SoftClosePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SoftClosePolicy::ResetOnEveryBid => write!(f, "Every bid resets the timer"),
+            SoftClosePolicy::ResetInFinalWindow { window_ms } => write!(
+                f,
+                "A bid resets the timer only in the last {:.1}s",
+                *window_ms as f32 / 1000.0
+            ),
+            SoftClosePolicy::ExtendUpToHardCap {
+                extension_ms,
+                hard_cap_ms,
+            } => write!(
+                f,
+                "Every bid adds {:.1}s, up to {:.1}s after the first bid",
+                *extension_ms as f32 / 1000.0,
+                *hard_cap_ms as f32 / 1000.0
+            ),
+            SoftClosePolicy::Candle {
+                min_duration_ms,
+                max_duration_ms,
+            } => write!(
+                f,
+                "Bidding ends at a secret time, between {:.1}s and {:.1}s after it starts",
+                *min_duration_ms as f32 / 1000.0,
+                *max_duration_ms as f32 / 1000.0
+            ),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -95,7 +527,19 @@ pub enum JapaneseAuctionBidState {
         arena_visibility_mode: ArenaVisibilityMode,
         current_price: Money,
         current_price_increase_per_100_seconds: Money,
+        price_step: Money,
         seconds_until_arena_closes: Option<f32>,
+
+        /// How long the arena stays open after it starts closing
+        arena_closing_secs: u32,
+
+        /// The prices at which arena members will leave the arena automatically.
+        /// These are secret: the server only sends a member their own.
+        drop_out_prices: Vec<(UserAccountData, Money)>,
+
+        /// The members who left the arena automatically, because the clock passed their drop-out price, with that price.
+        /// The server only sends a member themselves.
+        left_on_limit: Vec<(UserAccountData, Money)>,
     },
 
     /// The buyers can now exit the arena; last person standing wins the item
@@ -104,9 +548,192 @@ pub enum JapaneseAuctionBidState {
         arena_visibility_mode: ArenaVisibilityMode,
         current_price: Money,
         current_price_increase_per_100_seconds: Money,
+        price_step: Money,
+
+        /// As above.
+        drop_out_prices: Vec<(UserAccountData, Money)>,
+
+        /// As above.
+        left_on_limit: Vec<(UserAccountData, Money)>,
     },
 }
 
+/// How a Japanese auction's clock runs. These are chosen when the auction is started,
+/// and the admin can change them while it is running.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct JapaneseAuctionSettings {
+    /// How long the arena stays open for entry after the admin starts closing it, in seconds.
+    pub arena_closing_secs: u32,
+
+    /// How much the price increases per 100 seconds.
+    pub price_increase_per_100_seconds: Money,
+
+    /// How much the price increases on each tick of the clock.
+    /// The ticks are spaced out so that the price still increases at the above rate,
+    /// so a larger step means fewer, bigger jumps.
+    pub price_step: Money,
+
+    /// If set, the rate changes by itself while the clock is running, according to this schedule.
+    /// Changing the rate by hand stops the schedule.
+    pub rate_schedule: Option<JapaneseRateSchedule>,
+
+    /// Who wins when several members drop out of the arena at the same price.
+    pub tie_break: JapaneseTieBreak,
+}
+
+impl Default for JapaneseAuctionSettings {
+    fn default() -> Self {
+        Self {
+            arena_closing_secs: 10,
+            price_increase_per_100_seconds: 100,
+            price_step: 1,
+            rate_schedule: None,
+            tie_break: JapaneseTieBreak::EarliestEntry,
+        }
+    }
+}
+
+/// How a Japanese auction decides who wins, when several arena members can no longer pay
+/// at the same tick of the clock, and there are fewer units left than them.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum JapaneseTieBreak {
+    /// The members who entered the arena first win.
+    EarliestEntry,
+
+    /// The winners are drawn at random, and the seed of the draw is recorded.
+    RandomDraw,
+
+    /// The members with the most money available win;
+    /// if that is also tied, the ones who entered the arena first.
+    HighestBalance,
+
+    /// All the tied members share the units left, each paying an equal part of their price,
+    /// and the admin is told to settle who gets what.
+    Split,
+}
+
+impl std::fmt::Display for JapaneseTieBreak {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JapaneseTieBreak::EarliestEntry => write!(f, "earliest entry"),
+            JapaneseTieBreak::RandomDraw => write!(f, "random draw"),
+            JapaneseTieBreak::HighestBalance => write!(f, "highest available balance"),
+            JapaneseTieBreak::Split => write!(f, "split between the tied members"),
+        }
+    }
+}
+
+impl<T> From<T> for JapaneseTieBreak
+where
+    T: TryInto<u8>,
+{
+    fn from(value: T) -> Self {
+        let v: u8 = value.try_into().unwrap_or(0);
+        match v {
+            2 => Self::RandomDraw,
+            3 => Self::HighestBalance,
+            4 => Self::Split,
+            _ => Self::EarliestEntry,
+        }
+    }
+}
+
+impl JapaneseTieBreak {
+    pub fn to_db_val(&self) -> u8 {
+        match self {
+            JapaneseTieBreak::EarliestEntry => 1,
+            JapaneseTieBreak::RandomDraw => 2,
+            JapaneseTieBreak::HighestBalance => 3,
+            JapaneseTieBreak::Split => 4,
+        }
+    }
+}
+
+/// How a tie was broken, recorded with the sales of the units that it decided.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TieBreakRecord {
+    pub policy: JapaneseTieBreak,
+
+    /// For a random draw, the seed that the winners were drawn with.
+    pub seed: Option<u64>,
+}
+
+impl std::fmt::Display for TieBreakRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tie broken by {}", self.policy)?;
+        if let Some(seed) = self.seed {
+            write!(f, ", seed {seed}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A schedule for changing a Japanese auction's clock rate while the clock is running:
+/// the rate is sped up regularly while many members are in the arena,
+/// and slowed down when only a few of them remain.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct JapaneseRateSchedule {
+    /// Every this many seconds of the clock running, the rate is sped up.
+    pub speed_up_every_secs: u32,
+
+    /// What the rate becomes when it is sped up, in percent of the current rate (200 doubles it).
+    pub speed_up_percent: u32,
+
+    /// The rate is only sped up while more than this many members are in the arena.
+    pub speed_up_while_more_than: u32,
+
+    /// When this many members or fewer remain in the arena, the rate is slowed down to `slow_rate`,
+    /// and it is not sped up anymore.
+    pub slow_down_at: u32,
+
+    /// The rate once few members remain in the arena.
+    /// If the rate is already lower than this, it is kept.
+    pub slow_rate: Money,
+}
+
+impl Default for JapaneseRateSchedule {
+    fn default() -> Self {
+        Self {
+            speed_up_every_secs: 30,
+            speed_up_percent: 200,
+            speed_up_while_more_than: 5,
+            slow_down_at: 2,
+            slow_rate: 100,
+        }
+    }
+}
+
+impl JapaneseRateSchedule {
+    /// The rate that the clock should run at, if it has been running at `current_rate`
+    /// for `secs_since_change` seconds since the rate last changed, with this many members in the arena.
+    ///
+    /// Returns None if the rate should not change now.
+    /// Once the rate has been slowed down, `slowed_down` should be true, and then it is not sped up again.
+    pub fn next_rate(
+        &self,
+        current_rate: Money,
+        secs_since_change: f32,
+        arena_size: usize,
+        slowed_down: bool,
+    ) -> Option<Money> {
+        if slowed_down {
+            return None;
+        }
+        if arena_size <= self.slow_down_at as usize {
+            return Some(current_rate.min(self.slow_rate.max(1)));
+        }
+        if self.speed_up_every_secs > 0
+            && secs_since_change >= self.speed_up_every_secs as f32
+            && arena_size > self.speed_up_while_more_than as usize
+        {
+            let new_rate = (current_rate as u64 * self.speed_up_percent as u64 / 100)
+                .clamp(1, Money::MAX as u64);
+            return Some(new_rate as Money);
+        }
+        None
+    }
+}
+
 /// How to show the arena in the user's UI.
 ///
 /// This is not theoretically secure, because the arena needs to be in the message
@@ -148,6 +775,20 @@ impl JapaneseAuctionBidState {
         }
     }
 
+    pub fn get_current_price(&self) -> Money {
+        *match self {
+            JapaneseAuctionBidState::EnterArena { current_price, .. } => current_price,
+            JapaneseAuctionBidState::ClockRunning { current_price, .. } => current_price,
+        }
+    }
+
+    pub fn get_price_step(&self) -> Money {
+        *match self {
+            JapaneseAuctionBidState::EnterArena { price_step, .. } => price_step,
+            JapaneseAuctionBidState::ClockRunning { price_step, .. } => price_step,
+        }
+    }
+
     pub fn get_arena_visibility_mode(&self) -> ArenaVisibilityMode {
         *match self {
             JapaneseAuctionBidState::EnterArena {
@@ -160,47 +801,150 @@ impl JapaneseAuctionBidState {
             } => arena_visibility_mode,
         }
     }
+
+    pub fn get_drop_out_prices(&self) -> &[(UserAccountData, Money)] {
+        match self {
+            JapaneseAuctionBidState::EnterArena {
+                drop_out_prices, ..
+            } => drop_out_prices,
+            JapaneseAuctionBidState::ClockRunning {
+                drop_out_prices, ..
+            } => drop_out_prices,
+        }
+    }
+
+    pub fn get_left_on_limit(&self) -> &[(UserAccountData, Money)] {
+        match self {
+            JapaneseAuctionBidState::EnterArena { left_on_limit, .. } => left_on_limit,
+            JapaneseAuctionBidState::ClockRunning { left_on_limit, .. } => left_on_limit,
+        }
+    }
+
+    /// Keep only the given member's drop-out price and automatic drop-out,
+    /// so that the others' stay secret.
+    pub fn retain_only_for(&mut self, user_id: i64) {
+        match self {
+            JapaneseAuctionBidState::EnterArena {
+                drop_out_prices,
+                left_on_limit,
+                ..
+            }
+            | JapaneseAuctionBidState::ClockRunning {
+                drop_out_prices,
+                left_on_limit,
+                ..
+            } => {
+                drop_out_prices.retain(|(u, _)| u.id == user_id);
+                left_on_limit.retain(|(u, _)| u.id == user_id);
+            }
+        }
+    }
+}
+
+/// An item, or a category of items, that a sponsorship can be limited to.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum SponsorshipScope {
+    Item(i64),
+
+    /// Categories are compared ignoring case, and the spaces around them.
+    Category(String),
+}
+
+impl SponsorshipScope {
+    pub fn includes(&self, item: &AuctionItem) -> bool {
+        match self {
+            SponsorshipScope::Item(id) => item.id == *id,
+            SponsorshipScope::Category(category) => item
+                .category
+                .as_ref()
+                .is_some_and(|c| c.trim().eq_ignore_ascii_case(category.trim())),
+        }
+    }
+}
+
+impl std::fmt::Display for SponsorshipScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SponsorshipScope::Item(id) => write!(f, "item #{id}"),
+            SponsorshipScope::Category(category) => write!(f, "{}", category.trim()),
+        }
+    }
 }
 
 /// An active Sponsorship allows one user to spend money that is not in their own account.
-#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct Sponsorship {
     pub id: i64,
     pub donor_id: i64,
     pub recepient_id: i64,
     pub status: SponsorshipStatus,
     pub balance_remaining: Money,
+
+    /// If set, the donor covers at most this percentage of the price of each purchase,
+    /// as well as no more than `balance_remaining` in total.
+    pub max_percent: Option<u32>,
+
+    /// If this is not empty, the donor's money can only be spent on the items that one of these includes.
+    pub only_for: Vec<SponsorshipScope>,
 }
 
 impl Sponsorship {
-    /// Calculate the effective balance that the user has access to.
+    /// Whether the donor's money can be spent on the given item.
+    /// When there is no particular item, only a sponsorship that is not limited to some items can be used.
+    pub fn can_be_used_for(&self, item: Option<&AuctionItem>) -> bool {
+        self.only_for.is_empty()
+            || item.is_some_and(|item| self.only_for.iter().any(|scope| scope.includes(item)))
+    }
+
+    /// The most that the donor will spend on a purchase of the given price:
+    /// the balance remaining, limited by the percentage cap if there is one
+    /// (rounded down to a whole unit of money, so that it never goes over the cap).
+    pub fn pledge_for(&self, price: Money) -> Money {
+        match self.max_percent {
+            None => self.balance_remaining,
+            Some(percent) => {
+                let share = price as u64 * percent as u64 / 100;
+                self.balance_remaining
+                    .min(share.min(Money::MAX as u64) as Money)
+            }
+        }
+    }
+
+    /// Calculate the effective balance that the user has access to for buying the given item.
     /// First check the balance of the user,
-    /// and then the balances of all those who sponsor them.
+    /// and then the balances of all those who sponsor them, if their sponsorship can be used for the item.
     /// (Subsponsors are not allowed.)
     pub fn resolve_available_balance(
         user_id: i64,
         users: &[UserAccountData],
         sponsorships: &[Sponsorship],
+        item: Option<&AuctionItem>,
     ) -> Money {
         // Special case: the null user has infinite money
         if user_id == 0 {
             return Money::MAX;
         }
 
-        // First, get the list of users who directly sponsor me, along with how much they'd spend on me at most.
+        // First, get the list of users who directly sponsor me, along with how much they'd spend on me at most,
+        // and the percentage of each purchase that they'd cover at most.
         let mut my_sponsors = sponsorships
             .iter()
             .filter(|s| s.recepient_id == user_id)
+            .filter(|s| s.can_be_used_for(item))
             .filter_map(|s| {
-                (s.status == SponsorshipStatus::Active).then_some((s.donor_id, s.balance_remaining))
+                (s.status == SponsorshipStatus::Active).then_some((
+                    s.donor_id,
+                    s.balance_remaining,
+                    s.max_percent,
+                ))
             })
             .collect::<Vec<_>>();
 
         // Include myself: I would spend any amount on myself.
-        my_sponsors.push((user_id, Money::MAX));
+        my_sponsors.push((user_id, Money::MAX, None));
 
         // But if someone doesn't have the amount they want to spend, then reduce it.
-        for (sponsor_id, available_to_spend) in my_sponsors.iter_mut() {
+        for (sponsor_id, available_to_spend, _) in my_sponsors.iter_mut() {
             let user = users
                 .iter()
                 .find(|u| u.id == *sponsor_id)
@@ -208,29 +952,64 @@ impl Sponsorship {
             *available_to_spend = (*available_to_spend).min(user.balance);
         }
 
-        // Finally, calculate the sum. Use saturating arithmetic (just in case the balance is big)
-        my_sponsors
+        // Then, calculate the sum. Use saturating arithmetic (just in case the balance is big)
+        let total: Money = my_sponsors
             .iter()
             .map(|t| t.1)
-            .fold(0, |a, b| a.saturating_add(b))
+            .fold(0, |a, b| a.saturating_add(b));
+        if my_sponsors
+            .iter()
+            .all(|(_, _, max_percent)| max_percent.is_none())
+        {
+            return total;
+        }
+
+        // A sponsor with a percentage cap only covers part of each purchase,
+        // so the most I can spend is the highest price whose covered parts still add up to it.
+        // The higher the price, the less of it is covered,
+        // so the prices that can be paid are all those up to that one, and it can be found by bisection.
+        // Each part is rounded down in the same way as in `pledge_for`, so that the group can pay exactly that price.
+        let can_pay = |price: Money| {
+            let covered: u64 = my_sponsors
+                .iter()
+                .map(|(_, available, max_percent)| match max_percent {
+                    None => *available as u64,
+                    Some(percent) => (*available as u64).min(price as u64 * *percent as u64 / 100),
+                })
+                .sum();
+            covered >= price as u64
+        };
+        let (mut lowest, mut highest) = (0, total);
+        while lowest < highest {
+            let middle = lowest + (highest - lowest).div_ceil(2);
+            if can_pay(middle) {
+                lowest = middle;
+            } else {
+                highest = middle - 1;
+            }
+        }
+        lowest
     }
 
     /// When a user has won an auction,
     /// use this to calculate how to draw the money from the accounts
-    /// of that user and their sponsors.
-    #[cfg(feature = "rand")]
+    /// of that user and their sponsors who can pay for the item, splitting it with the given strategy.
     pub fn calculate_contributions(
         user_id: i64,
         purchase_price: Money,
         users: &[UserAccountData],
         sponsorships: &[Sponsorship],
+        item: &AuctionItem,
+        strategy: &impl ContributionStrategy,
     ) -> Vec<(UserAccountData, Money)> {
-        // First, get the list of users who directly sponsor me, along with the maximum value that they will spend on me.
+        // First, get the list of users who directly sponsor me, along with the maximum value that they will spend on me
+        // for this purchase.
         let mut my_sponsors = sponsorships
             .iter()
             .filter(|s| s.status == SponsorshipStatus::Active)
+            .filter(|s| s.can_be_used_for(Some(item)))
             .filter_map(|s| {
-                (s.recepient_id == user_id).then_some((s.donor_id, s.balance_remaining))
+                (s.recepient_id == user_id).then_some((s.donor_id, s.pledge_for(purchase_price)))
             })
             .map(|(id, balance)| {
                 (
@@ -251,91 +1030,318 @@ impl Sponsorship {
             (me, me.balance)
         });
 
-        // The weights of each member are the amounts they were willing to spend,
-        // ignoring how much they actually have.
-        let mut member_weights: Vec<_> = my_sponsors.iter().map(|(_, b)| *b).collect();
-        // However, the last member (who is myself)
-        // will have a weight half of my balance, plus one.
-        // This makes it more likely that other members will get to share some of the spend.
-        // TODO: make this more mathematically justified?
-        {
-            let last_ref = member_weights.last_mut().unwrap();
-            *last_ref = (*last_ref) / 2;
-            *last_ref += 1;
-        }
-
-        // Limit the spendable balance of each member by the true balance that they have
-        my_sponsors
-            .iter_mut()
-            .for_each(|(u, b)| *b = (*b).min(u.balance));
+        // Each member pledged the amount they were willing to spend,
+        // but can pay no more than the true balance that they have.
+        let group: Vec<_> = my_sponsors
+            .iter()
+            .map(|(u, pledged)| (*pledged, (*pledged).min(u.balance)))
+            .collect();
 
         // Get the collective balance of these.
-        let total_balance: Money = my_sponsors.iter().map(|(_, b)| b).sum();
+        let total_balance: Money = group.iter().map(|(_, available)| available).sum();
         assert!(
             purchase_price <= total_balance,
             "Sponsorship group cannot afford to buy this"
         );
 
-        let mut contributions: Vec<_> =
-            my_sponsors.iter().map(|(u, _)| ((*u).clone(), 0)).collect();
+        let contributions: Vec<_> = my_sponsors
+            .iter()
+            .zip(strategy.split(purchase_price, &group))
+            .map(|((u, _), amount)| ((*u).clone(), amount))
+            .collect();
 
-        {
-            use rand::distributions::WeightedIndex;
-            use rand::prelude::*;
-            let mut rng = thread_rng();
+        assert_eq!(
+            contributions.iter().map(|(_, p)| p).sum::<Money>(),
+            purchase_price,
+            "Bug in money distribution logic"
+        );
+        assert!(
+            contributions
+                .iter()
+                .zip(group.iter())
+                .all(|((_, amount), (_, available))| amount <= available),
+            "Bug in money distribution logic"
+        );
 
-            // These are the values of how much a member would have at every step.
-            let mut remaining_balances: Vec<_> = my_sponsors.iter().map(|(_, b)| *b).collect();
+        contributions
+    }
+}
 
-            // This is the priority with which we'll take money from each member.
-            // It starts out equal to their desired money to be shared,
-            // but it'll get set to zero when they have no more.
-            let mut weights = member_weights;
+/// A rule for how much each member of a sponsorship group pays toward a purchase.
+pub trait ContributionStrategy {
+    /// Split the price between the members of the group.
+    ///
+    /// `group` holds, for each member, how much they pledged to spend,
+    /// and how much of that they can actually pay.
+    /// The buyer is the last member, and pledges their whole balance.
+    /// The group must be able to pay the price.
+    ///
+    /// Returns how much each member pays, in the same order.
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money>;
+}
 
-            // The first zeroizing:
-            remaining_balances
-                .iter()
-                .zip(weights.iter_mut())
-                .for_each(|(balance, weight)| *weight = if balance > &&0 { *weight } else { 0 });
-
-            let mut price_left = purchase_price;
-
-            while price_left > 0 {
-                // At each step, we'll choose a member based on their balance
-                let dist = WeightedIndex::new(&weights)
-                    .expect("Weights turned to all zero while distributing money spend?");
-                let chosen_member = dist.sample(&mut rng);
-                // That member's balance is decreased by one, and the price remaining is too,
-                // and their contribution increases.
-                remaining_balances[chosen_member] -= 1;
+/// Take the price from the members in the given order, each paying as much as they can.
+fn pay_in_order(
+    price: Money,
+    group: &[(Money, Money)],
+    order: impl Iterator<Item = usize>,
+) -> Vec<Money> {
+    let mut payments = vec![0; group.len()];
+    let mut price_left = price;
+    for i in order {
+        let amount = price_left.min(group[i].1);
+        payments[i] = amount;
+        price_left -= amount;
+    }
+    payments
+}
+
+/// Split the price between the members in proportion to their weights.
+/// What a member cannot pay of their share is split between the others in the same way.
+fn pay_by_weights(price: Money, group: &[(Money, Money)], weights: &[Money]) -> Vec<Money> {
+    let mut payments = vec![0; group.len()];
+    let mut price_left = price;
+    while price_left > 0 {
+        let able: Vec<_> = (0..group.len())
+            .filter(|i| payments[*i] < group[*i].1 && weights[*i] > 0)
+            .collect();
+        assert!(
+            !able.is_empty(),
+            "Sponsorship group cannot afford to buy this"
+        );
+        let total_weight: u64 = able.iter().map(|i| weights[*i] as u64).sum();
+
+        let mut paid_now = 0;
+        for i in able.iter().copied() {
+            let share = (price_left as u64 * weights[i] as u64 / total_weight) as Money;
+            let amount = share.min(group[i].1 - payments[i]);
+            payments[i] += amount;
+            paid_now += amount;
+        }
+        price_left -= paid_now;
+
+        // The shares are rounded down, so a few coins may be left over that are nobody's share:
+        // those are paid one by one, in order.
+        if paid_now == 0 {
+            for i in able.into_iter().take(price_left as usize) {
+                payments[i] += 1;
                 price_left -= 1;
-                contributions[chosen_member].1 += 1;
-                // If the member ran out of money, drop them from the sampling.
-                if remaining_balances[chosen_member] == 0 {
-                    weights[chosen_member] = 0;
-                }
             }
         }
+    }
+    payments
+}
 
-        assert_eq!(
-            contributions.iter().map(|(_, p)| p).sum::<Money>(),
-            purchase_price,
-            "Bug in money distribution logic"
-        );
+/// Each member pays in proportion to the amount they pledged.
+pub struct ProportionalSplit;
 
-        contributions
+impl ContributionStrategy for ProportionalSplit {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        let weights: Vec<_> = group.iter().map(|(pledged, _)| *pledged).collect();
+        pay_by_weights(price, group, &weights)
+    }
+}
+
+/// The buyer pays as much as they can, and then the sponsors, in order.
+pub struct BuyerFirstSplit;
+
+impl ContributionStrategy for BuyerFirstSplit {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        let buyer = group.len() - 1;
+        pay_in_order(price, group, std::iter::once(buyer).chain(0..buyer))
+    }
+}
+
+/// The sponsors pay as much as they can, in order, and then the buyer.
+pub struct SponsorsFirstSplit;
+
+impl ContributionStrategy for SponsorsFirstSplit {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        pay_in_order(price, group, 0..group.len())
+    }
+}
+
+/// Every member pays the same, except that those who cannot pay that much pay what they can.
+pub struct EqualSplit;
+
+impl ContributionStrategy for EqualSplit {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        pay_by_weights(price, group, &vec![1; group.len()])
+    }
+}
+
+/// The price is drawn from the members one coin at a time,
+/// choosing the member at random, weighted by the amount they pledged.
+/// The buyer has a weight of half their balance, plus one,
+/// which makes it more likely that the sponsors will get to share some of the spend.
+///
+/// Drawing again with the same seed gives the same split.
+#[cfg(feature = "rand")]
+pub struct RandomSplit {
+    pub seed: u64,
+}
+
+#[cfg(feature = "rand")]
+impl ContributionStrategy for RandomSplit {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        use rand::distributions::WeightedIndex;
+        use rand::prelude::*;
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        // This is the priority with which we'll take money from each member.
+        let mut weights: Vec<_> = group.iter().map(|(pledged, _)| *pledged).collect();
+        if let Some(buyer_weight) = weights.last_mut() {
+            *buyer_weight = *buyer_weight / 2 + 1;
+        }
+
+        // These are the values of how much a member would have at every step.
+        // When a member has no more, their weight is set to zero.
+        let mut remaining_balances: Vec<_> =
+            group.iter().map(|(_, available)| *available).collect();
+        remaining_balances
+            .iter()
+            .zip(weights.iter_mut())
+            .for_each(|(balance, weight)| *weight = if *balance > 0 { *weight } else { 0 });
+
+        let mut payments = vec![0; group.len()];
+        let mut price_left = price;
+        while price_left > 0 {
+            // At each step, we'll choose a member based on their weight
+            let dist = WeightedIndex::new(&weights)
+                .expect("Weights turned to all zero while distributing money spend?");
+            let chosen_member = dist.sample(&mut rng);
+            // That member's balance is decreased by one, and the price remaining is too,
+            // and their payment increases.
+            remaining_balances[chosen_member] -= 1;
+            price_left -= 1;
+            payments[chosen_member] += 1;
+            // If the member ran out of money, drop them from the sampling.
+            if remaining_balances[chosen_member] == 0 {
+                weights[chosen_member] = 0;
+            }
+        }
+        payments
+    }
+}
+
+/// Which [`ContributionStrategy`] splits the price of a sale between the buyer and their sponsors.
+/// The admin chooses this for the auction, and it is recorded with every sale.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+pub enum ContributionSplit {
+    /// See [`RandomSplit`].
+    #[default]
+    Random,
+
+    /// See [`ProportionalSplit`].
+    Proportional,
+
+    /// See [`BuyerFirstSplit`].
+    BuyerFirst,
+
+    /// See [`SponsorsFirstSplit`].
+    SponsorsFirst,
+
+    /// See [`EqualSplit`].
+    Equal,
+}
+
+impl std::fmt::Display for ContributionSplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContributionSplit::Random => write!(f, "random, weighted by pledges"),
+            ContributionSplit::Proportional => write!(f, "in proportion to pledges"),
+            ContributionSplit::BuyerFirst => write!(f, "buyer pays first"),
+            ContributionSplit::SponsorsFirst => write!(f, "sponsors pay first"),
+            ContributionSplit::Equal => write!(f, "equal shares"),
+        }
+    }
+}
+
+impl<T> From<T> for ContributionSplit
+where
+    T: TryInto<u8>,
+{
+    fn from(value: T) -> Self {
+        let v: u8 = value.try_into().unwrap_or(0);
+        match v {
+            2 => Self::Proportional,
+            3 => Self::BuyerFirst,
+            4 => Self::SponsorsFirst,
+            5 => Self::Equal,
+            _ => Self::Random,
+        }
+    }
+}
+
+impl ContributionSplit {
+    pub fn to_db_val(&self) -> u8 {
+        match self {
+            ContributionSplit::Random => 1,
+            ContributionSplit::Proportional => 2,
+            ContributionSplit::BuyerFirst => 3,
+            ContributionSplit::SponsorsFirst => 4,
+            ContributionSplit::Equal => 5,
+        }
+    }
+
+    /// Prepare to split a sale's price this way, drawing a new seed if the split is random.
+    #[cfg(feature = "rand")]
+    pub fn record(self) -> ContributionSplitRecord {
+        ContributionSplitRecord {
+            split: self,
+            seed: (self == ContributionSplit::Random).then(rand::random),
+        }
+    }
+}
+
+/// How the price of a sale was split between the buyer and their sponsors, recorded with the sale.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ContributionSplitRecord {
+    pub split: ContributionSplit,
+
+    /// For a random split, the seed that it was drawn with.
+    pub seed: Option<u64>,
+}
+
+impl std::fmt::Display for ContributionSplitRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "price split: {}", self.split)?;
+        if let Some(seed) = self.seed {
+            write!(f, ", seed {seed}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl ContributionStrategy for ContributionSplitRecord {
+    fn split(&self, price: Money, group: &[(Money, Money)]) -> Vec<Money> {
+        match self.split {
+            ContributionSplit::Random => RandomSplit {
+                seed: self.seed.unwrap_or_default(),
+            }
+            .split(price, group),
+            ContributionSplit::Proportional => ProportionalSplit.split(price, group),
+            ContributionSplit::BuyerFirst => BuyerFirstSplit.split(price, group),
+            ContributionSplit::SponsorsFirst => SponsorsFirstSplit.split(price, group),
+            ContributionSplit::Equal => EqualSplit.split(price, group),
+        }
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
 pub enum SponsorshipStatus {
-    /// The sponsorship was created with a valid code, and is now active.
+    /// The recepient has asked the donor for a sponsorship of `balance_remaining`,
+    /// and the donor has not accepted or declined it yet.
+    Pending,
+
+    /// The sponsorship was created with a valid code, or the donor accepted the request for it, and is now active.
     Active,
 
-    /// The recepient rejected the sponsorship request.
+    /// The recepient rejected the sponsorship, or withdrew their request for it.
     Rejected,
 
-    /// The donor has decided not to provide the sponsorship anymore.
+    /// The donor has decided not to provide the sponsorship anymore, or declined the request for it.
     Retracted,
 }
 
@@ -346,6 +1352,7 @@ where
     fn from(value: T) -> Self {
         let v: u8 = value.try_into().unwrap_or(0);
         match v {
+            0 => Self::Pending,
             1 => Self::Active,
             2 => Self::Rejected,
             3 => Self::Retracted,
@@ -357,9 +1364,393 @@ where
 impl SponsorshipStatus {
     pub fn to_db_val(&self) -> u8 {
         match self {
+            SponsorshipStatus::Pending => 0,
             SponsorshipStatus::Active => 1,
             SponsorshipStatus::Rejected => 2,
             SponsorshipStatus::Retracted => 3,
         }
     }
 }
+
+/// Something that happened to a sponsorship, as shown in the sponsorship history of its donor and recepient.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SponsorshipLogEntry {
+    pub sponsorship_id: i64,
+    pub donor_id: i64,
+    pub recepient_id: i64,
+    pub event: SponsorshipLogEvent,
+
+    /// Milliseconds since the Unix epoch.
+    pub happened_at_ms: i64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum SponsorshipLogEvent {
+    /// The recepient asked the donor for a sponsorship of this amount.
+    Requested { amount: Money },
+
+    /// The sponsorship became active with this balance, from a code or by accepting a request.
+    Created { balance: Money },
+
+    /// The donor changed the balance, the percentage cap, or the items of the sponsorship.
+    /// This is the balance after the change.
+    Adjusted { balance: Money },
+
+    /// The donor paid this amount towards the recepient's payment for the item.
+    UsedFor { item_name: String, amount: Money },
+
+    /// The donor retracted the sponsorship, or declined the request for it.
+    Retracted,
+
+    /// The recepient rejected the sponsorship, or withdrew their request for it.
+    Rejected,
+}
+
+impl SponsorshipLogEvent {
+    pub fn to_db_val(&self) -> u8 {
+        match self {
+            SponsorshipLogEvent::Requested { .. } => 0,
+            SponsorshipLogEvent::Created { .. } => 1,
+            SponsorshipLogEvent::Adjusted { .. } => 2,
+            SponsorshipLogEvent::UsedFor { .. } => 3,
+            SponsorshipLogEvent::Retracted => 4,
+            SponsorshipLogEvent::Rejected => 5,
+        }
+    }
+
+    /// The amount of money that this event is about, if any.
+    pub fn amount(&self) -> Option<Money> {
+        match self {
+            SponsorshipLogEvent::Requested { amount } => Some(*amount),
+            SponsorshipLogEvent::Created { balance } => Some(*balance),
+            SponsorshipLogEvent::Adjusted { balance } => Some(*balance),
+            SponsorshipLogEvent::UsedFor { amount, .. } => Some(*amount),
+            SponsorshipLogEvent::Retracted | SponsorshipLogEvent::Rejected => None,
+        }
+    }
+
+    /// Make the event from the values stored in the database.
+    /// Returns None if the kind is not known, or a value that it needs is missing.
+    pub fn from_db_vals(
+        kind: u8,
+        amount: Option<Money>,
+        item_name: Option<String>,
+    ) -> Option<Self> {
+        Some(match kind {
+            0 => SponsorshipLogEvent::Requested { amount: amount? },
+            1 => SponsorshipLogEvent::Created { balance: amount? },
+            2 => SponsorshipLogEvent::Adjusted { balance: amount? },
+            3 => SponsorshipLogEvent::UsedFor {
+                item_name: item_name?,
+                amount: amount?,
+            },
+            4 => SponsorshipLogEvent::Retracted,
+            5 => SponsorshipLogEvent::Rejected,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UserSaleMode;
+
+    /// Split the price with the strategy, and check that the group pays exactly the price,
+    /// with no member paying more than they can.
+    fn split(
+        strategy: &impl ContributionStrategy,
+        price: Money,
+        group: &[(Money, Money)],
+    ) -> Vec<Money> {
+        let payments = strategy.split(price, group);
+        assert_eq!(payments.len(), group.len());
+        assert_eq!(payments.iter().sum::<Money>(), price);
+        for (paid, (_, available)) in payments.iter().zip(group) {
+            assert!(
+                paid <= available,
+                "{payments:?} is more than {group:?} can pay"
+            );
+        }
+        payments
+    }
+
+    #[test]
+    fn proportional_split_follows_pledges() {
+        assert_eq!(
+            split(&ProportionalSplit, 100, &[(100, 100), (300, 300)]),
+            vec![25, 75]
+        );
+    }
+
+    #[test]
+    fn proportional_split_passes_on_what_a_member_cannot_pay() {
+        // The first member pledged 100, but only has 10: the rest of their half is paid by the buyer.
+        assert_eq!(
+            split(&ProportionalSplit, 60, &[(100, 10), (100, 100)]),
+            vec![10, 50]
+        );
+    }
+
+    #[test]
+    fn proportional_split_skips_members_who_pledged_nothing() {
+        assert_eq!(
+            split(&ProportionalSplit, 5, &[(0, 0), (10, 10)]),
+            vec![0, 5]
+        );
+    }
+
+    #[test]
+    fn proportional_split_gives_the_remainder_to_the_first_members() {
+        assert_eq!(
+            split(&ProportionalSplit, 10, &[(1, 10), (1, 10), (1, 10)]),
+            vec![4, 3, 3]
+        );
+        assert_eq!(
+            split(&ProportionalSplit, 11, &[(1, 10), (1, 10), (1, 10)]),
+            vec![4, 4, 3]
+        );
+    }
+
+    #[test]
+    fn buyer_first_split_takes_from_the_buyer_and_then_the_sponsors_in_order() {
+        let group = [(50, 50), (30, 30), (100, 40)];
+        assert_eq!(split(&BuyerFirstSplit, 30, &group), vec![0, 0, 30]);
+        assert_eq!(split(&BuyerFirstSplit, 60, &group), vec![20, 0, 40]);
+        assert_eq!(split(&BuyerFirstSplit, 120, &group), vec![50, 30, 40]);
+    }
+
+    #[test]
+    fn sponsors_first_split_takes_from_the_sponsors_in_order_and_then_the_buyer() {
+        let group = [(50, 50), (30, 30), (100, 40)];
+        assert_eq!(split(&SponsorsFirstSplit, 30, &group), vec![30, 0, 0]);
+        assert_eq!(split(&SponsorsFirstSplit, 60, &group), vec![50, 10, 0]);
+        assert_eq!(split(&SponsorsFirstSplit, 120, &group), vec![50, 30, 40]);
+    }
+
+    #[test]
+    fn equal_split_shares_the_price_evenly() {
+        assert_eq!(
+            split(&EqualSplit, 30, &[(50, 50), (50, 50), (100, 100)]),
+            vec![10, 10, 10]
+        );
+    }
+
+    #[test]
+    fn equal_split_passes_on_what_a_member_cannot_pay() {
+        // The first member can only pay 5 of their 10, and the other 5 are split between the others,
+        // with the coin left over going to the first of them.
+        assert_eq!(
+            split(&EqualSplit, 30, &[(50, 5), (50, 50), (100, 100)]),
+            vec![5, 13, 12]
+        );
+    }
+
+    #[test]
+    fn equal_split_gives_the_remainder_to_the_first_members() {
+        assert_eq!(
+            split(&EqualSplit, 10, &[(10, 10), (10, 10), (10, 10)]),
+            vec![4, 3, 3]
+        );
+    }
+
+    #[test]
+    fn splits_can_take_everything_the_group_has() {
+        let group = [(20, 7), (5, 5), (13, 13)];
+        for payments in [
+            split(&ProportionalSplit, 25, &group),
+            split(&BuyerFirstSplit, 25, &group),
+            split(&SponsorsFirstSplit, 25, &group),
+            split(&EqualSplit, 25, &group),
+        ] {
+            assert_eq!(payments, vec![7, 5, 13]);
+        }
+    }
+
+    fn user(id: i64, balance: Money) -> UserAccountData {
+        UserAccountData {
+            id,
+            user_name: format!("user {id}"),
+            balance,
+            sale_mode: UserSaleMode::Bidding,
+            is_accepting_sponsorships: true,
+        }
+    }
+
+    fn sponsorship(
+        donor_id: i64,
+        balance_remaining: Money,
+        max_percent: Option<u32>,
+    ) -> Sponsorship {
+        Sponsorship {
+            id: donor_id,
+            donor_id,
+            recepient_id: 1,
+            status: SponsorshipStatus::Active,
+            balance_remaining,
+            max_percent,
+            only_for: vec![],
+        }
+    }
+
+    fn item() -> AuctionItem {
+        AuctionItem {
+            id: 1,
+            name: "item".to_string(),
+            initial_price: 1,
+            reserve_price: None,
+            buy_now_price: None,
+            quantity: 1,
+            category: None,
+        }
+    }
+
+    /// The available balance of the member with ID 1, checking that their group can pay exactly that much:
+    /// the pledges for that price cover it, and the pledges for one more do not.
+    fn available_balance(users: &[UserAccountData], sponsorships: &[Sponsorship]) -> Money {
+        let available =
+            Sponsorship::resolve_available_balance(1, users, sponsorships, Some(&item()));
+        let covered = |price: Money| {
+            let buyer = users.iter().find(|u| u.id == 1).unwrap().balance;
+            sponsorships
+                .iter()
+                .map(|s| {
+                    let donor = users.iter().find(|u| u.id == s.donor_id).unwrap();
+                    s.pledge_for(price).min(donor.balance)
+                })
+                .fold(buyer, Money::saturating_add)
+        };
+        assert!(covered(available) >= available);
+        if available < Money::MAX {
+            assert!(covered(available + 1) < available + 1);
+        }
+        let contributions = Sponsorship::calculate_contributions(
+            1,
+            available,
+            users,
+            sponsorships,
+            &item(),
+            &EqualSplit,
+        );
+        assert_eq!(
+            contributions.iter().map(|(_, paid)| paid).sum::<Money>(),
+            available
+        );
+        available
+    }
+
+    #[test]
+    fn available_balance_without_percentage_caps_is_the_sum_of_the_balances() {
+        let users = [user(1, 30), user(2, 100), user(3, 5)];
+        let sponsorships = [sponsorship(2, 40, None), sponsorship(3, 40, None)];
+        assert_eq!(available_balance(&users, &sponsorships), 75);
+    }
+
+    #[test]
+    fn available_balance_with_a_full_percentage_cap_is_the_whole_pledge() {
+        let users = [user(1, 0), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 50, Some(100))]),
+            50
+        );
+
+        let users = [user(1, 30), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 50, Some(100))]),
+            80
+        );
+    }
+
+    #[test]
+    fn available_balance_when_the_pledge_equals_the_price() {
+        // Half of 80 is exactly the 40 pledged, and the buyer pays the other 40.
+        let users = [user(1, 40), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 40, Some(50))]),
+            80
+        );
+
+        // With nothing of their own, the buyer cannot pay for anything, as the sponsor only covers half of it.
+        let users = [user(1, 0), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 40, Some(50))]),
+            0
+        );
+    }
+
+    #[test]
+    fn available_balance_rounds_the_percentage_down() {
+        // Half of 61 is rounded down to 30, so with the buyer's 30 the group can only pay 60.
+        let users = [user(1, 30), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 100, Some(50))]),
+            60
+        );
+    }
+
+    #[test]
+    fn pledge_never_goes_over_the_percentage_cap() {
+        let capped = sponsorship(2, 100, Some(50));
+        assert_eq!(capped.pledge_for(1), 0);
+        assert_eq!(capped.pledge_for(3), 1);
+        assert_eq!(capped.pledge_for(80), 40);
+        assert_eq!(capped.pledge_for(1000), 100);
+        assert_eq!(sponsorship(2, 100, Some(100)).pledge_for(7), 7);
+    }
+
+    #[test]
+    fn available_balance_is_limited_by_the_balance_remaining_before_the_percentage_cap() {
+        let users = [user(1, 100), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 10, Some(50))]),
+            110
+        );
+    }
+
+    #[test]
+    fn available_balance_is_limited_by_the_donors_own_balance() {
+        let users = [user(1, 30), user(2, 20)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 100, Some(50))]),
+            50
+        );
+    }
+
+    #[test]
+    fn available_balance_with_a_zero_percentage_cap_is_the_buyers_own() {
+        let users = [user(1, 30), user(2, 100)];
+        assert_eq!(
+            available_balance(&users, &[sponsorship(2, 100, Some(0))]),
+            30
+        );
+    }
+
+    #[test]
+    fn available_balance_with_capped_and_uncapped_sponsors() {
+        // A quarter of 40 is 10, which with the buyer's 10 and the other sponsor's 20 pays for it,
+        // but a quarter of 41 is rounded down to 10 as well.
+        let users = [user(1, 10), user(2, 100), user(3, 100)];
+        let sponsorships = [sponsorship(2, 100, Some(25)), sponsorship(3, 20, None)];
+        assert_eq!(available_balance(&users, &sponsorships), 40);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_split_is_the_same_with_the_same_seed() {
+        let group = [(100, 100), (40, 20), (0, 0), (300, 300)];
+        for seed in [0, 1, 42, u64::MAX] {
+            let payments = split(&RandomSplit { seed }, 150, &group);
+            assert_eq!(payments[2], 0);
+            assert_eq!(split(&RandomSplit { seed }, 150, &group), payments);
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_split_can_take_everything_the_group_has() {
+        assert_eq!(
+            split(&RandomSplit { seed: 7 }, 25, &[(20, 7), (5, 5), (13, 13)]),
+            vec![7, 5, 13]
+        );
+    }
+}
@@ -8,7 +8,12 @@ use std::{
 use admin_state::AdminState;
 use auction::{
     actions::JapaneseAuctionAction,
-    state::{ArenaVisibilityMode, AuctionItem, AuctionState, Sponsorship, SponsorshipStatus},
+    state::{
+        ArenaVisibilityMode, AuctionItem, AuctionState, ContributionSplit, ContributionSplitRecord,
+        IncrementRule, JapaneseAuctionSettings, JapaneseTieBreak, RerunPolicy, SilentAuctionState,
+        SoftClosePolicy, Sponsorship, SponsorshipLogEntry, SponsorshipScope, SponsorshipStatus,
+        TieBreakRecord,
+    },
 };
 use serde::{Deserialize, Serialize};
 
@@ -118,14 +123,22 @@ pub fn forget_user_secrets(src: Vec<UserAccountDataWithSecrets>) -> Vec<UserAcco
     src.iter().map(|u| u.into()).collect()
 }
 
+// As with AdminServerMessage, boxing the auction state is not worth it.
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ServerMessage {
     YourAccount(UserAccountDataWithSecrets),
     AuctionMembers(WithTimestamp<Vec<UserAccountData>>),
     AuctionState(WithTimestamp<AuctionState>),
     SponsorshipState(WithTimestamp<Vec<Sponsorship>>),
+    SilentAuctionState(WithTimestamp<SilentAuctionState>),
+
+    /// The history of the sponsorships in which the user is the donor or the recepient, oldest first.
+    SponsorshipLog(WithTimestamp<Vec<SponsorshipLogEntry>>),
 }
 
+// These messages only live until they are encoded or handled, so boxing the auction state is not worth it.
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub enum AdminServerMessage {
     AuctionMembers(WithTimestamp<Vec<UserAccountDataWithSecrets>>),
@@ -133,6 +146,7 @@ pub enum AdminServerMessage {
     ItemStates(WithTimestamp<Vec<ItemState>>),
     AdminState(WithTimestamp<AdminState>),
     SponsorshipState(WithTimestamp<Vec<Sponsorship>>),
+    SilentAuctionState(WithTimestamp<SilentAuctionState>),
 }
 
 /// A wrapper type that adds a timestamp to the data.
@@ -180,6 +194,10 @@ impl<T> From<T> for WithTimestamp<T> {
 pub struct ItemState {
     pub item: AuctionItem,
     pub state: ItemStateValue,
+
+    /// If this item is part of a lot, the ID of the lot, which is itself an item.
+    /// Such an item is not auctioned by itself: its sales are its shares of the lot's sales.
+    pub lot_id: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -187,18 +205,64 @@ pub enum ItemStateValue {
     /// The item is available to be sold
     Sellable,
 
-    /// The item has been sold, and should not be sold again
-    AlreadySold {
-        buyer: UserAccountData,
-        sale_price: Money,
-    },
+    /// Some units of the item have been sold, and the rest are available to be sold
+    PartiallySold { sales: Vec<ItemSale> },
+
+    /// Every unit of the item has been sold, and it should not be sold again
+    AlreadySold { sales: Vec<ItemSale> },
+
+    /// The item was auctioned, but the bidding did not reach its reserve price.
+    /// It is still available to be sold.
+    ReserveNotMet,
 }
 
 impl ItemStateValue {
-    pub fn get_sale_price(&self) -> Option<Money> {
+    pub fn get_sales(&self) -> &[ItemSale] {
         match self {
-            ItemStateValue::Sellable => None,
-            ItemStateValue::AlreadySold { sale_price, .. } => Some(*sale_price),
+            ItemStateValue::Sellable | ItemStateValue::ReserveNotMet => &[],
+            ItemStateValue::PartiallySold { sales } | ItemStateValue::AlreadySold { sales } => {
+                sales
+            }
+        }
+    }
+}
+
+/// A record of one unit of an item being sold.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ItemSale {
+    pub buyer: UserAccountData,
+
+    /// The total paid for this unit, including the payments of the members who did not win it.
+    pub sale_price: Money,
+
+    /// In an all-pay auction or a raffle, the members who paid toward this unit without winning it,
+    /// and how much each of them (with their sponsors) paid.
+    /// If the unit is shared after a tie, these are the other members who share it.
+    pub other_payments: Vec<(UserAccountData, Money)>,
+
+    /// If this unit was won in a tie in a Japanese auction, how the tie was broken.
+    pub tie_break: Option<TieBreakRecord>,
+
+    /// How the price was split between the buyer and their sponsors,
+    /// if this was recorded with the sale.
+    pub contribution_split: Option<ContributionSplitRecord>,
+}
+
+impl ItemSale {
+    /// How much the buyer (with their sponsors) paid for this unit.
+    pub fn buyer_paid(&self) -> Money {
+        let others: Money = self.other_payments.iter().map(|(_, amount)| amount).sum();
+        self.sale_price.saturating_sub(others)
+    }
+
+    /// How to describe the members in `other_payments`.
+    pub fn other_payments_note(&self) -> &'static str {
+        match self.tie_break {
+            Some(TieBreakRecord {
+                policy: JapaneseTieBreak::Split,
+                ..
+            }) => "shared after a tie",
+            _ => "did not win",
         }
     }
 }
@@ -211,11 +275,54 @@ pub enum AdminClientMessage {
     /// Prepare for auctioning an item by its ID
     PrepareAuctioning(i64),
 
-    /// Start auctioning an item according to the rules of an English auction.
-    RunEnglishAuction(i64),
+    /// Start auctioning an item according to the rules of an English auction,
+    /// with the deadline extended by bids according to the given policy.
+    /// If a re-run policy is given, and the auction ends without selling the item, it is auctioned again.
+    RunEnglishAuction {
+        item_id: i64,
+        soft_close_policy: SoftClosePolicy,
+        rerun: Option<RerunPolicy>,
+    },
+
+    /// Start auctioning an item according to the rules of a Japanese auction,
+    /// with its clock running according to the given settings.
+    /// If a re-run policy is given, and the auction ends without selling the item, it is auctioned again.
+    RunJapaneseAuction {
+        item_id: i64,
+        settings: JapaneseAuctionSettings,
+        rerun: Option<RerunPolicy>,
+    },
+
+    /// Start auctioning an item according to the rules of a Dutch auction,
+    /// with the clock starting at the given price.
+    RunDutchAuction { item_id: i64, starting_price: Money },
+
+    /// Start auctioning an item according to the rules of a sealed-bid first-price auction.
+    RunSealedBidAuction(i64),
+
+    /// Start auctioning an item according to the rules of a Vickrey (sealed-bid second-price) auction.
+    RunVickreyAuction(i64),
+
+    /// Start raffling an item: members buy tickets at the given price
+    /// until the entry window (in seconds) is over, and then a ticket is drawn to win the item.
+    RunRaffle {
+        item_id: i64,
+        ticket_price: Money,
+        entry_window_secs: u64,
+    },
+
+    /// Start auctioning an item according to the rules of an all-pay auction.
+    RunAllPayAuction(i64),
+
+    /// If an English or Japanese auction is running, stop its timer or clock,
+    /// and stop accepting actions from members, until it is resumed.
+    PauseAuction,
+
+    /// If an English or Japanese auction is paused, continue it from where it was paused.
+    ResumeAuction,
 
-    /// Start auctioning an item according to the rules of a Japanese auction.
-    RunJapaneseAuction(i64),
+    /// Choose how the price of each sale from now on is split between the buyer and their sponsors.
+    SetContributionSplit(ContributionSplit),
 
     /// Set the auction to the "auction over" state.
     FinishAuction,
@@ -230,9 +337,20 @@ pub enum AdminClientMessage {
     /// The clock rate is how much money the price increases per 100 seconds.
     SetJapaneseClockRate(Money),
 
+    /// If a Japanese auction is running, change how much its price increases on each tick of the clock.
+    SetJapanesePriceStep(Money),
+
+    /// If a Japanese auction is running, change how many seconds its arena stays open after it starts closing.
+    /// If the arena is already closing, this is counted from when it started closing.
+    SetJapaneseArenaClosingSecs(u32),
+
     /// If a Japanese auction is running, change its arena visibility mode.
     SetJapaneseVisibilityMode(ArenaVisibilityMode),
 
+    /// If a Dutch auction is running, change its clock rate.
+    /// The clock rate is how much money the price decreases per 100 seconds.
+    SetDutchClockRate(Money),
+
     /// Change a user's name
     ChangeUserName { id: i64, new_name: String },
 
@@ -257,9 +375,29 @@ pub enum AdminClientMessage {
     /// Change the initial price of an item by ID.
     ChangeItemInitialPrice { id: i64, new_price: String },
 
+    /// Change the hidden reserve price of an item by ID.
+    /// An empty string removes the reserve price.
+    ChangeItemReservePrice { id: i64, new_price: String },
+
+    /// Change the buy-now price of an item by ID.
+    /// An empty string removes the buy-now price.
+    ChangeItemBuyNowPrice { id: i64, new_price: String },
+
+    /// Change how many units of an item there are, by ID.
+    /// If the quantity cannot be parsed as a positive number, ignore this.
+    ChangeItemQuantity { id: i64, new_quantity: String },
+
+    /// Change the category of an item. An empty value removes it.
+    ChangeItemCategory { id: i64, new_category: String },
+
     /// Delete an item by ID.
+    /// If the item is a lot, the items in it become separate again.
     DeleteItem { id: i64 },
 
+    /// Create a lot with the given name, which groups the given items to be auctioned together.
+    /// Items that are already in a lot, are lots themselves, or have been sold are left out.
+    CreateLot { name: String, item_ids: Vec<i64> },
+
     /// Transactionally transfer money between the holding account and the given user account,
     /// so that the user account has the given amount of money.
     /// If the holding account does not have enough, zero it out.
@@ -272,6 +410,26 @@ pub enum AdminClientMessage {
     /// If the current auction is Japanese, and the arena isn't closing yet,
     /// start closing the arena.
     StartClosingJapaneseArena,
+
+    /// If the current auction is sealed-bid (including Vickrey), stop accepting bids and reveal the winner.
+    CloseSealedBidding,
+
+    /// If an item is being raffled, close the entries now and draw the winner.
+    CloseRaffleEntries,
+
+    /// If the current auction is English, change the rule for the minimum bid increment.
+    SetEnglishAuctionIncrementRule(IncrementRule),
+
+    /// Open an item for bidding in the silent auction, closing after the given number of seconds.
+    /// If the item's bidding is still open, only its closing time is changed.
+    OpenSilentAuctionItem { item_id: i64, duration_secs: u64 },
+
+    /// Close the bidding on an item in the silent auction now, selling it to the highest bidder.
+    CloseSilentAuctionItem(i64),
+
+    /// Take an item out of the silent auction.
+    /// If its bidding was still open, it is closed without selling the item.
+    RemoveSilentAuctionItem(i64),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -280,13 +438,57 @@ pub enum UserClientMessage {
         item_id: i64,
         bid_amount: Money,
     },
+
+    /// Register a secret maximum bid in the English auction,
+    /// up to which the auction will outbid others on this member's behalf.
+    /// `None` removes the member's maximum bid.
+    SetProxyBidInEnglishAuction {
+        item_id: i64,
+        max_bid: Option<Money>,
+    },
     JapaneseAuctionAction {
         item_id: i64,
         action: JapaneseAuctionAction,
     },
+    ClaimInDutchAuction {
+        item_id: i64,
+    },
+    BidInSealedBidAuction {
+        item_id: i64,
+        bid_amount: Money,
+    },
+
+    /// Bid in the all-pay auction: the member pays their last bid even if they do not win.
+    BidInAllPayAuction {
+        item_id: i64,
+        bid_amount: Money,
+    },
+
+    /// Buy this many more tickets in the raffle that is running.
+    BuyRaffleTickets {
+        item_id: i64,
+        count: u32,
+    },
+
+    /// Bid on an item that is open in the silent auction.
+    BidInSilentAuction {
+        item_id: i64,
+        bid_amount: Money,
+    },
+
+    /// Buy the item immediately at its buy-now price.
+    BuyNow {
+        item_id: i64,
+    },
     SetIsAcceptingSponsorships(bool),
     SetSaleMode(UserSaleMode),
     TryActivateSponsorshipCode(String),
+    /// Ask another member to sponsor me with this amount.
+    /// The request is pending until they accept it (by setting its status to Active) or decline it.
+    RequestSponsorship {
+        donor_id: i64,
+        amount: Money,
+    },
     SetSponsorshipBalance {
         sponsorship_id: i64,
         balance: Money,
@@ -295,5 +497,15 @@ pub enum UserClientMessage {
         sponsorship_id: i64,
         status: SponsorshipStatus,
     },
+    /// As the donor, cover at most this percentage of the price of each purchase, or remove the cap with `None`.
+    SetSponsorshipPercentCap {
+        sponsorship_id: i64,
+        max_percent: Option<u32>,
+    },
+    /// As the donor, limit the sponsorship to these items and categories, or to none of them (so any item) if empty.
+    SetSponsorshipScope {
+        sponsorship_id: i64,
+        only_for: Vec<SponsorshipScope>,
+    },
     RegenerateSponsorshipCode,
 }
@@ -11,7 +11,7 @@ use crate::{
     admin_ui::{
         choose_item::ChooseItemToSell, confirm_item::ConfirmItemToSell,
         holding_account_transfer::HoldingAccountTransferTable, item_sold::ItemSoldDisplay,
-        show_bid_progress::ShowBidProgress,
+        show_bid_progress::ShowBidProgress, silent_auction::SilentAuctionControls,
     },
     AppCtx,
 };
@@ -22,6 +22,7 @@ mod holding_account_transfer;
 mod item_sold;
 mod setup;
 mod show_bid_progress;
+mod silent_auction;
 
 pub type SendToServer = Callback<AdminClientMessage>;
 
@@ -106,8 +107,10 @@ pub fn AdminUserInterface() -> Html {
             sold_to,
             confirmation_code,
             contributions,
+            revealed_bids,
+            other_units,
         } => {
-            html!(<ItemSoldDisplay item={item.clone()} sold_to={sold_to.clone()} sold_for={*sold_for} confirmation_code={confirmation_code.clone()} contributions={contributions.clone()} />)
+            html!(<ItemSoldDisplay item={item.clone()} sold_to={sold_to.clone()} sold_for={*sold_for} confirmation_code={confirmation_code.clone()} contributions={contributions.clone()} revealed_bids={revealed_bids.clone()} other_units={other_units.clone()} />)
         }
     };
 
@@ -116,6 +119,10 @@ pub fn AdminUserInterface() -> Html {
             <AdminUiTabs state={(ctx.auction_state).clone()}/>
             <Container>
                 {content}
+                // The silent auction runs alongside the live auction, for as long as the auction is in progress.
+                if !matches!(ctx.auction_state, AuctionState::WaitingForAuction | AuctionState::AuctionOver(_)) {
+                    <SilentAuctionControls />
+                }
             </Container>
         </>
     }
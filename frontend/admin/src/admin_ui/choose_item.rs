@@ -6,7 +6,6 @@ use yew::prelude::*;
 
 use crate::AppCtx;
 
-
 #[function_component]
 pub fn ChooseItemToSell() -> Html {
     let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
@@ -16,30 +15,58 @@ pub fn ChooseItemToSell() -> Html {
 
     let mut item_rows: Vec<Html> = vec![];
     for item in items {
-        let action = match &item.state {
-            communication::ItemStateValue::Sellable => {
-                let send = send.clone();
-                let id = item.item.id;
+        // Items in a lot are auctioned with the lot, not by themselves.
+        let lot_item_names: Vec<_> = items
+            .iter()
+            .filter(|i| i.lot_id == Some(item.item.id))
+            .map(|i| i.item.name.as_str())
+            .collect();
+        let in_lot = item
+            .lot_id
+            .and_then(|lot_id| items.iter().find(|i| i.item.id == lot_id));
 
-                // Do not allow selling until the holding account is at zero.
-                if admin_state.holding_account_balance == 0 {
-                    let start_selling_cb = Callback::from(move |e: MouseEvent| {
-                        e.prevent_default();
-                        send.emit(AdminClientMessage::PrepareAuctioning(id));
-                    });
+        let in_silent_auction = ctx
+            .silent_auction
+            .items
+            .iter()
+            .any(|i| i.item.id == item.item.id && i.outcome.is_none());
 
-                    html! {
-                        <a href="#" class="btn btn-primary" onclick={start_selling_cb}>{"Sell this"}</a>
-                    }
-                } else {
-                    html! {
-                        <span class="btn btn-outline-danger disabled">{"Spend holding account first"}</span>
+        let action = if let Some(lot) = in_lot {
+            html! {
+                <span class="btn btn-secondary disabled">{"In lot: "}{&lot.item.name}</span>
+            }
+        } else if in_silent_auction {
+            html! {
+                <span class="btn btn-secondary disabled">{"In silent auction"}</span>
+            }
+        } else {
+            match &item.state {
+                communication::ItemStateValue::Sellable
+                | communication::ItemStateValue::ReserveNotMet
+                | communication::ItemStateValue::PartiallySold { .. } => {
+                    let send = send.clone();
+                    let id = item.item.id;
+
+                    // Do not allow selling until the holding account is at zero.
+                    if admin_state.holding_account_balance == 0 {
+                        let start_selling_cb = Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            send.emit(AdminClientMessage::PrepareAuctioning(id));
+                        });
+
+                        html! {
+                            <a href="#" class="btn btn-primary" onclick={start_selling_cb}>{"Sell this"}</a>
+                        }
+                    } else {
+                        html! {
+                            <span class="btn btn-outline-danger disabled">{"Spend holding account first"}</span>
+                        }
                     }
                 }
+                communication::ItemStateValue::AlreadySold { sales } => html! {
+                    <a href="#" class="btn btn-secondary disabled">{"Sold to "}{sales.iter().map(|s| s.buyer.user_name.as_str()).collect::<Vec<_>>().join(", ")}{" for "}<MoneyDisplay money={sales.iter().map(|s| s.sale_price).sum::<Money>()} /></a>
+                },
             }
-            communication::ItemStateValue::AlreadySold { buyer, sale_price } => html! {
-                <a href="#" class="btn btn-secondary disabled">{"Sold to "}{&buyer.user_name}{" for "}<MoneyDisplay money={sale_price} /></a>
-            },
         };
 
         let item_id = item.item.id;
@@ -55,7 +82,12 @@ pub fn ChooseItemToSell() -> Html {
 
         let item_html = html! {
             <tr>
-                <td>{&item.item.name}</td>
+                <td>
+                    {&item.item.name}
+                    if !lot_item_names.is_empty() {
+                        <p class="form-text">{"Lot of: "}{lot_item_names.join(", ")}</p>
+                    }
+                </td>
                 <td><NumberInput prefill_value={item.item.initial_price.to_string()} onchange={commit_initial_price_cb} min="0" max={Money::MAX.to_string()} step="1" /></td>
                 <td>{action}</td>
             </tr>
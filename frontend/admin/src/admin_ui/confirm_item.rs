@@ -1,7 +1,13 @@
 use std::rc::Rc;
 
-use common::components::ItemDisplay;
-use communication::{auction::state::AuctionItem, AdminClientMessage};
+use common::components::{ItemDisplay, NumberInput};
+use communication::{
+    auction::state::{
+        AuctionItem, ContributionSplit, JapaneseAuctionSettings, JapaneseRateSchedule,
+        JapaneseTieBreak, RerunAuctionKind, RerunPolicy, SoftClosePolicy,
+    },
+    AdminClientMessage, Money,
+};
 use yew::prelude::*;
 
 use crate::AppCtx;
@@ -25,18 +31,300 @@ pub fn ConfirmItemToSell(props: &ConfirmItemProps) -> Html {
             send.emit(AdminClientMessage::StartAuction);
         })
     };
+
+    // How the price is split between the buyer and their sponsors is stored on the server,
+    // and applies to every sale until it is changed.
+    let contribution_split = ctx.admin_state.contribution_split;
+    let contribution_split_button = |split: ContributionSplit, label: &str| {
+        let onclick = {
+            let send = send.clone();
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                send.emit(AdminClientMessage::SetContributionSplit(split));
+            })
+        };
+        html! {
+            <button class={classes!("btn", if contribution_split == split {"btn-info"} else {"btn-outline-info"})} {onclick}>{label.to_string()}</button>
+        }
+    };
+
+    // The English auction's soft-close policy, and the durations (in seconds) used by the policies that need them.
+    let soft_close_kind = use_state(|| 0);
+    let window_secs = use_state(|| 5);
+    let extension_secs = use_state(|| 5);
+    let hard_cap_secs = use_state(|| 120);
+    let candle_min_secs = use_state(|| 60);
+    let candle_max_secs = use_state(|| 180);
+    let soft_close_policy = match *soft_close_kind {
+        1 => SoftClosePolicy::ResetInFinalWindow {
+            window_ms: *window_secs * 1000,
+        },
+        2 => SoftClosePolicy::ExtendUpToHardCap {
+            extension_ms: *extension_secs * 1000,
+            hard_cap_ms: *hard_cap_secs * 1000,
+        },
+        3 => SoftClosePolicy::Candle {
+            min_duration_ms: *candle_min_secs * 1000,
+            max_duration_ms: *candle_max_secs * 1000,
+        },
+        _ => SoftClosePolicy::ResetOnEveryBid,
+    };
+    let get_set_kind_cb = |kind| {
+        let soft_close_kind = soft_close_kind.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            soft_close_kind.set(kind);
+        })
+    };
+    let get_set_secs_cb = |state: &UseStateHandle<u128>| {
+        let state = state.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                state.set(v);
+            }
+        })
+    };
+    let soft_close_options = match soft_close_policy {
+        SoftClosePolicy::ResetOnEveryBid => html!(),
+        SoftClosePolicy::ResetInFinalWindow { .. } => html!(
+            <div class="input-group mb-3">
+                <span class="input-group-text">{"Final window, seconds"}</span>
+                <NumberInput prefill_value={window_secs.to_string()} onchange={get_set_secs_cb(&window_secs)} min="1" max="3600" step="1" />
+            </div>
+        ),
+        SoftClosePolicy::ExtendUpToHardCap { .. } => html!(
+            <div class="input-group mb-3">
+                <span class="input-group-text">{"Extension per bid, seconds"}</span>
+                <NumberInput prefill_value={extension_secs.to_string()} onchange={get_set_secs_cb(&extension_secs)} min="1" max="3600" step="1" />
+                <span class="input-group-text">{"Hard cap, seconds"}</span>
+                <NumberInput prefill_value={hard_cap_secs.to_string()} onchange={get_set_secs_cb(&hard_cap_secs)} min="1" max="36000" step="1" />
+            </div>
+        ),
+        SoftClosePolicy::Candle { .. } => html!(
+            <div class="input-group mb-3">
+                <span class="input-group-text">{"Shortest duration, seconds"}</span>
+                <NumberInput prefill_value={candle_min_secs.to_string()} onchange={get_set_secs_cb(&candle_min_secs)} min="1" max="36000" step="1" />
+                <span class="input-group-text">{"Longest duration, seconds"}</span>
+                <NumberInput prefill_value={candle_max_secs.to_string()} onchange={get_set_secs_cb(&candle_max_secs)} min="1" max="36000" step="1" />
+            </div>
+        ),
+    };
+
+    // What to do if an English or Japanese auction does not sell the item, used only if enabled.
+    let rerun_enabled = use_state(|| false);
+    let rerun_policy = use_state(RerunPolicy::default);
+    let rerun = rerun_enabled.then_some(*rerun_policy);
+    let toggle_rerun_cb = {
+        let rerun_enabled = rerun_enabled.clone();
+        Callback::from(move |_e: Event| {
+            rerun_enabled.set(!*rerun_enabled);
+        })
+    };
+    let get_rerun_cb = |set: fn(&mut RerunPolicy, u32)| {
+        let rerun_policy = rerun_policy.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                let mut policy = *rerun_policy;
+                set(&mut policy, v);
+                rerun_policy.set(policy);
+            }
+        })
+    };
+    let rerun_kind_button = |switch_to: Option<RerunAuctionKind>, label: &str| {
+        let onclick = {
+            let rerun_policy = rerun_policy.clone();
+            Callback::from(move |e: MouseEvent| {
+                e.prevent_default();
+                rerun_policy.set(RerunPolicy {
+                    switch_to,
+                    ..*rerun_policy
+                });
+            })
+        };
+        html! {
+            <button class={classes!("btn", if rerun_policy.switch_to == switch_to {"btn-secondary"} else {"btn-outline-secondary"})} {onclick}>{label.to_string()}</button>
+        }
+    };
+
     let start_as_english_cb = {
         let send = send.clone();
         Callback::from(move |e: MouseEvent| {
             e.prevent_default();
-            send.emit(AdminClientMessage::RunEnglishAuction(item_id));
+            send.emit(AdminClientMessage::RunEnglishAuction {
+                item_id,
+                soft_close_policy,
+                rerun,
+            });
+        })
+    };
+    // The Japanese auction's clock settings.
+    let japanese_settings = use_state(JapaneseAuctionSettings::default);
+    let japanese_arena_closing_secs_cb = {
+        let japanese_settings = japanese_settings.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                japanese_settings.set(JapaneseAuctionSettings {
+                    arena_closing_secs: v,
+                    ..*japanese_settings
+                });
+            }
+        })
+    };
+    let japanese_rate_cb = {
+        let japanese_settings = japanese_settings.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                japanese_settings.set(JapaneseAuctionSettings {
+                    price_increase_per_100_seconds: v,
+                    ..*japanese_settings
+                });
+            }
+        })
+    };
+    let japanese_price_step_cb = {
+        let japanese_settings = japanese_settings.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                japanese_settings.set(JapaneseAuctionSettings {
+                    price_step: v,
+                    ..*japanese_settings
+                });
+            }
+        })
+    };
+    let get_set_japanese_tie_break_cb = |tie_break| {
+        let japanese_settings = japanese_settings.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            japanese_settings.set(JapaneseAuctionSettings {
+                tie_break,
+                ..*japanese_settings
+            });
+        })
+    };
+    let japanese_tie_break_button = |tie_break: JapaneseTieBreak, label: &str| {
+        html! {
+            <button class={classes!("btn", if japanese_settings.tie_break == tie_break {"btn-success"} else {"btn-outline-success"})} onclick={get_set_japanese_tie_break_cb(tie_break)}>{label.to_string()}</button>
+        }
+    };
+    // The Japanese auction's rate schedule, used only if enabled.
+    let japanese_schedule_enabled = use_state(|| false);
+    let japanese_schedule = use_state(JapaneseRateSchedule::default);
+    let toggle_japanese_schedule_cb = {
+        let japanese_schedule_enabled = japanese_schedule_enabled.clone();
+        Callback::from(move |_e: Event| {
+            japanese_schedule_enabled.set(!*japanese_schedule_enabled);
+        })
+    };
+    let get_japanese_schedule_cb = |set: fn(&mut JapaneseRateSchedule, u32)| {
+        let japanese_schedule = japanese_schedule.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                let mut schedule = *japanese_schedule;
+                set(&mut schedule, v);
+                japanese_schedule.set(schedule);
+            }
         })
     };
     let start_as_japanese_cb = {
+        let send = send.clone();
+        let settings = JapaneseAuctionSettings {
+            rate_schedule: japanese_schedule_enabled.then_some(*japanese_schedule),
+            ..*japanese_settings
+        };
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RunJapaneseAuction {
+                item_id,
+                settings,
+                rerun,
+            });
+        })
+    };
+    let start_as_sealed_bid_cb = {
         let send = send.clone();
         Callback::from(move |e: MouseEvent| {
             e.prevent_default();
-            send.emit(AdminClientMessage::RunJapaneseAuction(item_id));
+            send.emit(AdminClientMessage::RunSealedBidAuction(item_id));
+        })
+    };
+    let start_as_vickrey_cb = {
+        let send = send.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RunVickreyAuction(item_id));
+        })
+    };
+
+    let start_as_all_pay_cb = {
+        let send = send.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RunAllPayAuction(item_id));
+        })
+    };
+
+    // The Dutch auction clock starts from a high price, which defaults to double the initial price.
+    let dutch_starting_price = use_state(|| item.initial_price.saturating_mul(2));
+    let dutch_starting_price_cb = {
+        let dutch_starting_price = dutch_starting_price.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                dutch_starting_price.set(v);
+            }
+        })
+    };
+    let start_as_dutch_cb = {
+        let send = send.clone();
+        let starting_price = *dutch_starting_price;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RunDutchAuction {
+                item_id,
+                starting_price,
+            });
+        })
+    };
+
+    // A raffle ticket defaults to a tenth of the initial price, and the entries are open for 5 minutes.
+    let raffle_ticket_price = use_state(|| (item.initial_price / 10).max(1));
+    let raffle_ticket_price_cb = {
+        let raffle_ticket_price = raffle_ticket_price.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                raffle_ticket_price.set(v);
+            }
+        })
+    };
+    let raffle_entry_window_secs = use_state(|| 300u64);
+    let raffle_entry_window_secs_cb = {
+        let raffle_entry_window_secs = raffle_entry_window_secs.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                raffle_entry_window_secs.set(v);
+            }
+        })
+    };
+    let start_as_raffle_cb = {
+        let send = send.clone();
+        let ticket_price = *raffle_ticket_price;
+        let entry_window_secs = *raffle_entry_window_secs;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RunRaffle {
+                item_id,
+                ticket_price,
+                entry_window_secs,
+            });
         })
     };
 
@@ -44,9 +332,95 @@ pub fn ConfirmItemToSell(props: &ConfirmItemProps) -> Html {
         <>
         <h1>{"You are about to start selling:"}</h1>
         <ItemDisplay item={item.clone()} />
+        <p>{"Sponsored purchases are paid: "}{contribution_split.to_string()}</p>
+        <div class="btn-group mb-3">
+            {contribution_split_button(ContributionSplit::Random, "At random")}
+            {contribution_split_button(ContributionSplit::Proportional, "In proportion to pledges")}
+            {contribution_split_button(ContributionSplit::BuyerFirst, "Buyer first")}
+            {contribution_split_button(ContributionSplit::SponsorsFirst, "Sponsors first")}
+            {contribution_split_button(ContributionSplit::Equal, "In equal shares")}
+        </div>
+        <p>{"English auction timer: "}{soft_close_policy.to_string()}</p>
+        <div class="btn-group mb-3">
+            <button class={classes!("btn", if *soft_close_kind == 0 {"btn-primary"} else {"btn-outline-primary"})} onclick={get_set_kind_cb(0)}>{"Reset on every bid"}</button>
+            <button class={classes!("btn", if *soft_close_kind == 1 {"btn-primary"} else {"btn-outline-primary"})} onclick={get_set_kind_cb(1)}>{"Reset only in final seconds"}</button>
+            <button class={classes!("btn", if *soft_close_kind == 2 {"btn-primary"} else {"btn-outline-primary"})} onclick={get_set_kind_cb(2)}>{"Extend up to a hard cap"}</button>
+            <button class={classes!("btn", if *soft_close_kind == 3 {"btn-primary"} else {"btn-outline-primary"})} onclick={get_set_kind_cb(3)}>{"Candle (secret end time)"}</button>
+        </div>
+        {soft_close_options}
         <div class="d-flex gap-2 justify-content-center mb-3">
             <button class="btn btn-primary" type="button" onclick={start_as_english_cb}>{"Sell with English auction"}</button>
             <button class="btn btn-success" type="button" onclick={start_as_japanese_cb}>{"Sell with Japanese auction"}</button>
+            <button class="btn btn-secondary" type="button" onclick={start_as_sealed_bid_cb}>{"Sell with sealed-bid auction"}</button>
+            <button class="btn btn-dark" type="button" onclick={start_as_vickrey_cb}>{"Sell with Vickrey auction"}</button>
+            <button class="btn btn-danger" type="button" onclick={start_as_all_pay_cb}>{"Sell with all-pay auction"}</button>
+        </div>
+        <div class="input-group mb-3">
+            <span class="input-group-text">{"Japanese arena stays open after closing starts, seconds"}</span>
+            <NumberInput prefill_value={japanese_settings.arena_closing_secs.to_string()} onchange={japanese_arena_closing_secs_cb} min="0" max="3600" step="1" />
+            <span class="input-group-text">{"Price rise per 100 seconds"}</span>
+            <NumberInput prefill_value={japanese_settings.price_increase_per_100_seconds.to_string()} onchange={japanese_rate_cb} min="1" max={Money::MAX.to_string()} step="1" />
+            <span class="input-group-text">{"Price step"}</span>
+            <NumberInput prefill_value={japanese_settings.price_step.to_string()} onchange={japanese_price_step_cb} min="1" max={Money::MAX.to_string()} step="1" />
+        </div>
+        <p>{"Japanese auction tie-break: "}{japanese_settings.tie_break.to_string()}</p>
+        <div class="btn-group mb-3">
+            {japanese_tie_break_button(JapaneseTieBreak::EarliestEntry, "Earliest entry")}
+            {japanese_tie_break_button(JapaneseTieBreak::RandomDraw, "Random draw")}
+            {japanese_tie_break_button(JapaneseTieBreak::HighestBalance, "Highest balance")}
+            {japanese_tie_break_button(JapaneseTieBreak::Split, "Split and settle by hand")}
+        </div>
+        <div class="form-check mb-2">
+            <input class="form-check-input" type="checkbox" checked={*japanese_schedule_enabled} onchange={toggle_japanese_schedule_cb} />
+            <label class="form-check-label">{"Change the Japanese clock rate automatically"}</label>
+        </div>
+        if *japanese_schedule_enabled {
+            <div class="input-group mb-2">
+                <span class="input-group-text">{"Every, seconds"}</span>
+                <NumberInput prefill_value={japanese_schedule.speed_up_every_secs.to_string()} onchange={get_japanese_schedule_cb(|s, v| s.speed_up_every_secs = v)} min="1" max="3600" step="1" />
+                <span class="input-group-text">{"set the rate to, percent"}</span>
+                <NumberInput prefill_value={japanese_schedule.speed_up_percent.to_string()} onchange={get_japanese_schedule_cb(|s, v| s.speed_up_percent = v)} min="100" max="1000" step="10" />
+                <span class="input-group-text">{"while more members in the arena than"}</span>
+                <NumberInput prefill_value={japanese_schedule.speed_up_while_more_than.to_string()} onchange={get_japanese_schedule_cb(|s, v| s.speed_up_while_more_than = v)} min="0" max="1000" step="1" />
+            </div>
+            <div class="input-group mb-2">
+                <span class="input-group-text">{"When only this many members remain"}</span>
+                <NumberInput prefill_value={japanese_schedule.slow_down_at.to_string()} onchange={get_japanese_schedule_cb(|s, v| s.slow_down_at = v)} min="0" max="1000" step="1" />
+                <span class="input-group-text">{"slow the rate down to"}</span>
+                <NumberInput prefill_value={japanese_schedule.slow_rate.to_string()} onchange={get_japanese_schedule_cb(|s, v| s.slow_rate = v)} min="1" max={Money::MAX.to_string()} step="1" />
+            </div>
+            <p class="form-text">{"Changing the rate by hand during the auction stops the automatic changes."}</p>
+        }
+        <div class="form-check mb-2">
+            <input class="form-check-input" type="checkbox" checked={*rerun_enabled} onchange={toggle_rerun_cb} />
+            <label class="form-check-label">{"If the English or Japanese auction does not sell the item, auction it again"}</label>
+        </div>
+        if *rerun_enabled {
+            <div class="input-group mb-2">
+                <span class="input-group-text">{"Lower the initial price by, percent"}</span>
+                <NumberInput prefill_value={rerun_policy.price_reduction_percent.to_string()} onchange={get_rerun_cb(|p, v| p.price_reduction_percent = v)} min="0" max="99" step="1" />
+                <span class="input-group-text">{"after showing it for, seconds"}</span>
+                <NumberInput prefill_value={rerun_policy.pause_secs.to_string()} onchange={get_rerun_cb(|p, v| p.pause_secs = v)} min="0" max="3600" step="1" />
+                <span class="input-group-text">{"at most this many times"}</span>
+                <NumberInput prefill_value={rerun_policy.max_reruns.to_string()} onchange={get_rerun_cb(|p, v| p.max_reruns = v)} min="1" max="100" step="1" />
+            </div>
+            <div class="btn-group mb-3">
+                {rerun_kind_button(None, "The same way")}
+                {rerun_kind_button(Some(RerunAuctionKind::English), "As an English auction")}
+                {rerun_kind_button(Some(RerunAuctionKind::Japanese), "As a Japanese auction")}
+            </div>
+        }
+        <div class="input-group mb-3">
+            <span class="input-group-text">{"Dutch auction starting price"}</span>
+            <NumberInput prefill_value={dutch_starting_price.to_string()} onchange={dutch_starting_price_cb} min={item.initial_price.to_string()} max={Money::MAX.to_string()} step="1" />
+            <button class="btn btn-info" type="button" onclick={start_as_dutch_cb}>{"Sell with Dutch auction"}</button>
+        </div>
+        <div class="input-group mb-3">
+            <span class="input-group-text">{"Raffle ticket price"}</span>
+            <NumberInput prefill_value={raffle_ticket_price.to_string()} onchange={raffle_ticket_price_cb} min="1" max={Money::MAX.to_string()} step="1" />
+            <span class="input-group-text">{"Entries open for, seconds"}</span>
+            <NumberInput prefill_value={raffle_entry_window_secs.to_string()} onchange={raffle_entry_window_secs_cb} min="10" max="36000" step="1" />
+            <button class="btn btn-warning" type="button" onclick={start_as_raffle_cb}>{"Sell with raffle"}</button>
         </div>
         <div class="d-grid gap-2 col-6 mx-auto mb-3">
             <button class="btn btn-danger" type="button" onclick={reset_cb}>{"Do not sell this now"}</button>
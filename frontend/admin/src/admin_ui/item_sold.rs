@@ -1,13 +1,15 @@
 use std::rc::Rc;
 
-use common::components::{ItemDisplay, UserAccountCard};
+use common::components::{ItemDisplay, MoneyDisplay, RevealedBidsTable, UserAccountCard};
 use common::layout::{Container, VerticalStack};
-use communication::{auction::state::AuctionItem, AdminClientMessage, Money, UserAccountData};
+use communication::{
+    auction::state::{AuctionItem, JapaneseTieBreak, UnitSale},
+    AdminClientMessage, Money, UserAccountData,
+};
 use yew::prelude::*;
 
 use crate::AppCtx;
 
-
 #[derive(Properties, PartialEq)]
 pub struct ItemSoldDisplayProps {
     pub item: AuctionItem,
@@ -15,6 +17,8 @@ pub struct ItemSoldDisplayProps {
     pub sold_for: Money,
     pub confirmation_code: String,
     pub contributions: Vec<(UserAccountData, Money)>,
+    pub revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+    pub other_units: Vec<UnitSale>,
 }
 
 #[function_component]
@@ -29,10 +33,72 @@ pub fn ItemSoldDisplay(props: &ItemSoldDisplayProps) -> Html {
             send.emit(AdminClientMessage::StartAuction);
         })
     };
+    let revealed_bids = match &props.revealed_bids {
+        Some(bids) => html!(
+            <>
+                <h2>{"Revealed bids:"}</h2>
+                <RevealedBidsTable bids={bids.clone()} highlight_user_id={Some(props.sold_to.id)} />
+            </>
+        ),
+        None => html!(),
+    };
+
+    // If the item was shared between tied members, the admin needs to settle who gets what with them.
+    let shared_after_tie = ctx
+        .items
+        .iter()
+        .find(|i| i.item.id == props.item.id)
+        .is_some_and(|i| {
+            i.state.get_sales().iter().any(|sale| {
+                sale.tie_break
+                    .is_some_and(|t| t.policy == JapaneseTieBreak::Split)
+            })
+        });
+    let shared_alert = if shared_after_tie {
+        html!(
+            <div class="alert alert-warning">
+                {"Several members tied for this item, and they share it: each of them paid an equal part of the price. "}
+                {"Settle with them who gets what; their payments are listed in the report."}
+            </div>
+        )
+    } else {
+        html!()
+    };
+
+    // If several units were sold, each of the other buyers has their own confirmation code.
+    let other_units = if props.other_units.is_empty() {
+        html!()
+    } else {
+        html!(
+            <>
+                <h2>{"Other units sold:"}</h2>
+                <table class="table table-sm">
+                    <thead>
+                        <tr>
+                            <th scope="col">{"Buyer"}</th>
+                            <th scope="col">{"Price"}</th>
+                            <th scope="col">{"Confirmation code"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {for props.other_units.iter().map(|unit| html!(
+                            <tr>
+                                <td>{&unit.sold_to.user_name}</td>
+                                <td><MoneyDisplay money={unit.sold_for} /></td>
+                                <td>{&unit.confirmation_code}</td>
+                            </tr>
+                        ))}
+                    </tbody>
+                </table>
+            </>
+        )
+    };
+
     html!(
         <Container>
             <VerticalStack>
                 <h1>{"Item sold!"}</h1>
+                {shared_alert}
                 <div class="row justify-content-evenly align-items-center mb-3">
                     <div class="col-5">
                         <ItemDisplay item={props.item.clone()} />
@@ -48,6 +114,8 @@ pub fn ItemSoldDisplay(props: &ItemSoldDisplayProps) -> Html {
                 <h2>{"Confirmation code:"}</h2>
                 <h3 style="font-size: calc(100vw/0.625/6);">{props.confirmation_code.clone()}</h3>
                 // Font calc: https://stackoverflow.com/a/31322756/5936187
+                {other_units}
+                {revealed_bids}
                 <div class="d-grid gap-2">
                     <button onclick={return_cb} class="btn btn-success">{"Return to item select"}</button>
                 </div>
@@ -140,24 +140,53 @@ fn ItemSetup() -> Html {
     let send = &ctx.send;
     let mut rows = Vec::with_capacity(items.len());
 
+    // The items that are checked to be grouped into a new lot.
+    let lot_selection = use_state(Vec::<i64>::new);
+
     for item in &*items {
         let item_id = item.item.id;
 
-        let item_state_component = match &item.state {
-            communication::ItemStateValue::Sellable => html!(<span>{"Sellable"}</span>),
-            communication::ItemStateValue::AlreadySold { buyer, sale_price } => {
-                let reset_sale_status_cb = {
-                    let send = send.clone();
-                    Callback::from(move |e: MouseEvent| {
-                        e.prevent_default();
-                        send.emit(AdminClientMessage::ClearSaleStatus { id: item_id });
-                    })
-                };
-                html! {
-                    <>
-                        <span>{"Already sold to "}{buyer.user_name.clone()}{" for "}<MoneyDisplay money={sale_price} /></span>
-                        <button class="btn btn-warning" onclick={reset_sale_status_cb}>{"Clear sale status"}</button>
-                    </>
+        let lot_item_names: Vec<_> = items
+            .iter()
+            .filter(|i| i.lot_id == Some(item_id))
+            .map(|i| i.item.name.as_str())
+            .collect();
+        let in_lot = item
+            .lot_id
+            .and_then(|lot_id| items.iter().find(|i| i.item.id == lot_id));
+
+        // Items in a lot are sold with the lot, so their sale status is managed through it.
+        let item_state_component = if let Some(lot) = in_lot {
+            html!(<span>{"In lot: "}{&lot.item.name}</span>)
+        } else {
+            match &item.state {
+                communication::ItemStateValue::Sellable => html!(<span>{"Sellable"}</span>),
+                communication::ItemStateValue::ReserveNotMet => {
+                    html!(<span>{"Not sold: reserve not met"}</span>)
+                }
+                communication::ItemStateValue::PartiallySold { sales }
+                | communication::ItemStateValue::AlreadySold { sales } => {
+                    let reset_sale_status_cb = {
+                        let send = send.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            send.emit(AdminClientMessage::ClearSaleStatus { id: item_id });
+                        })
+                    };
+                    html! {
+                        <>
+                            <span>
+                                {if matches!(item.state, communication::ItemStateValue::AlreadySold { .. }) {"Already sold to "} else {"Partly sold to "}}
+                                {for sales.iter().enumerate().map(|(i, sale)| html!(
+                                    <>
+                                        {if i > 0 {", "} else {""}}
+                                        {sale.buyer.user_name.clone()}{" for "}<MoneyDisplay money={sale.sale_price} />
+                                    </>
+                                ))}
+                            </span>
+                            <button class="btn btn-warning" onclick={reset_sale_status_cb}>{"Clear sale status"}</button>
+                        </>
+                    }
                 }
             }
         };
@@ -182,6 +211,66 @@ fn ItemSetup() -> Html {
             })
         };
 
+        let commit_reserve_price_cb = {
+            let send = send.clone();
+            Callback::from(move |s: String| {
+                send.emit(AdminClientMessage::ChangeItemReservePrice {
+                    id: item_id,
+                    new_price: s,
+                });
+            })
+        };
+
+        let commit_buy_now_price_cb = {
+            let send = send.clone();
+            Callback::from(move |s: String| {
+                send.emit(AdminClientMessage::ChangeItemBuyNowPrice {
+                    id: item_id,
+                    new_price: s,
+                });
+            })
+        };
+
+        let commit_quantity_cb = {
+            let send = send.clone();
+            Callback::from(move |s: String| {
+                send.emit(AdminClientMessage::ChangeItemQuantity {
+                    id: item_id,
+                    new_quantity: s,
+                });
+            })
+        };
+
+        let commit_category_cb = {
+            let send = send.clone();
+            Callback::from(move |s: String| {
+                send.emit(AdminClientMessage::ChangeItemCategory {
+                    id: item_id,
+                    new_category: s,
+                });
+            })
+        };
+
+        let can_join_lot = item.lot_id.is_none()
+            && lot_item_names.is_empty()
+            && matches!(
+                item.state,
+                communication::ItemStateValue::Sellable
+                    | communication::ItemStateValue::ReserveNotMet
+            );
+        let toggle_lot_cb = {
+            let lot_selection = lot_selection.clone();
+            Callback::from(move |_e: Event| {
+                let mut selection = (*lot_selection).clone();
+                if selection.contains(&item_id) {
+                    selection.retain(|id| *id != item_id);
+                } else {
+                    selection.push(item_id);
+                }
+                lot_selection.set(selection);
+            })
+        };
+
         let delete_item_cb = {
             let send = send.clone();
             Callback::from(move |e: MouseEvent| {
@@ -194,15 +283,36 @@ fn ItemSetup() -> Html {
             <tr>
                 <td>
                     <TextInput prefill_value={item.item.name.clone()} onchange={commit_name_cb} />
+                    if !lot_item_names.is_empty() {
+                        <p class="form-text">{"Lot of: "}{lot_item_names.join(", ")}</p>
+                    }
                 </td>
                 <td>
                     <NumberInput prefill_value={item.item.initial_price.to_string()} onchange={commit_initial_price_cb} min="0" max={Money::MAX.to_string()} step="1" />
                 </td>
+                <td>
+                    <NumberInput prefill_value={item.item.reserve_price.map(|p| p.to_string()).unwrap_or_default()} onchange={commit_reserve_price_cb} min="0" max={Money::MAX.to_string()} step="1" />
+                </td>
+                <td>
+                    <NumberInput prefill_value={item.item.buy_now_price.map(|p| p.to_string()).unwrap_or_default()} onchange={commit_buy_now_price_cb} min="0" max={Money::MAX.to_string()} step="1" />
+                </td>
+                <td>
+                    <NumberInput prefill_value={item.item.quantity.to_string()} onchange={commit_quantity_cb} min="1" max={u32::MAX.to_string()} step="1" />
+                </td>
+                <td>
+                    <TextInput prefill_value={item.item.category.clone().unwrap_or_default()} onchange={commit_category_cb} />
+                </td>
                 <td>
                     {item_state_component}
                 </td>
                 <td>
                     <button class="btn btn-outline-danger" onclick={delete_item_cb}>{"Delete"}</button>
+                    if can_join_lot {
+                        <div class="form-check">
+                            <input class="form-check-input" type="checkbox" checked={lot_selection.contains(&item_id)} onchange={toggle_lot_cb} />
+                            <label class="form-check-label">{"Put in lot"}</label>
+                        </div>
+                    }
                 </td>
             </tr>
         };
@@ -234,7 +344,7 @@ fn ItemSetup() -> Html {
     };
     rows.push(html!(
         <tr>
-            <td colspan="3">
+            <td colspan="7">
                 <input class="form-control mb-2" type="text" value={(*new_item_name).clone()} oninput={new_item_name_edit_cb} placeholder="New item name..." />
             </td>
             <td>
@@ -243,12 +353,52 @@ fn ItemSetup() -> Html {
         </tr>
     ));
 
+    let new_lot_name = use_state(|| String::new());
+
+    let new_lot_name_edit_cb = {
+        let new_lot_name = new_lot_name.clone();
+        Callback::from(move |e: InputEvent| {
+            let event: Event = e.dyn_into().unwrap_throw();
+            let event_target = event.target().unwrap_throw();
+            let target: HtmlInputElement = event_target.dyn_into().unwrap_throw();
+            new_lot_name.set(target.value());
+        })
+    };
+
+    let add_lot_cb = {
+        let new_lot_name = new_lot_name.clone();
+        let lot_selection = lot_selection.clone();
+        let send = send.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            let name = (*new_lot_name).clone();
+            let item_ids = (*lot_selection).clone();
+            send.emit(AdminClientMessage::CreateLot { name, item_ids });
+            new_lot_name.set(String::new());
+            lot_selection.set(vec![]);
+        })
+    };
+    rows.push(html!(
+        <tr>
+            <td colspan="7">
+                <input class="form-control mb-2" type="text" value={(*new_lot_name).clone()} oninput={new_lot_name_edit_cb} placeholder="New lot name..." />
+            </td>
+            <td>
+                <button class="btn btn-success" onclick={add_lot_cb} disabled={lot_selection.len() < 2}>{"Create lot from checked items"}</button>
+            </td>
+        </tr>
+    ));
+
     html! {
         <table class="table table-sm">
             <thead>
                 <tr>
                     <th scope="col">{"Name"}</th>
                     <th scope="col">{"Initial price"}</th>
+                    <th scope="col">{"Reserve price (hidden, empty for none)"}</th>
+                    <th scope="col">{"Buy-now price (empty for none)"}</th>
+                    <th scope="col">{"Quantity"}</th>
+                    <th scope="col">{"Category (empty for none)"}</th>
                     <th scope="col">{"State"}</th>
                     <th scope="col">{"Action"}</th>
                 </tr>
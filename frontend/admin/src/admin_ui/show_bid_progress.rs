@@ -1,11 +1,15 @@
 use std::rc::Rc;
 
 use common::{
-    components::{ItemDisplay, MoneyDisplay, UserAccountCard, UserAccountTable},
+    components::{
+        ItemDisplay, MoneyDisplay, NumberInput, TextInput, UserAccountCard, UserAccountTable,
+    },
     layout::Container,
 };
 use communication::{
-    auction::state::{ArenaVisibilityMode, BiddingState, JapaneseAuctionBidState},
+    auction::state::{
+        ArenaVisibilityMode, BiddingState, JapaneseAuctionBidState, SealedBidPricing,
+    },
     AdminClientMessage, Money, UserAccountData,
 };
 use yew::prelude::*;
@@ -34,10 +38,24 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
         communication::auction::state::ActiveBidState::EnglishAuctionBid {
             current_bid_amount,
             current_bidder,
+            units,
+            winning_bids,
             minimum_increment,
+            increment_rule,
+            soft_close_policy,
             seconds_until_commit,
             max_millis_until_commit,
+            proxy_bids,
         } => {
+            let increment_rule_cb = {
+                let send = send.clone();
+                Callback::from(move |s: String| {
+                    // If the input cannot be parsed as an increment rule, ignore it.
+                    if let Ok(rule) = s.parse() {
+                        send.emit(AdminClientMessage::SetEnglishAuctionIncrementRule(rule));
+                    }
+                })
+            };
             let increase_bet_time_cb = {
                 let send = send.clone();
                 let mmuc = *max_millis_until_commit;
@@ -64,13 +82,33 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                 <p>{"Current bidder:"}</p>
                 <UserAccountCard account={current_bidder.clone()} />
                 <p>{"Current bid amount: "}<MoneyDisplay money={current_bid_amount} /></p>
+                if *units > 1 {
+                    <p>{"Units being sold: "}{units}{"; winning bids:"}</p>
+                    <ul>
+                        { for winning_bids.iter().map(|(user, bid)| html!(
+                            <li>{&user.user_name}{": "}<MoneyDisplay money={bid} /></li>
+                        ))}
+                    </ul>
+                }
                 <p>{"Minimum bid increment: "}<MoneyDisplay money={minimum_increment} /></p>
+                <div class="input-group mb-3">
+                    <span class="input-group-text">{"Increment rule"}</span>
+                    <TextInput prefill_value={increment_rule.to_string()} onchange={increment_rule_cb} />
+                </div>
+                <p class="form-text">{"A fixed amount (5), a percentage of the current bid (10%), or brackets of \"below:increment\" and the increment above them (100:1, 500:5, 10)"}</p>
                 <p>{"Time remaining: "}{seconds_until_commit}</p>
+                <p>{"Timer policy: "}{soft_close_policy.to_string()}</p>
                 <p>
                     {"Max bid time: "}{format!("{:.2}", *max_millis_until_commit as f32 / 1000.0)}
                     <button class="btn btn-danger" onclick={decrease_bet_time_cb}>{"Sub 1 second"}</button>
                     <button class="btn btn-success" onclick={increase_bet_time_cb}>{"Add 5 seconds"}</button>
                 </p>
+                <p>{"Secret maximum bids:"}</p>
+                <ul>
+                    { for proxy_bids.iter().map(|(user, max_bid)| html!(
+                        <li>{&user.user_name}{": "}<MoneyDisplay money={max_bid} /></li>
+                    ))}
+                </ul>
                 </>
             }
         }
@@ -122,6 +160,44 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                 })
             };
 
+            // The price step is changed by typing in a new one.
+            let set_price_step_cb = {
+                let send = send.clone();
+                Callback::from(move |s: String| {
+                    // If the input cannot be parsed as a money value, ignore it.
+                    if let Ok(v) = s.parse() {
+                        send.emit(AdminClientMessage::SetJapanesePriceStep(v));
+                    }
+                })
+            };
+            // The members' secret drop-out prices, and who has left the arena on theirs rather than by hand.
+            let drop_out_info = html! {
+                <>
+                    if !state.get_drop_out_prices().is_empty() {
+                        <p>{"Secret drop-out prices:"}</p>
+                        <ul>
+                            { for state.get_drop_out_prices().iter().map(|(user, price)| html!(
+                                <li>{&user.user_name}{": "}<MoneyDisplay money={price} /></li>
+                            ))}
+                        </ul>
+                    }
+                    if !state.get_left_on_limit().is_empty() {
+                        <p>{"Left automatically at their drop-out price:"}</p>
+                        <ul>
+                            { for state.get_left_on_limit().iter().map(|(user, price)| html!(
+                                <li>{&user.user_name}{": "}<MoneyDisplay money={price} /></li>
+                            ))}
+                        </ul>
+                    }
+                </>
+            };
+            let price_step_input = html! {
+                <div class="input-group mb-3">
+                    <span class="input-group-text">{"Price step per tick"}</span>
+                    <NumberInput prefill_value={state.get_price_step().to_string()} onchange={set_price_step_cb} min="1" max={Money::MAX.to_string()} step="1" />
+                </div>
+            };
+
             // These three callbacks set the visibility mode.
             let set_full_cb = {
                 let send = send.clone();
@@ -159,7 +235,18 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                     current_price,
                     current_price_increase_per_100_seconds,
                     arena_visibility_mode,
+                    arena_closing_secs,
+                    ..
                 } => {
+                    let set_arena_closing_secs_cb = {
+                        let send = send.clone();
+                        Callback::from(move |s: String| {
+                            // If the input cannot be parsed as a number, ignore it.
+                            if let Ok(v) = s.parse() {
+                                send.emit(AdminClientMessage::SetJapaneseArenaClosingSecs(v));
+                            }
+                        })
+                    };
                     let arena_closes = if let Some(s) = seconds_until_arena_closes {
                         html!(
                             <p>{"Arena closes in: "}{s}</p>
@@ -185,6 +272,10 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                             <h1>{"Arena is now open"}</h1>
                             <p>{"Current price: "}<MoneyDisplay money={current_price} /></p>
                             {arena_closes}
+                            <div class="input-group mb-3">
+                                <span class="input-group-text">{"Arena stays open after closing starts, seconds"}</span>
+                                <NumberInput prefill_value={arena_closing_secs.to_string()} onchange={set_arena_closing_secs_cb} min="0" max="3600" step="1" />
+                            </div>
                             <p>
                                 {"Current price increase rate: +"}
                                 <MoneyDisplay money={current_price_increase_per_100_seconds}/>
@@ -192,6 +283,7 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                                 <button class="btn btn-danger" onclick={clock_rate_down_cb}>{"-"}</button>
                                 <button class="btn btn-success" onclick={clock_rate_up_cb}>{"+"}</button>
                             </p>
+                            {price_step_input}
 
                             <p>{"Members can see the following info about the arena:"}</p>
                             <div class="btn-group">
@@ -200,6 +292,7 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                                 <button class={classes!("btn", if matches!(arena_visibility_mode, ArenaVisibilityMode::Nothing){"btn-primary"} else {"btn-outline-primary"})} onclick={set_nothing_cb}>{"Nothing"}</button>
                             </div>
 
+                            {drop_out_info}
                             <div class="overflow-scroll" style="height: 40vh; max-height: 40vh;">
                                 <h3>{currently_in_arena.len()}{" members in arena"}</h3>
                                 <UserAccountTable accounts={currently_in_arena.clone()} users={users.iter().map(|u| u.into()).collect::<Vec<_>>()} sponsorships={sponsorships.clone()} action_col_cb={get_kick_btn_cb} />
@@ -212,6 +305,7 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                     current_price,
                     current_price_increase_per_100_seconds,
                     arena_visibility_mode,
+                    ..
                 } => html! {
                     <>
                         <h1>{"Arena is now closed"}</h1>
@@ -224,6 +318,7 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                             <button class="btn btn-danger" onclick={clock_rate_down_cb}>{"-"}</button>
                             <button class="btn btn-success" onclick={clock_rate_up_cb}>{"+"}</button>
                         </p>
+                        {price_step_input}
 
                         <p>{"Members can see the following info about the arena:"}</p>
                         <div class="btn-group">
@@ -232,6 +327,7 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                             <button class={classes!("btn", if matches!(arena_visibility_mode, ArenaVisibilityMode::Nothing){"btn-primary"} else {"btn-outline-primary"})} onclick={set_nothing_cb}>{"Nothing"}</button>
                         </div>
 
+                        {drop_out_info}
                         <div class="overflow-scroll" style="height: 20vh; max-height: 20vh;">
                             <h3>{currently_in_arena.len()}{" members in arena"}</h3>
                             <UserAccountTable accounts={currently_in_arena.clone()} users={users.iter().map(|u| u.into()).collect::<Vec<_>>()} sponsorships={sponsorships.clone()} action_col_cb={get_kick_btn_cb} />
@@ -240,6 +336,181 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                 },
             }
         }
+        communication::auction::state::ActiveBidState::DutchAuctionBid {
+            current_price,
+            current_price_decrease_per_100_seconds,
+            minimum_price,
+        } => {
+            let current_clock_rate = *current_price_decrease_per_100_seconds;
+
+            // These two callbacks change the clock rate, in the same way as for the Japanese auction.
+            let clock_rate_up_cb = {
+                let send = send.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    let cr = current_clock_rate as f64;
+                    let new_crln = cr.ln() + 0.05;
+                    let new_cr = new_crln.exp();
+                    let new_clock_rate = new_cr.ceil() as Money;
+                    send.emit(AdminClientMessage::SetDutchClockRate(new_clock_rate));
+                })
+            };
+            let clock_rate_down_cb = {
+                let send = send.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    let cr = current_clock_rate as f64;
+                    let new_crln = cr.ln() - 0.05;
+                    let new_cr = new_crln.exp();
+                    let new_clock_rate = new_cr.floor() as Money;
+                    send.emit(AdminClientMessage::SetDutchClockRate(new_clock_rate));
+                })
+            };
+
+            html! {
+                <>
+                    <h1>{"Price clock is running"}</h1>
+                    <p>{"Current price: "}<MoneyDisplay money={current_price} /></p>
+                    <p>{"Minimum price: "}<MoneyDisplay money={minimum_price} /></p>
+                    <p>
+                        {"Current price decrease rate: -"}
+                        <MoneyDisplay money={current_price_decrease_per_100_seconds}/>
+                        {"/100 seconds"}
+                        <button class="btn btn-danger" onclick={clock_rate_down_cb}>{"-"}</button>
+                        <button class="btn btn-success" onclick={clock_rate_up_cb}>{"+"}</button>
+                    </p>
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::SealedBidAuctionBid {
+            bids_submitted_by,
+            pricing,
+        } => {
+            let close_bidding_cb = {
+                let send = send.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(AdminClientMessage::CloseSealedBidding);
+                })
+            };
+
+            html! {
+                <>
+                    <h1>{"Sealed bids are being collected"}</h1>
+                    <p>{match pricing {
+                        SealedBidPricing::FirstPrice => "The winner will pay their own bid",
+                        SealedBidPricing::SecondPrice => "Vickrey auction: the winner will pay the second-highest bid",
+                    }}</p>
+                    <p>
+                        <button class="btn btn-warning" onclick={close_bidding_cb}>
+                            {"Close bidding and reveal"}
+                        </button>
+                    </p>
+                    <div class="overflow-scroll" style="height: 40vh; max-height: 40vh;">
+                        <h3>{bids_submitted_by.len()}{" bids submitted"}</h3>
+                        <UserAccountTable accounts={bids_submitted_by.clone()} users={users.iter().map(|u| u.into()).collect::<Vec<_>>()} sponsorships={sponsorships.clone()} />
+                    </div>
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::AllPayAuctionBid {
+            bids,
+            seconds_until_commit,
+            ..
+        } => {
+            let total: Money = bids.iter().map(|(_, bid)| bid).sum();
+
+            html! {
+                <>
+                    <h1>{"All-pay auction is running"}</h1>
+                    <p>{"Every bidder will pay their last bid; the highest bidder gets the item."}</p>
+                    if bids.is_empty() {
+                        <p>{"No bids yet"}</p>
+                    } else {
+                        <p>{"Bidding ends in "}{format!("{seconds_until_commit:.1}s")}</p>
+                    }
+                    <p>{"Total to be paid: "}<MoneyDisplay money={total} /></p>
+                    <div class="overflow-scroll" style="height: 40vh; max-height: 40vh;">
+                        <table class="table table-sm">
+                            <thead>
+                                <tr>
+                                    <th scope="col">{"Member"}</th>
+                                    <th scope="col">{"Last bid"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {for bids.iter().map(|(user, bid)| html!(
+                                    <tr><td>{&user.user_name}</td><td><MoneyDisplay money={bid} /></td></tr>
+                                ))}
+                            </tbody>
+                        </table>
+                    </div>
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::RaffleEntry {
+            ticket_price,
+            tickets,
+            seconds_until_close,
+        } => {
+            let close_entries_cb = {
+                let send = send.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(AdminClientMessage::CloseRaffleEntries);
+                })
+            };
+
+            let total_tickets: u32 = tickets.iter().map(|(_, t)| *t).sum();
+            let seconds_left = *seconds_until_close as u64;
+
+            html! {
+                <>
+                    <h1>{"Raffle tickets are being sold"}</h1>
+                    <p>{"Ticket price: "}<MoneyDisplay money={ticket_price} /></p>
+                    <p>{"Tickets sold: "}{total_tickets}{"; pot: "}<MoneyDisplay money={total_tickets * ticket_price} /></p>
+                    <p>{"Entries close in "}{format!("{}:{:02}", seconds_left / 60, seconds_left % 60)}</p>
+                    <p>
+                        <button class="btn btn-warning" onclick={close_entries_cb}>
+                            {"Close entries and draw"}
+                        </button>
+                    </p>
+                    <div class="overflow-scroll" style="height: 40vh; max-height: 40vh;">
+                        <table class="table table-sm">
+                            <thead>
+                                <tr>
+                                    <th scope="col">{"Member"}</th>
+                                    <th scope="col">{"Tickets"}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                {for tickets.iter().map(|(user, count)| html!(
+                                    <tr><td>{&user.user_name}</td><td>{count}</td></tr>
+                                ))}
+                            </tbody>
+                        </table>
+                    </div>
+                </>
+            }
+        }
+    };
+    // Only English and Japanese auctions can be paused.
+    let can_pause = matches!(
+        props.bid_state.active_bid,
+        communication::auction::state::ActiveBidState::EnglishAuctionBid { .. }
+            | communication::auction::state::ActiveBidState::JapaneseAuctionBid(_)
+    );
+    let pause_cb = {
+        let send = send.clone();
+        let is_paused = props.bid_state.is_paused;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(if is_paused {
+                AdminClientMessage::ResumeAuction
+            } else {
+                AdminClientMessage::PauseAuction
+            });
+        })
     };
     let return_cb = {
         let send = send.clone();
@@ -260,6 +531,13 @@ pub fn ShowBidProgress(props: &ShowBidProgressProps) -> Html {
                 </div>
             </div>
             <div class="d-grid gap-2">
+                if can_pause {
+                    if props.bid_state.is_paused {
+                        <button onclick={pause_cb} class="btn btn-success">{"Resume auction"}</button>
+                    } else {
+                        <button onclick={pause_cb} class="btn btn-warning">{"Pause auction"}</button>
+                    }
+                }
                 <button onclick={return_cb} class="btn btn-danger">{"Return to item select"}</button>
             </div>
 
@@ -0,0 +1,140 @@
+use std::rc::Rc;
+
+use common::components::{MoneyDisplay, NumberInput};
+use communication::{
+    auction::state::{SilentAuctionItemState, SilentAuctionOutcome},
+    AdminClientMessage, ItemStateValue,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+fn silent_item_row(
+    item_state: &SilentAuctionItemState,
+    send: &Callback<AdminClientMessage>,
+) -> Html {
+    let item_id = item_state.item.id;
+    let close_cb = {
+        let send = send.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::CloseSilentAuctionItem(item_id));
+        })
+    };
+    let remove_cb = {
+        let send = send.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(AdminClientMessage::RemoveSilentAuctionItem(item_id));
+        })
+    };
+
+    let current_bid = match &item_state.current_bid {
+        None => html!({ "No bids" }),
+        Some((bidder, amount)) => {
+            html!(<><MoneyDisplay money={*amount} />{" by "}{&bidder.user_name}</>)
+        }
+    };
+
+    let status = match &item_state.outcome {
+        None => {
+            let seconds_left = item_state.seconds_until_close as u64;
+            html!({ format!("Closes in {}:{:02}", seconds_left / 60, seconds_left % 60) })
+        }
+        Some(SilentAuctionOutcome::Sold(sale)) => html! {
+            <>
+                {"Sold to "}{&sale.sold_to.user_name}{" for "}<MoneyDisplay money={sale.sold_for} />
+                {"; code: "}<strong>{&sale.confirmation_code}</strong>
+            </>
+        },
+        Some(SilentAuctionOutcome::NotSold) => html!({ "Not sold" }),
+    };
+
+    html! {
+        <tr>
+            <td>{&item_state.item.name}</td>
+            <td>{current_bid}</td>
+            <td>{status}</td>
+            <td>
+                if item_state.outcome.is_none() {
+                    <button class="btn btn-warning me-2" onclick={close_cb}>{"Close now"}</button>
+                }
+                <button class="btn btn-danger" onclick={remove_cb}>{"Remove"}</button>
+            </td>
+        </tr>
+    }
+}
+
+/// Lets the admin open items for bidding in the silent auction, which runs alongside the live auction,
+/// and follow and close the bidding on them.
+#[function_component]
+pub fn SilentAuctionControls() -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let send = &ctx.send;
+    let silent_auction = &ctx.silent_auction;
+
+    let duration_minutes = use_state(|| 30u64);
+    let duration_cb = {
+        let duration_minutes = duration_minutes.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                duration_minutes.set(v);
+            }
+        })
+    };
+
+    // Items that can be opened: those that are still sellable by themselves, and not already in the silent auction.
+    let openable_items = ctx
+        .items
+        .iter()
+        .filter(|i| i.lot_id.is_none())
+        .filter(|i| !matches!(i.state, ItemStateValue::AlreadySold { .. }))
+        .filter(|i| !silent_auction.items.iter().any(|s| s.item.id == i.item.id))
+        .map(|i| {
+            let open_cb = {
+                let send = send.clone();
+                let item_id = i.item.id;
+                let duration_minutes = duration_minutes.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(AdminClientMessage::OpenSilentAuctionItem {
+                        item_id,
+                        duration_secs: *duration_minutes * 60,
+                    });
+                })
+            };
+            html! {
+                <button class="btn btn-outline-primary me-2 mb-2" onclick={open_cb}>{"Open: "}{&i.item.name}</button>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    html! {
+        <>
+            <h2>{"Silent auction"}</h2>
+            if !silent_auction.items.is_empty() {
+                <table class="table table-sm">
+                    <thead>
+                        <tr>
+                            <th scope="col">{"Item"}</th>
+                            <th scope="col">{"Highest bid"}</th>
+                            <th scope="col">{"Status"}</th>
+                            <th scope="col">{"Action"}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {for silent_auction.items.iter().map(|i| silent_item_row(i, send))}
+                    </tbody>
+                </table>
+            }
+            <div class="input-group mb-2">
+                <span class="input-group-text">{"Bidding time (minutes)"}</span>
+                <NumberInput prefill_value={duration_minutes.to_string()} onchange={duration_cb} min="1" max="1440" step="1" />
+            </div>
+            <div>
+                {for openable_items}
+            </div>
+        </>
+    }
+}
@@ -8,7 +8,7 @@ use admin_ui::SendToServer;
 use common::layout::{Container, VerticalStack};
 use common::screens::fullscreen_message::FullscreenMsg;
 use communication::admin_state::AdminState;
-use communication::auction::state::{AuctionState, Sponsorship};
+use communication::auction::state::{AuctionState, SilentAuctionState, Sponsorship};
 use communication::{
     decode, encode, AdminClientMessage, AdminServerMessage, ItemState, LoginRequest,
     UserAccountDataWithSecrets, WithTimestamp,
@@ -90,6 +90,8 @@ fn main_app() -> Html {
     let auction_members = use_state(|| WithTimestamp::new_with_zero_time(vec![]));
     let item_states = use_state(|| WithTimestamp::new_with_zero_time(vec![]));
     let sponsorship_states = use_state(|| None);
+    let silent_auction_state =
+        use_state(|| WithTimestamp::new_with_zero_time(SilentAuctionState::default()));
 
     {
         let ws = ws.clone();
@@ -99,6 +101,7 @@ fn main_app() -> Html {
         let item_states = item_states.clone();
         let admin_state = admin_state.clone();
         let sponsorship_states = sponsorship_states.clone();
+        let silent_auction_state = silent_auction_state.clone();
         // Receive message by depending on `ws.message_bytes`.
         use_effect_with_deps(
             move |message| {
@@ -117,6 +120,9 @@ fn main_app() -> Html {
                             AdminServerMessage::SponsorshipState(state) => {
                                 sponsorship_states.set(Some(state))
                             }
+                            AdminServerMessage::SilentAuctionState(state) => {
+                                silent_auction_state.set(state)
+                            }
                         },
                     }
                 }
@@ -171,6 +177,7 @@ fn main_app() -> Html {
                         send: send_cb,
                         items: item_states.data.clone(),
                         users: auction_members.data.clone(),
+                        silent_auction: silent_auction_state.data.clone(),
                     };
                     let ctx = Rc::new(ctx);
                     html!(
@@ -198,6 +205,7 @@ pub struct AppCtx {
     send: SendToServer,
     items: Vec<ItemState>,
     users: Vec<UserAccountDataWithSecrets>,
+    silent_auction: SilentAuctionState,
 }
 
 #[function_component(AppWrapper)]
@@ -1,6 +1,6 @@
 use communication::{
     auction::state::{AuctionItem, AuctionReport, Sponsorship},
-    Money, UserAccountData,
+    ItemState, Money, UserAccountData,
 };
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::HtmlInputElement;
@@ -54,7 +54,7 @@ pub fn UserAccountTable(props: &UserAccountTableProps) -> Html {
                 { for props.accounts.iter().map(|i| html!(
                     <tr>
                     <td>{&i.user_name}</td>
-                    <td><MoneyDisplay money={Sponsorship::resolve_available_balance(i.id, &props.users, &props.sponsorships)} /></td>
+                    <td><MoneyDisplay money={Sponsorship::resolve_available_balance(i.id, &props.users, &props.sponsorships, None)} /></td>
                     {if props.action_col_cb.is_some() {
                         let html = props.action_col_cb.as_ref().unwrap().emit(i.clone());
                         html!(<td>{html}</td>)
@@ -67,6 +67,37 @@ pub fn UserAccountTable(props: &UserAccountTableProps) -> Html {
     }
 }
 
+#[derive(Properties, PartialEq)]
+pub struct RevealedBidsTableProps {
+    pub bids: Vec<(UserAccountData, Money)>,
+    pub highlight_user_id: Option<i64>,
+}
+
+/// Table showing the hidden bids of an auction after they are revealed, from highest to lowest.
+#[function_component]
+pub fn RevealedBidsTable(props: &RevealedBidsTableProps) -> Html {
+    html! {
+        <table class="table table-sm">
+            <thead>
+                <tr>
+                    <th scope="col">{"Rank"}</th>
+                    <th scope="col">{"Name"}</th>
+                    <th scope="col">{"Bid"}</th>
+                </tr>
+            </thead>
+            <tbody>
+                { for props.bids.iter().enumerate().map(|(rank, (user, bid))| html!(
+                    <tr class={classes!(props.highlight_user_id.and_then(|i| (i==user.id).then_some("table-active")))}>
+                        <td>{rank + 1}</td>
+                        <td>{&user.user_name}</td>
+                        <td><MoneyDisplay money={bid} /></td>
+                    </tr>
+                ))}
+            </tbody>
+        </table>
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct MoneyDisplayProps {
     pub money: Money,
@@ -90,7 +121,8 @@ pub struct ItemDisplayProps {
     pub item: AuctionItem,
 }
 
-/// Show overview info card about an item, including its name and initial price.
+/// Show overview info card about an item, including its name and initial price,
+/// and its reserve price if it is known (members are not sent it).
 #[function_component]
 pub fn ItemDisplay(props: &ItemDisplayProps) -> Html {
     let item = &props.item;
@@ -99,11 +131,119 @@ pub fn ItemDisplay(props: &ItemDisplayProps) -> Html {
             <div class="card-body">
                 <h5 class="card-title">{&item.name}</h5>
                 <h6 class="card-subtitle">{"Initial price: "}<MoneyDisplay money={item.initial_price} /></h6>
+                if let Some(category) = &item.category {
+                    <p class="card-text">{"Category: "}{category}</p>
+                }
+                if item.quantity > 1 {
+                    <p class="card-text">{"Quantity: "}{item.quantity}{" units, each sold to a different member"}</p>
+                }
+                if let Some(buy_now_price) = item.buy_now_price {
+                    <p class="card-text">{"Buy now price: "}<MoneyDisplay money={buy_now_price} /></p>
+                }
+                if let Some(reserve_price) = item.reserve_price {
+                    <p class="card-text">{"Hidden reserve price: "}<MoneyDisplay money={reserve_price} /></p>
+                }
             </div>
         </div>
     )
 }
 
+/// The name of an item in a report; if it is a lot, with the names of the items in it.
+fn lot_display_name(items: &[ItemState], item: &AuctionItem) -> String {
+    let lot_item_names: Vec<_> = items
+        .iter()
+        .filter(|i| i.lot_id == Some(item.id))
+        .map(|i| i.item.name.as_str())
+        .collect();
+    if lot_item_names.is_empty() {
+        item.name.clone()
+    } else {
+        format!("{} (lot: {})", item.name, lot_item_names.join(", "))
+    }
+}
+
+/// The rows of the item-oriented report for one item: one for each sale,
+/// and one for each payment by a member who did not win, or one if it was not sold.
+fn report_item_rows(
+    item_state: &ItemState,
+    name: String,
+    highlight_user_id: Option<i64>,
+) -> Vec<Html> {
+    let mut rows = vec![];
+    let mut payments = vec![];
+    for sale in item_state.state.get_sales() {
+        // The buyer's row explains how the unit was won, and how its price was split with their sponsors.
+        let buyer_note: Vec<_> = sale
+            .tie_break
+            .map(|t| t.to_string())
+            .into_iter()
+            .chain(sale.contribution_split.map(|s| s.to_string()))
+            .collect();
+        payments.push((
+            &sale.buyer,
+            sale.buyer_paid(),
+            (!buyer_note.is_empty()).then(|| buyer_note.join("; ")),
+        ));
+        payments.extend(
+            sale.other_payments.iter().map(|(payer, amount)| {
+                (payer, *amount, Some(sale.other_payments_note().to_string()))
+            }),
+        );
+    }
+    let is_highlighted = |buyer: &UserAccountData| Some(buyer.id) == highlight_user_id;
+    let (first_payment, other_payments) = match payments.split_first() {
+        Some(split) => split,
+        None => {
+            let who_bought = match &item_state.state {
+                communication::ItemStateValue::ReserveNotMet => html! {
+                    <td colspan=2 style="text-align: center;">{"Not sold: reserve not met"}</td>
+                },
+                _ => html! {
+                    // Nobody bought this, so draw a null symbol
+                    <td colspan=2 style="text-align: center;">{"∅"}</td> // Empty set symbol U+2205
+                },
+            };
+            rows.push(html!(
+                <tr>
+                    <th scope="row">{name}</th>
+                    <td><MoneyDisplay money={item_state.item.initial_price} /></td>
+                    {who_bought}
+                </tr>
+            ));
+            return rows;
+        }
+    };
+
+    // Members who paid without winning are marked as such, and so are those who won in a tie.
+    let payer_name = |payer: &UserAccountData, note: &Option<String>| match note {
+        None => payer.user_name.clone(),
+        Some(note) => format!("{} ({note})", payer.user_name),
+    };
+
+    // If several units were sold, or several members paid, then the first row will contain the item name and initial price,
+    // and be rowspan'd to the number of payments.
+    let (first_payer, first_amount, first_note) = first_payment;
+    rows.push(html!(
+        <tr class={classes!(is_highlighted(first_payer).then_some("table-active"))}>
+            <th scope="row" rowspan={payments.len().to_string()}>{name}</th>
+            <td rowspan={payments.len().to_string()}><MoneyDisplay money={item_state.item.initial_price} /></td>
+            <td>{payer_name(first_payer, first_note)}</td>
+            <td><MoneyDisplay money={*first_amount} /></td>
+        </tr>
+    ));
+
+    // The other rows will contain only the payer and price.
+    for (payer, amount, note) in other_payments {
+        rows.push(html!(
+            <tr class={classes!(is_highlighted(payer).then_some("table-active"))}>
+                <td>{payer_name(payer, note)}</td>
+                <td><MoneyDisplay money={*amount} /></td>
+            </tr>
+        ));
+    }
+    rows
+}
+
 #[derive(Properties, PartialEq)]
 pub struct AuctionReportViewProps {
     pub report: AuctionReport,
@@ -146,16 +286,31 @@ pub fn AuctionReportView(props: &AuctionReportViewProps) -> Html {
             let mut rows = vec![];
             for user in &props.report.members {
                 let user_id = user.id;
-                // For each user, figure out which items they purchased.
+                // For each user, figure out which items they purchased, and for how much,
+                // and which items they paid for without winning them.
+                // The items in a lot are listed as part of the lot.
                 let items_bought: Vec<_> = props
                     .report
                     .items
                     .iter()
-                    .filter(|i| match &i.state {
-                        communication::ItemStateValue::Sellable => false,
-                        communication::ItemStateValue::AlreadySold { buyer, .. } => {
-                            buyer.id == user_id
-                        }
+                    .filter(|i| i.lot_id.is_none())
+                    .flat_map(|i| {
+                        let name = lot_display_name(&props.report.items, &i.item);
+                        i.state.get_sales().iter().filter_map(move |sale| {
+                            if sale.buyer.id == user_id {
+                                Some((name.clone(), sale.buyer_paid()))
+                            } else {
+                                sale.other_payments
+                                    .iter()
+                                    .find(|(payer, _)| payer.id == user_id)
+                                    .map(|(_, amount)| {
+                                        (
+                                            format!("{name} ({})", sale.other_payments_note()),
+                                            *amount,
+                                        )
+                                    })
+                            }
+                        })
                     })
                     .collect();
 
@@ -173,24 +328,24 @@ pub fn AuctionReportView(props: &AuctionReportViewProps) -> Html {
                 }
 
                 // If there are items, then the first row will contain the name and balance, and be rowspan'd to the number of items.
-                let first_item = items_bought.first().unwrap();
+                let (first_item, first_price) = items_bought.first().unwrap();
                 rows.push(html!(
                     // Highlight if: `highlight_user_id` is provided, and is equal to the current user's ID.
                     <tr class={classes!(props.highlight_user_id.and_then(|i| (i==user_id).then_some("table-active")))}>
                         <th scope="row" rowspan={items_bought.len().to_string()}>{&user.user_name}</th>
                         <td rowspan={items_bought.len().to_string()}><MoneyDisplay money={user.balance} /></td>
-                        <td>{&first_item.item.name}</td>
-                        <td><MoneyDisplay money={first_item.state.get_sale_price().unwrap()} /></td>
+                        <td>{first_item}</td>
+                        <td><MoneyDisplay money={first_price} /></td>
                     </tr>
                 ));
 
                 // The other rows will contain only the item and price, as the name and balance are rowspanned.
-                for item in items_bought.iter().skip(1) {
+                for (item, price) in items_bought.iter().skip(1) {
                     rows.push(html!(
                         // Highlight if: `highlight_user_id` is provided, and is equal to the current user's ID.
                         <tr class={classes!(props.highlight_user_id.and_then(|i| (i==user_id).then_some("table-active")))}>
-                            <td>{&item.item.name}</td>
-                            <td><MoneyDisplay money={item.state.get_sale_price().unwrap()} /></td>
+                            <td>{item}</td>
+                            <td><MoneyDisplay money={price} /></td>
                         </tr>
                     ));
                 }
@@ -213,34 +368,26 @@ pub fn AuctionReportView(props: &AuctionReportViewProps) -> Html {
         }
         ItemFirst => {
             let mut rows = vec![];
-            for item_state in &props.report.items {
-                let do_highlight = match &item_state.state {
-                    communication::ItemStateValue::Sellable => false,
-                    communication::ItemStateValue::AlreadySold { buyer, .. } => {
-                        Some(buyer.id) == props.highlight_user_id
-                    }
-                };
-                let who_bought = match &item_state.state {
-                    communication::ItemStateValue::Sellable => html! {
-                            // Nobody bought this, so draw a null symbol
-                            <td colspan=2 style="text-align: center;">{"∅"}</td> // Empty set symbol U+2205
-                    },
-                    communication::ItemStateValue::AlreadySold { buyer, sale_price } => html! {
-                        <>
-                            <td>{&buyer.user_name}</td>
-                            <td><MoneyDisplay money={sale_price} /></td>
-                        </>
-                    },
-                };
-
-                let row = html!(
-                    <tr class={classes!(do_highlight.then_some("table-active"))}>
-                        <th scope="row">{&item_state.item.name}</th>
-                        <td><MoneyDisplay money={item_state.item.initial_price} /></td>
-                        {who_bought}
-                    </tr>
-                );
-                rows.push(row);
+            for item_state in props.report.items.iter().filter(|i| i.lot_id.is_none()) {
+                rows.extend(report_item_rows(
+                    item_state,
+                    item_state.item.name.clone(),
+                    props.highlight_user_id,
+                ));
+
+                // The items in a lot are shown under it, with their shares of the lot's sale price.
+                for lot_item in props
+                    .report
+                    .items
+                    .iter()
+                    .filter(|i| i.lot_id == Some(item_state.item.id))
+                {
+                    rows.extend(report_item_rows(
+                        lot_item,
+                        format!("↳ {}", lot_item.item.name), // Downwards arrow with tip rightwards U+21B3
+                        props.highlight_user_id,
+                    ));
+                }
             }
 
             html! {
@@ -14,12 +14,59 @@ use crate::{
         bidding_screen::BiddingScreen,
         item_sold::{SoldToSomeoneElse, SoldToYou},
         show_item_before_bid::ShowItemBeforeBid,
+        silent_auction::SilentAuctionScreen,
     },
     AppCtx,
 };
 
 #[function_component]
 pub fn AuctionView() -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let showing_silent_auction = use_state(|| false);
+
+    // While there are items in the silent auction, these tabs switch between it and the live auction.
+    if ctx.silent_auction.items.is_empty() {
+        return html!(<LiveAuctionView />);
+    }
+    let open_count = ctx
+        .silent_auction
+        .items
+        .iter()
+        .filter(|i| i.outcome.is_none())
+        .count();
+    let tab_cb = |show_silent: bool| {
+        let showing_silent_auction = showing_silent_auction.clone();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            showing_silent_auction.set(show_silent);
+        })
+    };
+
+    html! {
+        <>
+            <ul class="nav nav-tabs">
+                <li class="nav-item">
+                    <a href="#" onclick={tab_cb(false)} class={classes!("nav-link", (!*showing_silent_auction).then_some("active"))}>
+                        {"Live auction"}
+                    </a>
+                </li>
+                <li class="nav-item">
+                    <a href="#" onclick={tab_cb(true)} class={classes!("nav-link", (*showing_silent_auction).then_some("active"))}>
+                        {"Silent auction ("}{open_count}{" open)"}
+                    </a>
+                </li>
+            </ul>
+            if *showing_silent_auction {
+                <SilentAuctionScreen />
+            } else {
+                <LiveAuctionView />
+            }
+        </>
+    }
+}
+
+#[function_component]
+fn LiveAuctionView() -> Html {
     let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
     let my_account = &ctx.my_account;
     match &ctx.state {
@@ -51,16 +98,20 @@ pub fn AuctionView() -> Html {
             sold_for,
             confirmation_code,
             contributions,
+            revealed_bids,
+            other_units,
         } => {
-            html!(<SoldToYou item={item.clone()} sold_for={sold_for} confirmation_code={confirmation_code.clone()} contributions={contributions.clone()} />)
+            html!(<SoldToYou item={item.clone()} sold_for={sold_for} confirmation_code={confirmation_code.clone()} contributions={contributions.clone()} revealed_bids={revealed_bids.clone()} other_units={other_units.clone()} />)
         }
         AuctionState::SoldToSomeoneElse {
             item,
             sold_to,
             sold_for,
             contributions,
+            revealed_bids,
+            other_units,
         } => {
-            html!(<SoldToSomeoneElse item={item.clone()} sold_to={sold_to.clone()} sold_for={sold_for} contributions={contributions.clone()} />)
+            html!(<SoldToSomeoneElse item={item.clone()} sold_to={sold_to.clone()} sold_for={sold_for} contributions={contributions.clone()} revealed_bids={revealed_bids.clone()} other_units={other_units.clone()} />)
         }
         _ => {
             html!(<FullscreenMsg message={format!("Current auction state is not implemented: {:?}", ctx.state)} show_reload_button={true} user_account={Some((my_account).into())} />)
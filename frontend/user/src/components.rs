@@ -1,3 +1,6 @@
 pub mod bidding_screen;
+pub mod buy_now_button;
 pub mod item_sold;
 pub mod show_item_before_bid;
+pub mod silent_auction;
+pub mod sponsorship_log;
@@ -11,16 +11,26 @@ use communication::{
 use yew::prelude::*;
 
 use crate::{
-    components::bidding_screen::{
-        sponsorship_edit::SponsorshipEdit, sponsorship_mode_set::SponsorshipModeSet,
+    components::{
+        bidding_screen::{
+            sponsorship_edit::SponsorshipEdit, sponsorship_mode_set::SponsorshipModeSet,
+        },
+        buy_now_button::BuyNowButton,
     },
     AppCtx,
 };
 
-use {english::EnglishAuctionBidInput, japanese::JapaneseAuctionBidInput};
+use {
+    all_pay::AllPayAuctionBidInput, dutch::DutchAuctionBidInput, english::EnglishAuctionBidInput,
+    japanese::JapaneseAuctionBidInput, raffle::RaffleTicketInput, sealed_bid::SealedBidInput,
+};
 
+mod all_pay;
+mod dutch;
 mod english;
 mod japanese;
+mod raffle;
+mod sealed_bid;
 pub mod sponsorship_edit;
 pub mod sponsorship_mode_set;
 
@@ -37,6 +47,7 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
 
     let item = &props.bid_state.item;
     // These tabs at the top allow you to choose between betting and sponsoring mode.
+    // Below them, it is shown if the auction is paused.
     let mode_tabs = {
         let mode = my_account.sale_mode.clone();
         let set_bidding_cb = {
@@ -55,6 +66,7 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
         };
 
         html!(
+            <>
             <ul class="nav nav-tabs">
                 <li class="nav-item">
                     <a href="#" onclick={set_bidding_cb} class={classes!("nav-link", (mode == UserSaleMode::Bidding).then_some("active"))}>
@@ -67,6 +79,12 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
                     </a>
                 </li>
             </ul>
+            if props.bid_state.is_paused {
+                <div class="alert alert-warning mt-2">
+                    <strong>{"The auction is paused."}</strong>{" The timer is stopped, and no bids are accepted until the auction continues."}
+                </div>
+            }
+            </>
         )
     };
 
@@ -76,11 +94,22 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
         communication::auction::state::ActiveBidState::EnglishAuctionBid {
             current_bid_amount,
             current_bidder,
+            units,
+            winning_bids,
             minimum_increment,
             seconds_until_commit,
             max_millis_until_commit,
+            soft_close_policy,
+            proxy_bids,
+            ..
         } => {
-            let bid_is_me = current_bidder.id == my_account.id;
+            // If several units are being sold, any of the winning bids may be mine.
+            let bid_is_me = winning_bids.iter().any(|(u, _)| u.id == my_account.id);
+            // The server only sends us our own maximum bid.
+            let my_proxy_bid = proxy_bids
+                .iter()
+                .find(|(u, _)| u.id == my_account.id)
+                .map(|(_, max_bid)| *max_bid);
             let english_screen = if i_am_bidding {
                 html!(
                         <Container class={classes!(bid_is_me.then_some("bg-success"))}>
@@ -89,11 +118,28 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
                                     {"Bidding on: "}{&item.name}
                                 </h1>
                                 <SponsorshipModeSet />
-                                <p>
-                                    {"Current top bid: "}<MoneyDisplay money={current_bid_amount} />
-                                </p>
-                                <UserAccountCard account={current_bidder.clone()} />
-                                <EnglishAuctionBidInput item_id={item.id} current_bid={current_bid_amount} increment={minimum_increment} seconds_left={seconds_until_commit} {max_millis_until_commit} />
+                                if *units > 1 {
+                                    <p>
+                                        {"Units for sale: "}{units}{"; each of the "}{units}{" highest bidders wins one"}
+                                    </p>
+                                    <p>
+                                        {"Lowest winning bid: "}<MoneyDisplay money={current_bid_amount} />
+                                    </p>
+                                    <ul>
+                                        { for winning_bids.iter().map(|(user, bid)| html!(
+                                            <li>{&user.user_name}{": "}<MoneyDisplay money={bid} /></li>
+                                        ))}
+                                    </ul>
+                                } else {
+                                    <p>
+                                        {"Current top bid: "}<MoneyDisplay money={current_bid_amount} />
+                                    </p>
+                                    <UserAccountCard account={current_bidder.clone()} />
+                                }
+                                <EnglishAuctionBidInput item={item.clone()} current_bid={current_bid_amount} increment={minimum_increment} seconds_left={seconds_until_commit} {max_millis_until_commit} {my_proxy_bid} soft_close_policy={*soft_close_policy} />
+                                if let Some(buy_now_price) = item.buy_now_price.filter(|p| current_bid_amount < p) {
+                                    <BuyNowButton item={item.clone()} {buy_now_price} />
+                                }
                             </VerticalStack>
                         </Container>
                 )
@@ -127,7 +173,10 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
                                 {"Bidding on: "}{&item.name}
                             </h1>
                             <SponsorshipModeSet  />
-                            <JapaneseAuctionBidInput item_id={item.id} state={state.clone()} />
+                            if item.quantity > 1 {
+                                <p>{"Several units are for sale: when no more members are left in the arena than there are units left, each of them wins one."}</p>
+                            }
+                            <JapaneseAuctionBidInput item={item.clone()} state={state.clone()} />
                         </VerticalStack>
                     </Container>
                 )
@@ -178,6 +227,152 @@ pub fn BiddingScreen(props: &BiddingScreenProps) -> Html {
                 </>
             }
         }
+        communication::auction::state::ActiveBidState::DutchAuctionBid {
+            current_price,
+            minimum_price,
+            ..
+        } => {
+            let dutch_screen = if i_am_bidding {
+                html!(
+                    <Container>
+                        <VerticalStack>
+                            <h1>
+                                {"Bidding on: "}{&item.name}
+                            </h1>
+                            <SponsorshipModeSet />
+                            <DutchAuctionBidInput item={item.clone()} current_price={current_price} minimum_price={minimum_price} />
+                        </VerticalStack>
+                    </Container>
+                )
+            } else {
+                html!(
+                    <Container>
+                        <div class="alert alert-info">
+                            {"Item for sale: "}{&item.name}{"; "}
+                            {"Current price: "}
+                            <MoneyDisplay money={current_price} />
+                        </div>
+                        <SponsorshipEdit bid_state={props.bid_state.clone()}/>
+                    </Container>
+                )
+            };
+            html! {
+                <>
+                    {mode_tabs}
+                    {dutch_screen}
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::SealedBidAuctionBid {
+            bids_submitted_by,
+            pricing,
+        } => {
+            let sealed_bid_screen = if i_am_bidding {
+                html!(
+                    <Container>
+                        <VerticalStack>
+                            <h1>
+                                {"Bidding on: "}{&item.name}
+                            </h1>
+                            <SponsorshipModeSet />
+                            <SealedBidInput item={item.clone()} initial_price={item.initial_price} bids_submitted_by={bids_submitted_by.clone()} pricing={*pricing} />
+                        </VerticalStack>
+                    </Container>
+                )
+            } else {
+                html!(
+                    <Container>
+                        <div class="alert alert-info">
+                            {"Item for sale: "}{&item.name}{"; "}
+                            {"hidden bids submitted: "}{bids_submitted_by.len()}
+                        </div>
+                        <SponsorshipEdit bid_state={props.bid_state.clone()}/>
+                    </Container>
+                )
+            };
+            html! {
+                <>
+                    {mode_tabs}
+                    {sealed_bid_screen}
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::AllPayAuctionBid {
+            bids,
+            minimum_bid,
+            seconds_until_commit,
+            max_millis_until_commit,
+        } => {
+            let all_pay_screen = if i_am_bidding {
+                html!(
+                    <Container>
+                        <VerticalStack>
+                            <h1>
+                                {"Bidding on: "}{&item.name}
+                            </h1>
+                            <SponsorshipModeSet />
+                            <AllPayAuctionBidInput item={item.clone()} bids={bids.clone()} minimum_bid={*minimum_bid} seconds_left={*seconds_until_commit} max_millis_until_commit={*max_millis_until_commit} />
+                        </VerticalStack>
+                    </Container>
+                )
+            } else {
+                html!(
+                    <Container>
+                        <div class="alert alert-info">
+                            {"Item for sale: "}{&item.name}{"; "}
+                            {"every bidder pays their bid; "}
+                            if let Some((bidder, bid)) = bids.first() {
+                                {"highest bid: "}<MoneyDisplay money={bid} />{" by "}{&bidder.user_name}
+                            } else {
+                                {"no bids yet"}
+                            }
+                        </div>
+                        <SponsorshipEdit bid_state={props.bid_state.clone()}/>
+                    </Container>
+                )
+            };
+            html! {
+                <>
+                    {mode_tabs}
+                    {all_pay_screen}
+                </>
+            }
+        }
+        communication::auction::state::ActiveBidState::RaffleEntry {
+            ticket_price,
+            tickets,
+            seconds_until_close,
+        } => {
+            let raffle_screen = if i_am_bidding {
+                html!(
+                    <Container>
+                        <VerticalStack>
+                            <h1>
+                                {"Raffle for: "}{&item.name}
+                            </h1>
+                            <SponsorshipModeSet />
+                            <RaffleTicketInput item={item.clone()} ticket_price={*ticket_price} tickets={tickets.clone()} seconds_until_close={*seconds_until_close} />
+                        </VerticalStack>
+                    </Container>
+                )
+            } else {
+                html!(
+                    <Container>
+                        <div class="alert alert-info">
+                            {"Raffle for: "}{&item.name}{"; "}
+                            {"tickets sold: "}{tickets.iter().map(|(_, t)| *t).sum::<u32>()}
+                        </div>
+                        <SponsorshipEdit bid_state={props.bid_state.clone()}/>
+                    </Container>
+                )
+            };
+            html! {
+                <>
+                    {mode_tabs}
+                    {raffle_screen}
+                </>
+            }
+        }
     };
 
     bid_ui
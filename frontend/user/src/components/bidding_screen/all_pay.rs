@@ -0,0 +1,158 @@
+use std::rc::Rc;
+
+use common::components::{MoneyDisplay, NumberInput};
+use communication::{
+    auction::state::{AuctionItem, Sponsorship},
+    Money, UserAccountData, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+pub struct AllPayAuctionBidInputProps {
+    pub item: AuctionItem,
+    pub bids: Vec<(UserAccountData, Money)>,
+    pub minimum_bid: Money,
+    pub seconds_left: f32,
+    pub max_millis_until_commit: u128,
+}
+
+#[function_component]
+pub fn AllPayAuctionBidInput(props: &AllPayAuctionBidInputProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let users = &ctx.users;
+    let sponsorships = &ctx.sponsorships;
+    let send = &ctx.send;
+
+    let selected_bid = use_state_eq(|| props.minimum_bid);
+
+    let available_balance = use_state(|| 0);
+    {
+        let available_balance = available_balance.clone();
+        use_effect_with_deps(
+            move |(user_id, users, sponsorships, item)| {
+                available_balance.set(Sponsorship::resolve_available_balance(
+                    *user_id,
+                    users,
+                    sponsorships,
+                    Some(item),
+                ));
+            },
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
+        );
+    }
+
+    {
+        let selected_bid = selected_bid.clone();
+        use_effect_with_deps(
+            move |minimum_bid| {
+                // When somebody has bid at least as much as the selected bid,
+                // raise the selected bid to the new minimum.
+                if *minimum_bid > *selected_bid {
+                    selected_bid.set(*minimum_bid);
+                }
+            },
+            props.minimum_bid,
+        );
+    }
+
+    let my_bid = props
+        .bids
+        .iter()
+        .find(|(u, _)| u.id == my_account.id)
+        .map(|(_, bid)| *bid);
+    let i_am_winning = props
+        .bids
+        .first()
+        .is_some_and(|(u, _)| u.id == my_account.id);
+
+    let bid_input_cb = {
+        let selected_bid = selected_bid.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                selected_bid.set(v);
+            }
+        })
+    };
+
+    let send_cb = {
+        let selected_bid = selected_bid.clone();
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::BidInAllPayAuction {
+                item_id,
+                bid_amount: *selected_bid,
+            })
+        })
+    };
+
+    let send_btn = if *selected_bid > *available_balance {
+        html!(<button class="btn btn-lg btn-info-outline" disabled={true}>{"Cannot afford!"}</button>)
+    } else if *selected_bid < props.minimum_bid {
+        html!(<button class="btn btn-lg btn-info-outline" disabled={true}>{"Too low!"}</button>)
+    } else {
+        html!(<button class="btn btn-lg btn-info" onclick={send_cb}>{"Send bid: "}<MoneyDisplay money={*selected_bid} /></button>)
+    };
+
+    let max_time = props.max_millis_until_commit as f32 / 1000.0;
+    let seconds_left = props.seconds_left;
+    let percent_left = (seconds_left / max_time) * 100.0;
+    let percent_now = 100.0 - percent_left;
+    let (pb_first_style, pb_second_style, pb_text) = if props.bids.is_empty() {
+        (
+            String::from("width: 100%;"),
+            String::from("width: 0%;"),
+            String::from("No bid yet..."),
+        )
+    } else {
+        (
+            format!("width: {percent_left:.0}%;"),
+            format!("width: {percent_now:.0}%;"),
+            format!("{seconds_left:.1}s"),
+        )
+    };
+
+    html! {
+        <>
+            <div class="alert alert-warning">
+                {"Every bidder pays their last bid, even if they do not win. The highest bidder gets the item."}
+            </div>
+            if let Some(my_bid) = my_bid {
+                <p class={classes!(i_am_winning.then_some("text-success"))}>
+                    {"You will pay: "}<MoneyDisplay money={my_bid} />
+                    {if i_am_winning {" (your bid is the highest)"} else {" (you have been outbid)"}}
+                </p>
+            }
+            <div class="input-group input-group-lg mb-3">
+                <NumberInput prefill_value={selected_bid.to_string()} onchange={bid_input_cb} min={props.minimum_bid.to_string()} max={available_balance.to_string()} step="1" />
+                {send_btn}
+            </div>
+            <div class="progress-stacked mb-3">
+                <div class="progress" style={pb_first_style}>
+                    <div class="progress-bar progress-bar-striped progress-bar-animated">
+                    </div>
+                </div>
+                <div class="progress" style={pb_second_style}>
+                    <div class="progress-bar progress-bar-striped progress-bar-animated bg-danger">
+                        {pb_text}
+                    </div>
+                </div>
+            </div>
+            <ul>
+                {for props.bids.iter().map(|(user, bid)| html!(
+                    <li>{&user.user_name}{": "}<MoneyDisplay money={bid} /></li>
+                ))}
+            </ul>
+        </>
+    }
+}
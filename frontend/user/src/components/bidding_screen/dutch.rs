@@ -0,0 +1,73 @@
+use std::rc::Rc;
+
+use common::components::MoneyDisplay;
+use communication::{
+    auction::state::{AuctionItem, Sponsorship},
+    Money, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+pub struct DutchAuctionBidInputProps {
+    pub item: AuctionItem,
+    pub current_price: Money,
+    pub minimum_price: Money,
+}
+
+#[function_component]
+pub fn DutchAuctionBidInput(props: &DutchAuctionBidInputProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let users = &ctx.users;
+    let sponsorships = &ctx.sponsorships;
+    let send = &ctx.send;
+
+    let available_balance = use_state(|| 0);
+    {
+        let available_balance = available_balance.clone();
+        use_effect_with_deps(
+            move |(user_id, users, sponsorships, item)| {
+                available_balance.set(Sponsorship::resolve_available_balance(
+                    *user_id,
+                    users,
+                    sponsorships,
+                    Some(item),
+                ));
+            },
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
+        );
+    }
+
+    let claim_cb = {
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::ClaimInDutchAuction { item_id })
+        })
+    };
+
+    let claim_btn = if props.current_price > *available_balance {
+        html!(<button class="btn btn-lg btn-info-outline" disabled={true}>{"Cannot afford!"}</button>)
+    } else {
+        html!(<button class="btn btn-lg btn-info" onclick={claim_cb}>{"Buy now for: "}<MoneyDisplay money={props.current_price} /></button>)
+    };
+
+    html! {
+        <>
+            <h2>{"Current price: "}<MoneyDisplay money={props.current_price} /></h2>
+            <p>{"The price goes down until somebody buys the item, but not below "}<MoneyDisplay money={props.minimum_price} /></p>
+            <p>{"You can use: "}<MoneyDisplay money={*available_balance} /></p>
+            <div class="d-grid mb-3">
+                {claim_btn}
+            </div>
+        </>
+    }
+}
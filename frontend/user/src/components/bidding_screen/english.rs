@@ -1,18 +1,23 @@
 use std::rc::Rc;
 
-use common::components::MoneyDisplay;
-use communication::{auction::state::Sponsorship, Money, UserClientMessage};
+use common::components::{MoneyDisplay, NumberInput};
+use communication::{
+    auction::state::{AuctionItem, SoftClosePolicy, Sponsorship},
+    Money, UserClientMessage,
+};
 use yew::prelude::*;
 
 use crate::AppCtx;
 
 #[derive(Properties, PartialEq)]
 pub struct EnglishAuctionBidInputProps {
-    pub item_id: i64,
+    pub item: AuctionItem,
     pub current_bid: Money,
     pub increment: Money,
     pub seconds_left: f32,
     pub max_millis_until_commit: u128,
+    pub my_proxy_bid: Option<Money>,
+    pub soft_close_policy: SoftClosePolicy,
 }
 
 #[function_component]
@@ -29,14 +34,20 @@ pub fn EnglishAuctionBidInput(props: &EnglishAuctionBidInputProps) -> Html {
     {
         let available_balance = available_balance.clone();
         use_effect_with_deps(
-            move |(user_id, users, sponsorships)| {
+            move |(user_id, users, sponsorships, item)| {
                 available_balance.set(Sponsorship::resolve_available_balance(
                     *user_id,
                     &users,
                     &sponsorships,
+                    Some(item),
                 ));
             },
-            (my_account.id, users.clone(), sponsorships.clone()),
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
         );
     }
 
@@ -58,7 +69,7 @@ pub fn EnglishAuctionBidInput(props: &EnglishAuctionBidInputProps) -> Html {
     let send_cb = {
         let selected_bid = selected_bid.clone();
         let send = send.clone();
-        let item_id = props.item_id;
+        let item_id = props.item.id;
         Callback::from(move |e: MouseEvent| {
             e.prevent_default();
             send.emit(UserClientMessage::BidInEnglishAuction {
@@ -119,6 +130,62 @@ pub fn EnglishAuctionBidInput(props: &EnglishAuctionBidInputProps) -> Html {
         })
     };
 
+    // The secret maximum bid, up to which the server bids for us automatically.
+    let selected_proxy_bid = use_state_eq(|| props.current_bid + props.increment);
+    let proxy_bid_input_cb = {
+        let selected_proxy_bid = selected_proxy_bid.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                selected_proxy_bid.set(v);
+            }
+        })
+    };
+    let set_proxy_bid_cb = {
+        let selected_proxy_bid = selected_proxy_bid.clone();
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::SetProxyBidInEnglishAuction {
+                item_id,
+                max_bid: Some(*selected_proxy_bid),
+            })
+        })
+    };
+    let clear_proxy_bid_cb = {
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::SetProxyBidInEnglishAuction {
+                item_id,
+                max_bid: None,
+            })
+        })
+    };
+    let proxy_bid_btn = if *selected_proxy_bid > *available_balance {
+        html!(<button class="btn btn-outline-secondary" disabled={true}>{"Cannot afford!"}</button>)
+    } else if *selected_proxy_bid <= props.current_bid {
+        html!(<button class="btn btn-outline-secondary" disabled={true}>{"Too low!"}</button>)
+    } else {
+        html!(<button class="btn btn-secondary" onclick={set_proxy_bid_cb}>{"Bid for me up to this"}</button>)
+    };
+    let proxy_bid_status = match props.my_proxy_bid {
+        None => {
+            html!(<p>{"You can set a secret maximum bid, and you will be outbidding others automatically until it is reached."}</p>)
+        }
+        Some(max_bid) => html!(
+            <p>
+                {"Bidding for you automatically up to: "}<MoneyDisplay money={max_bid} />
+                {" "}<button class="btn btn-sm btn-danger" onclick={clear_proxy_bid_cb}>{"Stop"}</button>
+            </p>
+        ),
+    };
+
+    // In a candle auction, the time left is secret, so there is no countdown to show.
+    let is_candle = matches!(props.soft_close_policy, SoftClosePolicy::Candle { .. });
+
     let max_time = props.max_millis_until_commit as f32 / 1000.0;
     let seconds_left = props.seconds_left;
     let percent_left = (seconds_left / max_time) * 100.0;
@@ -147,16 +214,26 @@ pub fn EnglishAuctionBidInput(props: &EnglishAuctionBidInputProps) -> Html {
             <div class="d-grid mb-3">
                 {send_btn}
             </div>
-            <div class="progress-stacked mb-3">
-                <div class="progress" style={pb_first_style}>
-                    <div class="progress-bar progress-bar-striped progress-bar-animated">
+            if is_candle {
+                <div class="alert alert-warning">{"🕯 The candle is burning: bidding may end at any moment!"}</div>  // U+1F56F CANDLE
+            } else {
+                <div class="progress-stacked mb-3">
+                    <div class="progress" style={pb_first_style}>
+                        <div class="progress-bar progress-bar-striped progress-bar-animated">
+                        </div>
                     </div>
-                </div>
-                <div class="progress" style={pb_second_style}>
-                    <div class="progress-bar progress-bar-striped progress-bar-animated bg-danger">
-                        {pb_text}
+                    <div class="progress" style={pb_second_style}>
+                        <div class="progress-bar progress-bar-striped progress-bar-animated bg-danger">
+                            {pb_text}
+                        </div>
                     </div>
                 </div>
+            }
+            <p class="form-text">{props.soft_close_policy.to_string()}</p>
+            {proxy_bid_status}
+            <div class="input-group mb-3">
+                <NumberInput prefill_value={selected_proxy_bid.to_string()} onchange={proxy_bid_input_cb} min={(props.current_bid + props.increment).to_string()} max={available_balance.to_string()} step="1" />
+                {proxy_bid_btn}
             </div>
         </>
     }
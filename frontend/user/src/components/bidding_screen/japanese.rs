@@ -1,13 +1,13 @@
 use std::rc::Rc;
 
 use common::{
-    components::{MoneyDisplay, UserAccountTable},
+    components::{MoneyDisplay, NumberInput, UserAccountTable},
     layout::VerticalStack,
 };
 use communication::{
     auction::{
         actions::JapaneseAuctionAction,
-        state::{JapaneseAuctionBidState, Sponsorship},
+        state::{AuctionItem, JapaneseAuctionBidState, Sponsorship},
     },
     UserClientMessage,
 };
@@ -18,7 +18,7 @@ use crate::AppCtx;
 
 #[derive(Properties, PartialEq)]
 pub struct JapaneseAuctionBidInputProps {
-    pub item_id: i64,
+    pub item: AuctionItem,
     pub state: JapaneseAuctionBidState,
 }
 
@@ -41,14 +41,20 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
     {
         let available_balance = available_balance.clone();
         use_effect_with_deps(
-            move |(user_id, users, sponsorships)| {
+            move |(user_id, users, sponsorships, item)| {
                 available_balance.set(Sponsorship::resolve_available_balance(
                     *user_id,
                     &users,
                     &sponsorships,
+                    Some(item),
                 ));
             },
-            (my_account.id, users.clone(), sponsorships.clone()),
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
         );
     }
 
@@ -73,6 +79,20 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
 
     let arena_mode = props.state.get_arena_visibility_mode();
 
+    // The server only sends us our own drop-out price, and whether we left on it.
+    let my_drop_out_price = props
+        .state
+        .get_drop_out_prices()
+        .iter()
+        .find(|(u, _)| u.id == my_account.id)
+        .map(|(_, price)| *price);
+    let my_left_on_limit = props
+        .state
+        .get_left_on_limit()
+        .iter()
+        .find(|(u, _)| u.id == my_account.id)
+        .map(|(_, price)| *price);
+
     let pressed = use_state(|| false);
     let changed_recently = use_state(|| false);
     let changed_at = use_state(|| performance.now());
@@ -94,9 +114,14 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
         // If the button is released, and it has been released for more than the timeout,
         // and we are still in the arena, then we want to exit the arena.
         // The loop will stop as soon as the server recognizes our exit.
-        if (!*pressed) && ((performance.now() - *changed_at) > repress_delay) && me_in_arena {
+        // If we have set a drop-out price, we stay until the server takes us out at that price.
+        if (!*pressed)
+            && ((performance.now() - *changed_at) > repress_delay)
+            && me_in_arena
+            && my_drop_out_price.is_none()
+        {
             send.emit(UserClientMessage::JapaneseAuctionAction {
-                item_id: props.item_id,
+                item_id: props.item.id,
                 action: JapaneseAuctionAction::ExitArena,
             });
         }
@@ -109,7 +134,7 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
         // Also: if we are locked out because we don't have enough money, we'll not be able to enter here.
         if *pressed && !me_in_arena && !locked_out_of_arena {
             send.emit(UserClientMessage::JapaneseAuctionAction {
-                item_id: props.item_id,
+                item_id: props.item.id,
                 action: JapaneseAuctionAction::EnterArena,
             });
         }
@@ -160,8 +185,8 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
     let pb_style = if locked_out_of_arena {
         // If we are locked out, show a mild danger color, indicating nothing to do.
         String::from("height: 100%; width: 100%; background-color: var(--bs-danger-bg-subtle); border-radius: inherit;")
-    } else if *pressed {
-        // If we are still pressing, show a success color.
+    } else if *pressed || (me_in_arena && my_drop_out_price.is_some()) {
+        // If we are still pressing, or staying until our drop-out price, show a success color.
         String::from("height: 100%; width: 100%; background-color: var(--bs-success); border-radius: inherit;")
     } else if me_in_arena {
         // If we are not pressing, but still in the arena, show a success color, but the box is shrinking relative to how much time is left.
@@ -216,6 +241,48 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
         }
     };
 
+    let drop_out_price_input = use_state_eq(|| 0);
+    let drop_out_price_cb = {
+        let drop_out_price_input = drop_out_price_input.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                drop_out_price_input.set(v);
+            }
+        })
+    };
+    let get_set_drop_out_price_cb = |price: Option<_>| {
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::JapaneseAuctionAction {
+                item_id,
+                action: JapaneseAuctionAction::SetDropOutPrice(price),
+            });
+        })
+    };
+    let drop_out_info = if let Some(price) = my_left_on_limit {
+        html!(<p>{"You left automatically at your drop-out price: "}<MoneyDisplay money={price} /></p>)
+    } else if !me_in_arena {
+        html!()
+    } else if let Some(price) = my_drop_out_price {
+        html!(
+            <p>
+                {"You stay in until the price passes "}<MoneyDisplay money={price} />{", even if you let go."}
+                <button class="btn btn-sm btn-outline-secondary ms-2" onclick={get_set_drop_out_price_cb(None)}>{"Remove"}</button>
+            </p>
+        )
+    } else {
+        html!(
+            <div class="input-group mb-2">
+                <span class="input-group-text">{"Leave automatically above"}</span>
+                <NumberInput prefill_value={drop_out_price_input.to_string()} onchange={drop_out_price_cb} min={props.state.get_current_price().to_string()} max={available_balance.to_string()} step="1" />
+                <button class="btn btn-outline-primary" onclick={get_set_drop_out_price_cb(Some(*drop_out_price_input))}>{"Set"}</button>
+            </div>
+        )
+    };
+
     let arena_info = match arena_mode {
         communication::auction::state::ArenaVisibilityMode::Full => html!(
             <>
@@ -238,6 +305,7 @@ pub fn JapaneseAuctionBidInput(props: &JapaneseAuctionBidInputProps) -> Html {
     html! {
         <VerticalStack>
             {header_line}
+            {drop_out_info}
 
             // This is the click target.
             // It must not trigger select events on mobile,
@@ -0,0 +1,113 @@
+use std::rc::Rc;
+
+use common::components::{MoneyDisplay, NumberInput};
+use communication::{
+    auction::state::{AuctionItem, Sponsorship},
+    Money, UserAccountData, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+pub struct RaffleTicketInputProps {
+    pub item: AuctionItem,
+    pub ticket_price: Money,
+    pub tickets: Vec<(UserAccountData, u32)>,
+    pub seconds_until_close: f32,
+}
+
+#[function_component]
+pub fn RaffleTicketInput(props: &RaffleTicketInputProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let users = &ctx.users;
+    let sponsorships = &ctx.sponsorships;
+    let send = &ctx.send;
+
+    let selected_count = use_state_eq(|| 1u32);
+
+    let available_balance = use_state(|| 0);
+    {
+        let available_balance = available_balance.clone();
+        use_effect_with_deps(
+            move |(user_id, users, sponsorships, item)| {
+                available_balance.set(Sponsorship::resolve_available_balance(
+                    *user_id,
+                    users,
+                    sponsorships,
+                    Some(item),
+                ));
+            },
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
+        );
+    }
+
+    let my_tickets = props
+        .tickets
+        .iter()
+        .find(|(u, _)| u.id == my_account.id)
+        .map_or(0, |(_, t)| *t);
+    let total_tickets: u32 = props.tickets.iter().map(|(_, t)| *t).sum();
+
+    let count_input_cb = {
+        let selected_count = selected_count.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a number, ignore it.
+            if let Ok(v) = s.parse() {
+                selected_count.set(v);
+            }
+        })
+    };
+
+    let send_cb = {
+        let selected_count = selected_count.clone();
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::BuyRaffleTickets {
+                item_id,
+                count: *selected_count,
+            })
+        })
+    };
+
+    // All of my tickets are paid for together when the winner is drawn.
+    let total_cost = (my_tickets as u64 + *selected_count as u64) * props.ticket_price as u64;
+    let send_btn = if *selected_count == 0 {
+        html!(<button class="btn btn-info-outline" disabled={true}>{"Choose how many"}</button>)
+    } else if total_cost > *available_balance as u64 {
+        html!(<button class="btn btn-info-outline" disabled={true}>{"Cannot afford!"}</button>)
+    } else {
+        html!(<button class="btn btn-info" onclick={send_cb}>{"Buy "}{*selected_count}{" for: "}<MoneyDisplay money={*selected_count * props.ticket_price} /></button>)
+    };
+
+    // The most tickets I can still add to the ones I have.
+    let max_count = (*available_balance / props.ticket_price.max(1)).saturating_sub(my_tickets);
+
+    let seconds_left = props.seconds_until_close as u64;
+    html! {
+        <>
+            <h2>{"Ticket price: "}<MoneyDisplay money={props.ticket_price} /></h2>
+            <p>
+                {"Your tickets: "}{my_tickets}{" of "}{total_tickets}
+                if total_tickets > 0 {
+                    {format!(" ({:.1}% chance to win)", my_tickets as f32 * 100.0 / total_tickets as f32)}
+                }
+            </p>
+            <p>{"Entries close in "}{format!("{}:{:02}", seconds_left / 60, seconds_left % 60)}</p>
+            <p>{"You can use: "}<MoneyDisplay money={*available_balance} /></p>
+            <p class="form-text">{"Your tickets are paid for when the winner is drawn. If you can no longer pay for all of them by then, you keep only those you can pay for."}</p>
+            <div class="input-group mb-3">
+                <NumberInput prefill_value="1" onchange={count_input_cb} min="1" max={max_count.to_string()} step="1" />
+                {send_btn}
+            </div>
+        </>
+    }
+}
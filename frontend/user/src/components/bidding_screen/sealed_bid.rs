@@ -0,0 +1,119 @@
+use std::rc::Rc;
+
+use common::components::{MoneyDisplay, NumberInput};
+use communication::{
+    auction::state::{AuctionItem, SealedBidPricing, Sponsorship},
+    Money, UserAccountData, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+pub struct SealedBidInputProps {
+    pub item: AuctionItem,
+    pub initial_price: Money,
+    pub bids_submitted_by: Vec<UserAccountData>,
+    pub pricing: SealedBidPricing,
+}
+
+#[function_component]
+pub fn SealedBidInput(props: &SealedBidInputProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let users = &ctx.users;
+    let sponsorships = &ctx.sponsorships;
+    let send = &ctx.send;
+
+    let selected_bid = use_state_eq(|| props.initial_price);
+
+    let available_balance = use_state(|| 0);
+    {
+        let available_balance = available_balance.clone();
+        use_effect_with_deps(
+            move |(user_id, users, sponsorships, item)| {
+                available_balance.set(Sponsorship::resolve_available_balance(
+                    *user_id,
+                    users,
+                    sponsorships,
+                    Some(item),
+                ));
+            },
+            (
+                my_account.id,
+                users.clone(),
+                sponsorships.clone(),
+                props.item.clone(),
+            ),
+        );
+    }
+
+    let bid_count = props.bids_submitted_by.len();
+
+    // The server only accepts one bid per member, so once it has ours, there is nothing left to do.
+    if props
+        .bids_submitted_by
+        .iter()
+        .any(|u| u.id == my_account.id)
+    {
+        return html! {
+            <>
+                <div class="alert alert-success">{"Your bid has been submitted"}</div>
+                <p>{bid_count}{" bids submitted so far"}</p>
+            </>
+        };
+    }
+
+    let bid_input_cb = {
+        let selected_bid = selected_bid.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                selected_bid.set(v);
+            }
+        })
+    };
+
+    let send_cb = {
+        let selected_bid = selected_bid.clone();
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::BidInSealedBidAuction {
+                item_id,
+                bid_amount: *selected_bid,
+            })
+        })
+    };
+
+    let send_btn = if *selected_bid > *available_balance {
+        html!(<button class="btn btn-lg btn-info-outline" disabled={true}>{"Cannot afford!"}</button>)
+    } else if *selected_bid < props.initial_price {
+        html!(<button class="btn btn-lg btn-info-outline" disabled={true}>{"Too low!"}</button>)
+    } else {
+        html!(<button class="btn btn-lg btn-info" onclick={send_cb}>{"Submit hidden bid: "}<MoneyDisplay money={*selected_bid} /></button>)
+    };
+
+    let pricing_explanation = match props.pricing {
+        SealedBidPricing::FirstPrice => "The highest bid wins, and pays the amount that was bid.",
+        SealedBidPricing::SecondPrice => {
+            "The highest bid wins, but only pays the amount of the second-highest bid."
+        }
+    };
+
+    html! {
+        <>
+            <p>{"Nobody can see your bid until bidding is closed. You can only bid once."}</p>
+            <p>{pricing_explanation}</p>
+            <p>{"Minimum bid: "}<MoneyDisplay money={props.initial_price} />{"/ You can use: "}<MoneyDisplay money={*available_balance} /></p>
+            <div class="mb-3">
+                <NumberInput prefill_value={props.initial_price.to_string()} onchange={bid_input_cb} min={props.initial_price.to_string()} max={available_balance.to_string()} step="1" />
+            </div>
+            <div class="d-grid mb-3">
+                {send_btn}
+            </div>
+            <p>{bid_count}{" bids submitted so far"}</p>
+        </>
+    }
+}
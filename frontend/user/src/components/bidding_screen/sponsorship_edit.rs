@@ -1,15 +1,15 @@
 use std::{collections::HashMap, rc::Rc};
 
-use common::components::MoneyDisplay;
+use common::components::{MoneyDisplay, TextInput};
 use communication::{
-    auction::state::{BiddingState, Sponsorship, SponsorshipStatus},
+    auction::state::{BiddingState, Sponsorship, SponsorshipScope, SponsorshipStatus},
     UserClientMessage,
 };
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::HtmlInputElement;
 use yew::prelude::*;
 
-use crate::AppCtx;
+use crate::{components::sponsorship_log::SponsorshipLogPanel, AppCtx};
 
 #[derive(Properties, PartialEq)]
 pub struct SponsorshipEditProps {
@@ -28,6 +28,10 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
     // In an English auction, it is the single user who's currently bidding.
     // In a Japanese auction, it is all the users who are in the arena,
     // or none if the arena users aren't visible.
+    // In a Dutch auction, it is nobody.
+    // In a sealed-bid auction, it is all the users who have submitted a bid.
+    // In an all-pay auction, it is all the users who have bid.
+    // In a raffle, it is all the users who have bought tickets.
     let users_to_highlight;
     if let Some(bid_state) = &props.bid_state {
         users_to_highlight = match &bid_state.active_bid {
@@ -58,22 +62,37 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
                     _ => None,
                 },
             },
+            communication::auction::state::ActiveBidState::DutchAuctionBid { .. } => None,
+            communication::auction::state::ActiveBidState::SealedBidAuctionBid {
+                bids_submitted_by,
+                ..
+            } => Some(bids_submitted_by.iter().map(|u| u.id).collect()),
+            communication::auction::state::ActiveBidState::AllPayAuctionBid { bids, .. } => {
+                Some(bids.iter().map(|(u, _)| u.id).collect())
+            }
+            communication::auction::state::ActiveBidState::RaffleEntry { tickets, .. } => {
+                Some(tickets.iter().map(|(u, _)| u.id).collect())
+            }
         }
         .unwrap_or(vec![]);
     } else {
         users_to_highlight = vec![];
     }
 
+    // The balances shown are those that can be spent on the item being sold, if there is one.
+    let current_item = props.bid_state.as_ref().map(|b| &b.item);
+
     // Get the list of my own sponsorships.
     // Replace each with the newest version, or with the one that's Active.
+    // The requests that I have not answered yet are shown separately, in the inbox.
 
     let mut my_sponsorships = HashMap::new();
     let my_sponsorships_full_count = sponsorships
         .iter()
-        .filter(|s| s.donor_id == my_account.id)
+        .filter(|s| s.donor_id == my_account.id && s.status != SponsorshipStatus::Pending)
         .count();
     for s in sponsorships {
-        if s.donor_id != my_account.id {
+        if s.donor_id != my_account.id || s.status == SponsorshipStatus::Pending {
             continue;
         }
 
@@ -111,7 +130,7 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
                 {
                     for
                     could_sponsor.map(|u| {
-                        let available_balance = Sponsorship::resolve_available_balance(u.id, users, sponsorships);
+                        let available_balance = Sponsorship::resolve_available_balance(u.id, users, sponsorships, current_item);
                         html!(
                             <tr class={classes!(users_to_highlight.iter().any(|i| *i == u.id).then_some("table-active"))}>
                                 <td>{&u.user_name}</td>
@@ -139,6 +158,7 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
 
             let sponsorship_id = sponsorship.id;
             let sponsorship_status = match sponsorship.status {
+                SponsorshipStatus::Pending => html!(),
                 SponsorshipStatus::Rejected => {
                     html!(<span class="text-bg-danger">{"Rejected by recepient"}</span>)
                 }
@@ -172,6 +192,108 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
                     };
                     let b = sponsorship.balance_remaining;
 
+                    // The percentage cap is changed in steps of 10%, and having no cap counts as 100%.
+                    let set_percent_cb = |what: Option<u32>| {
+                        let send = send.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            send.emit(UserClientMessage::SetSponsorshipPercentCap {
+                                sponsorship_id,
+                                max_percent: what,
+                            })
+                        })
+                    };
+                    let p = sponsorship.max_percent.unwrap_or(100);
+                    let sub_10_percent_btn = if p > 10 {
+                        html!(<button class="btn btn-outline-danger" onclick={set_percent_cb(Some(p - 10))}>{"-10%"}</button>)
+                    } else {
+                        html!(<button class="btn btn-outline-danger" disabled={true}>{"-10%"}</button>)
+                    };
+                    let add_10_percent_btn = if sponsorship.max_percent.is_some() {
+                        let what = (p + 10 < 100).then_some(p + 10);
+                        html!(<button class="btn btn-outline-success" onclick={set_percent_cb(what)}>{"+10%"}</button>)
+                    } else {
+                        html!(<button class="btn btn-outline-success" disabled={true}>{"+10%"}</button>)
+                    };
+                    let percent_text = match sponsorship.max_percent {
+                        None => String::from("Any share of each purchase"),
+                        Some(p) => format!("Up to {p}% of each purchase"),
+                    };
+
+                    // The categories are entered as a comma-separated list, which replaces the previous categories,
+                    // but keeps the items that the sponsorship is limited to.
+                    let set_scope_cb = |what: Vec<SponsorshipScope>| {
+                        let send = send.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.prevent_default();
+                            send.emit(UserClientMessage::SetSponsorshipScope {
+                                sponsorship_id,
+                                only_for: what.clone(),
+                            })
+                        })
+                    };
+                    let categories_cb = {
+                        let send = send.clone();
+                        let only_for = sponsorship.only_for.clone();
+                        Callback::from(move |s: String| {
+                            let mut new_only_for: Vec<_> = only_for
+                                .iter()
+                                .filter(|scope| matches!(scope, SponsorshipScope::Item(_)))
+                                .cloned()
+                                .collect();
+                            new_only_for.extend(
+                                s.split(',')
+                                    .map(|c| c.trim())
+                                    .filter(|c| !c.is_empty())
+                                    .map(|c| SponsorshipScope::Category(c.to_string())),
+                            );
+                            send.emit(UserClientMessage::SetSponsorshipScope {
+                                sponsorship_id,
+                                only_for: new_only_for,
+                            })
+                        })
+                    };
+                    let categories_text = sponsorship
+                        .only_for
+                        .iter()
+                        .filter(|scope| matches!(scope, SponsorshipScope::Category(_)))
+                        .map(|scope| scope.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let scope_text = if sponsorship.only_for.is_empty() {
+                        String::from("Any item")
+                    } else {
+                        let scopes = sponsorship
+                            .only_for
+                            .iter()
+                            .map(|scope| match (scope, current_item) {
+                                (SponsorshipScope::Item(id), Some(item)) if item.id == *id => {
+                                    item.name.clone()
+                                }
+                                _ => scope.to_string(),
+                            })
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!("Only for: {scopes}")
+                    };
+                    let only_this_item_btn = match current_item {
+                        Some(item)
+                            if !sponsorship
+                                .only_for
+                                .contains(&SponsorshipScope::Item(item.id)) =>
+                        {
+                            let label = if sponsorship.only_for.is_empty() {
+                                "Only for this item"
+                            } else {
+                                "Also for this item"
+                            };
+                            let mut what = sponsorship.only_for.clone();
+                            what.push(SponsorshipScope::Item(item.id));
+                            html!(<button class="btn btn-outline-primary" onclick={set_scope_cb(what)}>{label}</button>)
+                        }
+                        _ => html!(),
+                    };
+
                     let sub_100_btn = if can_sub_100 {
                         html!(<button class="btn btn-danger" onclick={set_cb(b-100)}>{"-100"}</button>)
                     } else {
@@ -203,6 +325,25 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
                                     {"🚫"}  // U+1F6AB NO ENTRY SIGN
                                 </button>
                             </div>
+                            <div class="input-group">
+                                {sub_10_percent_btn}
+                                <span class="input-group-text">{percent_text}</span>
+                                {add_10_percent_btn}
+                                if sponsorship.max_percent.is_some() {
+                                    <button class="btn btn-outline-secondary" onclick={set_percent_cb(None)}>{"No cap"}</button>
+                                }
+                            </div>
+                            <div class="input-group">
+                                <span class="input-group-text">{scope_text}</span>
+                                {only_this_item_btn}
+                                if !sponsorship.only_for.is_empty() {
+                                    <button class="btn btn-outline-secondary" onclick={set_scope_cb(vec![])}>{"Any item"}</button>
+                                }
+                            </div>
+                            <div class="input-group">
+                                <span class="input-group-text">{"Only for categories (comma-separated)"}</span>
+                                <TextInput prefill_value={categories_text} onchange={categories_cb} />
+                            </div>
                         </>
                     )
                 }
@@ -212,7 +353,7 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
                 <>
                     <tr class={classes!(users_to_highlight.iter().any(|i| i == rcpt_id).then_some("table-active"))}>
                         <td>{&rcpt.user_name}</td>
-                        <td><MoneyDisplay money={Sponsorship::resolve_available_balance(*rcpt_id, users, sponsorships)} /></td>
+                        <td><MoneyDisplay money={Sponsorship::resolve_available_balance(*rcpt_id, users, sponsorships, current_item)} /></td>
                     </tr>
                     <tr class={classes!(users_to_highlight.iter().any(|i| i == rcpt_id).then_some("table-active"))}>
                         <td colspan="2">{sponsorship_status}</td>
@@ -236,6 +377,62 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
         )
     };
 
+    // The requests from other members for me to sponsor them, which I can accept or decline.
+    let requests_inbox = {
+        let rows = sponsorships
+            .iter()
+            .filter(|s| s.donor_id == my_account.id && s.status == SponsorshipStatus::Pending)
+            .filter_map(|s| users.iter().find(|u| u.id == s.recepient_id).map(|u| (s, u)))
+            .map(|(s, u)| {
+                let set_status_cb = |status| {
+                    let send = send.clone();
+                    let sponsorship_id = s.id;
+                    Callback::from(move |e: MouseEvent| {
+                        e.prevent_default();
+                        send.emit(UserClientMessage::SetSponsorshipStatus {
+                            sponsorship_id,
+                            status,
+                        })
+                    })
+                };
+                html!(
+                    <tr class={classes!(users_to_highlight.iter().any(|i| *i == u.id).then_some("table-active"))}>
+                        <td>{&u.user_name}</td>
+                        <td><MoneyDisplay money={s.balance_remaining} /></td>
+                        <td>
+                            <button class="btn btn-success me-2" onclick={set_status_cb(SponsorshipStatus::Active)}>{"Accept"}</button>
+                            <button class="btn btn-outline-danger" onclick={set_status_cb(SponsorshipStatus::Retracted)}>{"Decline"}</button>
+                        </td>
+                    </tr>
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // If nobody has asked me for a sponsorship, do not show the inbox at all.
+        if rows.is_empty() {
+            html!()
+        } else {
+            html!(
+                <>
+                    <h2>{"Sponsorship requests:"}</h2>
+                    <table class="table">
+                        <thead>
+                            <tr>
+                                <th scope="col">{"Name"}</th>
+                                <th scope="col">{"Amount asked for"}</th>
+                                <th scope="col">{"Actions"}</th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {for rows}
+                        </tbody>
+                    </table>
+                    <hr />
+                </>
+            )
+        }
+    };
+
     // This stores the value inside the input box.
     let sponsor_code_value = use_state(|| String::new());
     {
@@ -268,6 +465,7 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
 
     html!(
         <>
+        {requests_inbox}
         <h1>{"Your sponsorships:"}</h1>
         {current_sponsorships}
         <hr />
@@ -280,6 +478,7 @@ pub fn SponsorshipEdit(props: &SponsorshipEditProps) -> Html {
         <hr />
         <h3>{"Users accepting sponsorships:"}</h3>
         {could_sponsor}
+        <SponsorshipLogPanel />
         </>
     )
 }
@@ -1,13 +1,13 @@
 use std::rc::Rc;
 
-use common::components::MoneyDisplay;
+use common::components::{MoneyDisplay, NumberInput};
 use communication::{
     auction::state::{Sponsorship, SponsorshipStatus},
-    UserClientMessage,
+    Money, UserClientMessage,
 };
 use yew::prelude::*;
 
-use crate::AppCtx;
+use crate::{components::sponsorship_log::SponsorshipLogPanel, AppCtx};
 
 #[function_component]
 pub fn SponsorshipModeSet() -> Html {
@@ -23,7 +23,7 @@ pub fn SponsorshipModeSet() -> Html {
         .count();
 
     let available_balance =
-        Sponsorship::resolve_available_balance(my_account.id, users, sponsorships);
+        Sponsorship::resolve_available_balance(my_account.id, users, sponsorships, None);
 
     let sponsorship_code_display = if let Some(code) = &my_account.sponsorship_code {
         let refresh_code_cb = {
@@ -64,10 +64,99 @@ pub fn SponsorshipModeSet() -> Html {
         <p>{"Current sponsors: "}{my_sponsors_count}{"; total balance available for bids: "}<MoneyDisplay money={available_balance}/></p>
     );
 
+    // Instead of giving out the code, I can ask a member to sponsor me with some amount,
+    // and they accept or decline the request.
+    let requested_amount = use_state(|| 100);
+    let requested_amount_cb = {
+        let requested_amount = requested_amount.clone();
+        Callback::from(move |s: String| {
+            // If the input cannot be parsed as a money value, ignore it.
+            if let Ok(v) = s.parse() {
+                requested_amount.set(v);
+            }
+        })
+    };
+
+    let my_requests = sponsorships
+        .iter()
+        .filter(|s| s.status == SponsorshipStatus::Pending && s.recepient_id == my_account.id)
+        .filter_map(|s| users.iter().find(|u| u.id == s.donor_id).map(|u| (s, u)))
+        .map(|(s, u)| {
+            let withdraw_cb = {
+                let send = send.clone();
+                let sponsorship_id = s.id;
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(UserClientMessage::SetSponsorshipStatus {
+                        sponsorship_id,
+                        status: SponsorshipStatus::Rejected,
+                    });
+                })
+            };
+            html!(
+                <li>
+                    {"Asked "}{&u.user_name}{" for "}<MoneyDisplay money={s.balance_remaining} />{" "}
+                    <button class="btn btn-sm btn-outline-danger" onclick={withdraw_cb}>{"Withdraw"}</button>
+                </li>
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Members who are not sponsoring me yet, and whom I have not asked already.
+    let could_ask = users
+        .iter()
+        .filter(|u| u.id != my_account.id)
+        .filter(|u| {
+            !sponsorships.iter().any(|s| {
+                s.donor_id == u.id
+                    && s.recepient_id == my_account.id
+                    && matches!(s.status, SponsorshipStatus::Active | SponsorshipStatus::Pending)
+            })
+        })
+        .map(|u| {
+            let ask_cb = {
+                let send = send.clone();
+                let donor_id = u.id;
+                let requested_amount = requested_amount.clone();
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(UserClientMessage::RequestSponsorship {
+                        donor_id,
+                        amount: *requested_amount,
+                    });
+                })
+            };
+            html!(
+                <button class="btn btn-outline-primary me-2 mb-2" onclick={ask_cb}>{"Ask "}{&u.user_name}</button>
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let sponsorship_requests = html!(
+        <>
+            if !my_requests.is_empty() {
+                <p>{"Waiting for an answer:"}</p>
+                <ul>{for my_requests}</ul>
+            }
+            <details>
+                <summary>{"Ask a member for a sponsorship"}</summary>
+                <div class="input-group mb-2">
+                    <span class="input-group-text">{"Amount to ask for"}</span>
+                    <NumberInput prefill_value={requested_amount.to_string()} onchange={requested_amount_cb} min="1" max={Money::MAX.to_string()} step="1" />
+                </div>
+                <div>
+                    {for could_ask}
+                </div>
+            </details>
+        </>
+    );
+
     html!(
         <>
             {sponsorship_code_display}
             {sponsors_data}
+            {sponsorship_requests}
+            <SponsorshipLogPanel />
         </>
     )
 }
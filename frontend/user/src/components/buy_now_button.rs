@@ -0,0 +1,56 @@
+use std::rc::Rc;
+
+use common::components::MoneyDisplay;
+use communication::{
+    auction::state::{AuctionItem, Sponsorship},
+    Money, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+pub struct BuyNowButtonProps {
+    pub item: AuctionItem,
+    pub buy_now_price: Money,
+}
+
+/// Button for buying the item immediately at its buy-now price.
+#[function_component]
+pub fn BuyNowButton(props: &BuyNowButtonProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let users = &ctx.users;
+    let sponsorships = &ctx.sponsorships;
+    let send = &ctx.send;
+
+    let available_balance = Sponsorship::resolve_available_balance(
+        my_account.id,
+        users,
+        sponsorships,
+        Some(&props.item),
+    );
+
+    let buy_now_cb = {
+        let send = send.clone();
+        let item_id = props.item.id;
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            send.emit(UserClientMessage::BuyNow { item_id })
+        })
+    };
+
+    if props.buy_now_price > available_balance {
+        html! {
+            <div class="d-grid mb-3">
+                <button class="btn btn-lg btn-warning-outline" disabled={true}>{"Cannot afford to buy now: "}<MoneyDisplay money={props.buy_now_price} /></button>
+            </div>
+        }
+    } else {
+        html! {
+            <div class="d-grid mb-3">
+                <button class="btn btn-lg btn-warning" onclick={buy_now_cb}>{"Buy now for: "}<MoneyDisplay money={props.buy_now_price} /></button>
+            </div>
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use common::{
-    components::{ItemDisplay, MoneyDisplay, UserAccountCard},
+    components::{ItemDisplay, MoneyDisplay, RevealedBidsTable, UserAccountCard},
     layout::{Container, VerticalStack},
 };
 use communication::{auction::state::AuctionItem, Money, UserAccountData};
@@ -9,12 +9,42 @@ use yew::prelude::*;
 
 use crate::AppCtx;
 
+/// If several units of the item were sold, show who bought the other ones.
+fn other_units_table(other_units: &[(UserAccountData, Money)], my_id: i64) -> Html {
+    if other_units.is_empty() {
+        return html!();
+    }
+    html! {
+        <>
+            <h3>{"Other units sold:"}</h3>
+            <table class="table table-sm">
+                <thead>
+                    <tr>
+                        <th scope="col">{"Buyer"}</th>
+                        <th scope="col">{"Price"}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {for other_units.iter().map(|(buyer, price)| html!(
+                        <tr class={classes!((buyer.id==my_id).then_some("table-active"))}>
+                            <td>{&buyer.user_name}</td>
+                            <td><MoneyDisplay money={price} /></td>
+                        </tr>
+                    ))}
+                </tbody>
+            </table>
+        </>
+    }
+}
+
 #[derive(Properties, PartialEq)]
 pub struct ItemSoldToYouProps {
     pub item: AuctionItem,
     pub sold_for: Money,
     pub confirmation_code: String,
     pub contributions: Vec<(UserAccountData, Money)>,
+    pub revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+    pub other_units: Vec<(UserAccountData, Money)>,
 }
 
 #[function_component]
@@ -47,6 +77,18 @@ pub fn SoldToYou(props: &ItemSoldToYouProps) -> Html {
         </table>
     };
 
+    let revealed_bids = match &props.revealed_bids {
+        Some(bids) => html!(
+            <>
+                <h3>{"Revealed bids:"}</h3>
+                <RevealedBidsTable bids={bids.clone()} highlight_user_id={Some(my_account.id)} />
+            </>
+        ),
+        None => html!(),
+    };
+
+    let other_units = other_units_table(&props.other_units, my_account.id);
+
     html! {
         <Container class="text-bg-success">
             <VerticalStack>
@@ -56,6 +98,8 @@ pub fn SoldToYou(props: &ItemSoldToYouProps) -> Html {
                 <h2 style="font-size: calc(100vw/0.625/4);">{&props.confirmation_code}</h2>
                 // Size calc: https://stackoverflow.com/a/31322756/5936187
                 {contributor_table}
+                {revealed_bids}
+                {other_units}
             </VerticalStack>
         </Container>
     }
@@ -67,6 +111,8 @@ pub struct ItemSoldToSomeoneElseProps {
     pub sold_for: Money,
     pub sold_to: UserAccountData,
     pub contributions: Vec<(UserAccountData, Money)>,
+    pub revealed_bids: Option<Vec<(UserAccountData, Money)>>,
+    pub other_units: Vec<(UserAccountData, Money)>,
 }
 
 #[function_component]
@@ -99,6 +145,18 @@ pub fn SoldToSomeoneElse(props: &ItemSoldToSomeoneElseProps) -> Html {
         </table>
     };
 
+    let revealed_bids = match &props.revealed_bids {
+        Some(bids) => html!(
+            <>
+                <h3>{"Revealed bids:"}</h3>
+                <RevealedBidsTable bids={bids.clone()} highlight_user_id={Some(my_account.id)} />
+            </>
+        ),
+        None => html!(),
+    };
+
+    let other_units = other_units_table(&props.other_units, my_account.id);
+
     if props.contributions.iter().any(|i| i.0.id == my_account.id) {
         html! {
             <Container class="text-bg-warning">
@@ -110,6 +168,8 @@ pub fn SoldToSomeoneElse(props: &ItemSoldToSomeoneElseProps) -> Html {
                     </div>
                     <UserAccountCard account={props.sold_to.clone()} />
                     {contributor_table}
+                    {revealed_bids}
+                    {other_units}
                 </VerticalStack>
             </Container>
         }
@@ -124,6 +184,8 @@ pub fn SoldToSomeoneElse(props: &ItemSoldToSomeoneElseProps) -> Html {
                     </div>
                     <UserAccountCard account={props.sold_to.clone()} />
                     {contributor_table}
+                    {revealed_bids}
+                    {other_units}
                 </VerticalStack>
             </Container>
         }
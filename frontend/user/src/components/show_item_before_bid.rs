@@ -11,8 +11,11 @@ use communication::{
 use yew::prelude::*;
 
 use crate::{
-    components::bidding_screen::{
-        sponsorship_edit::SponsorshipEdit, sponsorship_mode_set::SponsorshipModeSet,
+    components::{
+        bidding_screen::{
+            sponsorship_edit::SponsorshipEdit, sponsorship_mode_set::SponsorshipModeSet,
+        },
+        buy_now_button::BuyNowButton,
     },
     AppCtx,
 };
@@ -70,6 +73,7 @@ pub fn ShowItemBeforeBid(props: &ShowItemProps) -> Html {
             my_account.id,
             users,
             sponsorships,
+            Some(&props.item),
         ) {
             myself if myself == my_account.balance => {
                 // If nobody is sponsoring me, do not show any balance here
@@ -104,9 +108,17 @@ pub fn ShowItemBeforeBid(props: &ShowItemProps) -> Html {
                     html!(
                         <tr>
                             <td>{&u.user_name}</td>
-                            <td><MoneyDisplay money={
-                                s.balance_remaining.min(u.balance)
-                            } /></td>
+                            <td>
+                                <MoneyDisplay money={
+                                    s.balance_remaining.min(u.balance)
+                                } />
+                                if let Some(p) = s.max_percent {
+                                    {format!(" (up to {p}% of each purchase)")}
+                                }
+                                if !s.can_be_used_for(Some(&props.item)) {
+                                    {" (not for this item)"}
+                                }
+                            </td>
                             <td>
                                 <button class="btn btn-warning" onclick={cancel_cb}>
                                     {"🚫"}  // U+1F6AB NO ENTRY SIGN
@@ -144,6 +156,9 @@ pub fn ShowItemBeforeBid(props: &ShowItemProps) -> Html {
                 <ItemDisplay item={props.item.clone()} />
                 <p>{"You have: "}<MoneyDisplay money={my_account.balance} /></p>
                 {maybe_sponsor_balance}
+                if let Some(buy_now_price) = props.item.buy_now_price {
+                    <BuyNowButton item={props.item.clone()} {buy_now_price} />
+                }
                 <SponsorshipModeSet />
                 {sponsor_table}
             </Container>
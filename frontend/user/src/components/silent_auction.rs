@@ -0,0 +1,146 @@
+use std::rc::Rc;
+
+use common::{
+    components::{MoneyDisplay, NumberInput},
+    layout::{Container, VerticalStack},
+};
+use communication::{
+    auction::state::{SilentAuctionItemState, SilentAuctionOutcome, Sponsorship},
+    Money, UserClientMessage,
+};
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+#[derive(Properties, PartialEq)]
+struct SilentAuctionItemProps {
+    item_state: SilentAuctionItemState,
+
+    /// How much the member can still bid on this item,
+    /// after the money committed to their leading bids on the other items.
+    can_use: Money,
+}
+
+#[function_component]
+fn SilentAuctionItemCard(props: &SilentAuctionItemProps) -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let send = &ctx.send;
+    let item_state = &props.item_state;
+    let item = &item_state.item;
+
+    let selected_bid = use_state_eq(|| item_state.minimum_bid);
+
+    let current_bid = match &item_state.current_bid {
+        None => {
+            html!(<p>{"No bids yet. Minimum bid: "}<MoneyDisplay money={item_state.minimum_bid} /></p>)
+        }
+        Some((bidder, amount)) if bidder.id == my_account.id => {
+            html!(<div class="alert alert-success">{"Your bid is the highest: "}<MoneyDisplay money={*amount} /></div>)
+        }
+        Some((bidder, amount)) => {
+            html!(<p>{"Highest bid: "}<MoneyDisplay money={*amount} />{" by "}{&bidder.user_name}</p>)
+        }
+    };
+
+    let body = match &item_state.outcome {
+        Some(SilentAuctionOutcome::Sold(sale)) if sale.sold_to.id == my_account.id => html! {
+            <div class="alert alert-success">
+                {"You bought this for "}<MoneyDisplay money={sale.sold_for} />
+                {". Show this code to the auctioneer: "}<strong>{&sale.confirmation_code}</strong>
+            </div>
+        },
+        Some(SilentAuctionOutcome::Sold(sale)) => html! {
+            <div class="alert alert-secondary">
+                {"Sold to "}{&sale.sold_to.user_name}{" for "}<MoneyDisplay money={sale.sold_for} />
+            </div>
+        },
+        Some(SilentAuctionOutcome::NotSold) => html! {
+            <div class="alert alert-secondary">{"Bidding closed; the item was not sold"}</div>
+        },
+        None => {
+            let bid_input_cb = {
+                let selected_bid = selected_bid.clone();
+                Callback::from(move |s: String| {
+                    // If the input cannot be parsed as a money value, ignore it.
+                    if let Ok(v) = s.parse() {
+                        selected_bid.set(v);
+                    }
+                })
+            };
+
+            let send_cb = {
+                let selected_bid = selected_bid.clone();
+                let send = send.clone();
+                let item_id = item.id;
+                Callback::from(move |e: MouseEvent| {
+                    e.prevent_default();
+                    send.emit(UserClientMessage::BidInSilentAuction {
+                        item_id,
+                        bid_amount: *selected_bid,
+                    })
+                })
+            };
+
+            let send_btn = if *selected_bid > props.can_use {
+                html!(<button class="btn btn-info-outline" disabled={true}>{"Cannot afford!"}</button>)
+            } else if *selected_bid < item_state.minimum_bid {
+                html!(<button class="btn btn-info-outline" disabled={true}>{"Too low!"}</button>)
+            } else {
+                html!(<button class="btn btn-info" onclick={send_cb}>{"Bid: "}<MoneyDisplay money={*selected_bid} /></button>)
+            };
+
+            let seconds_left = item_state.seconds_until_close as u64;
+            html! {
+                <>
+                    {current_bid}
+                    <p>{"Closes in "}{format!("{}:{:02}", seconds_left / 60, seconds_left % 60)}</p>
+                    <div class="input-group">
+                        <NumberInput prefill_value={item_state.minimum_bid.to_string()} onchange={bid_input_cb} min={item_state.minimum_bid.to_string()} max={props.can_use.to_string()} step="1" />
+                        {send_btn}
+                    </div>
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="card mb-3">
+            <div class="card-body">
+                <h5 class="card-title">{&item.name}</h5>
+                {body}
+            </div>
+        </div>
+    }
+}
+
+/// Lists all the items in the silent auction, with a bid input for those that are still open.
+#[function_component]
+pub fn SilentAuctionScreen() -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_account = &ctx.my_account;
+    let silent_auction = &ctx.silent_auction;
+
+    // Sponsorships that are limited to some items are not counted here.
+    let available_balance =
+        Sponsorship::resolve_available_balance(my_account.id, &ctx.users, &ctx.sponsorships, None);
+    let committed = silent_auction.committed_by(my_account.id, None);
+
+    html! {
+        <Container>
+            <VerticalStack>
+                <h1>{"Silent auction"}</h1>
+                <p>
+                    {"Committed to your highest bids: "}<MoneyDisplay money={committed} />
+                    {" / Still available: "}<MoneyDisplay money={available_balance.saturating_sub(committed)} />
+                </p>
+                <p class="form-text">{"If you can no longer pay for all of your highest bids, your most recent ones are withdrawn."}</p>
+                {for silent_auction.items.iter().map(|item_state| {
+                    let available_for_item = Sponsorship::resolve_available_balance(my_account.id, &ctx.users, &ctx.sponsorships, Some(&item_state.item));
+                    let can_use = available_for_item.saturating_sub(silent_auction.committed_by(my_account.id, Some(item_state.item.id)));
+                    html!(<SilentAuctionItemCard item_state={item_state.clone()} {can_use} />)
+                })}
+            </VerticalStack>
+        </Container>
+    }
+}
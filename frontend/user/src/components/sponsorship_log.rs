@@ -0,0 +1,150 @@
+use std::rc::Rc;
+
+use common::components::MoneyDisplay;
+use communication::auction::state::{SponsorshipLogEntry, SponsorshipLogEvent};
+use wasm_bindgen::JsValue;
+use yew::prelude::*;
+
+use crate::AppCtx;
+
+/// The local time of day at which the entry happened.
+fn time_display(entry: &SponsorshipLogEntry) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(entry.happened_at_ms as f64));
+    String::from(date.to_locale_time_string("default"))
+}
+
+/// Describe the entry from the point of view of the given user, who is its donor or recepient.
+fn entry_display(entry: &SponsorshipLogEntry, my_id: i64, ctx: &AppCtx) -> Html {
+    let i_am_donor = entry.donor_id == my_id;
+    let other_id = if i_am_donor {
+        entry.recepient_id
+    } else {
+        entry.donor_id
+    };
+    let other_name = ctx
+        .users
+        .iter()
+        .find(|u| u.id == other_id)
+        .map(|u| u.user_name.clone())
+        .unwrap_or_else(|| String::from("a former member"));
+
+    match (&entry.event, i_am_donor) {
+        (SponsorshipLogEvent::Requested { amount }, true) => {
+            html!(<>{&other_name}{" asked you for "}<MoneyDisplay money={*amount} /></>)
+        }
+        (SponsorshipLogEvent::Requested { amount }, false) => {
+            html!(<>{"You asked "}{&other_name}{" for "}<MoneyDisplay money={*amount} /></>)
+        }
+        (SponsorshipLogEvent::Created { balance }, true) => {
+            html!(<>{"You started sponsoring "}{&other_name}{" with "}<MoneyDisplay money={*balance} /></>)
+        }
+        (SponsorshipLogEvent::Created { balance }, false) => {
+            html!(<>{&other_name}{" started sponsoring you with "}<MoneyDisplay money={*balance} /></>)
+        }
+        (SponsorshipLogEvent::Adjusted { balance }, true) => {
+            html!(<>{"You changed your sponsorship of "}{&other_name}{"; balance: "}<MoneyDisplay money={*balance} /></>)
+        }
+        (SponsorshipLogEvent::Adjusted { balance }, false) => {
+            html!(<>{&other_name}{" changed their sponsorship of you; balance: "}<MoneyDisplay money={*balance} /></>)
+        }
+        (SponsorshipLogEvent::UsedFor { item_name, amount }, true) => {
+            html!(<>{&other_name}{" used "}<MoneyDisplay money={*amount} />{" of your money on: "}<strong>{item_name}</strong></>)
+        }
+        (SponsorshipLogEvent::UsedFor { item_name, amount }, false) => {
+            html!(<>{"You used "}<MoneyDisplay money={*amount} />{" from "}{&other_name}{" on: "}<strong>{item_name}</strong></>)
+        }
+        (SponsorshipLogEvent::Retracted, true) => {
+            html!(<>{"You stopped sponsoring "}{&other_name}</>)
+        }
+        (SponsorshipLogEvent::Retracted, false) => {
+            html!(<>{&other_name}{" stopped sponsoring you"}</>)
+        }
+        (SponsorshipLogEvent::Rejected, true) => {
+            html!(<>{&other_name}{" turned down your sponsorship"}</>)
+        }
+        (SponsorshipLogEvent::Rejected, false) => {
+            html!(<>{"You turned down the sponsorship from "}{&other_name}</>)
+        }
+    }
+}
+
+/// Whether the user should be told about the entry as soon as it happens:
+/// when somebody else did something with a sponsorship they are part of.
+fn is_notable(entry: &SponsorshipLogEntry, my_id: i64) -> bool {
+    let i_am_donor = entry.donor_id == my_id;
+    match entry.event {
+        SponsorshipLogEvent::Requested { .. }
+        | SponsorshipLogEvent::UsedFor { .. }
+        | SponsorshipLogEvent::Rejected => i_am_donor,
+        SponsorshipLogEvent::Created { .. }
+        | SponsorshipLogEvent::Adjusted { .. }
+        | SponsorshipLogEvent::Retracted => !i_am_donor,
+    }
+}
+
+/// The history of the sponsorships that I give and receive, newest first,
+/// so that donors can see where their money went.
+#[function_component]
+pub fn SponsorshipLogPanel() -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_id = ctx.my_account.id;
+
+    if ctx.sponsorship_log.is_empty() {
+        return html!();
+    }
+
+    html! {
+        <details>
+            <summary>{"Sponsorship history ("}{ctx.sponsorship_log.len()}{")"}</summary>
+            <ul class="list-unstyled">
+                {for ctx.sponsorship_log.iter().rev().map(|entry| html!(
+                    <li>
+                        <span class="text-body-secondary me-2">{time_display(entry)}</span>
+                        {entry_display(entry, my_id, &ctx)}
+                    </li>
+                ))}
+            </ul>
+        </details>
+    }
+}
+
+/// Tells the user about what others have done with their sponsorships since they last looked,
+/// such as a sponsored member spending the donor's money on an item.
+#[function_component]
+pub fn SponsorshipNotifications() -> Html {
+    let ctx: Rc<AppCtx> = use_context().expect("no ctx found");
+    let my_id = ctx.my_account.id;
+
+    // Only the entries that arrive after the page is loaded are shown here.
+    let seen_count = use_state(|| ctx.sponsorship_log.len());
+
+    let new_entries: Vec<_> = ctx
+        .sponsorship_log
+        .iter()
+        .skip(*seen_count)
+        .filter(|e| is_notable(e, my_id))
+        .collect();
+    if new_entries.is_empty() {
+        return html!();
+    }
+
+    let dismiss_cb = {
+        let seen_count = seen_count.clone();
+        let log_len = ctx.sponsorship_log.len();
+        Callback::from(move |e: MouseEvent| {
+            e.prevent_default();
+            seen_count.set(log_len);
+        })
+    };
+
+    html! {
+        <div class="alert alert-info">
+            <ul class="mb-2">
+                {for new_entries.iter().map(|entry| html!(
+                    <li>{entry_display(entry, my_id, &ctx)}</li>
+                ))}
+            </ul>
+            <button class="btn btn-sm btn-outline-secondary" onclick={dismiss_cb}>{"Dismiss"}</button>
+        </div>
+    }
+}
@@ -7,11 +7,12 @@ use std::rc::Rc;
 use auction_view::AuctionView;
 use common::layout::{Container, VerticalStack};
 use common::screens::fullscreen_message::FullscreenMsg;
-use communication::auction::state::Sponsorship;
+use communication::auction::state::{SilentAuctionState, Sponsorship, SponsorshipLogEntry};
 use communication::{auction::state::AuctionState, decode, encode, LoginRequest, ServerMessage};
 use communication::{
     UserAccountData, UserAccountDataWithSecrets, UserClientMessage, WithTimestamp,
 };
+use components::sponsorship_log::SponsorshipNotifications;
 use gloo_storage::{SessionStorage, Storage};
 use log::info;
 use serde::Deserialize;
@@ -87,12 +88,17 @@ fn main_app() -> Html {
     let user_account = use_state(|| None);
     let auction_members = use_state(|| WithTimestamp::new_with_zero_time(vec![]));
     let sponsorship_states = use_state(|| None);
+    let sponsorship_log = use_state(|| None);
+    let silent_auction_state =
+        use_state(|| WithTimestamp::new_with_zero_time(SilentAuctionState::default()));
     {
         let ws = ws.clone();
         let user_account = user_account.clone();
         let auction_members = auction_members.clone();
         let auction_state = auction_state.clone();
         let sponsorship_states = sponsorship_states.clone();
+        let sponsorship_log = sponsorship_log.clone();
+        let silent_auction_state = silent_auction_state.clone();
 
         // Receive message by depending on `ws.message_bytes`.
         use_effect_with_deps(
@@ -107,6 +113,10 @@ fn main_app() -> Html {
                             ServerMessage::SponsorshipState(state) => {
                                 sponsorship_states.set(Some(state))
                             }
+                            ServerMessage::SilentAuctionState(state) => {
+                                silent_auction_state.set(state)
+                            }
+                            ServerMessage::SponsorshipLog(log) => sponsorship_log.set(Some(log)),
                         },
                     }
                 }
@@ -154,18 +164,22 @@ fn main_app() -> Html {
     match *ws.ready_state {
         UseWebSocketReadyState::Open => {
             // We need to have the user info before continuing
-            match (&*user_account, &*sponsorship_states) {
-                (Some(acc), Some(sponsors)) => {
+            // The sponsorship history is also needed, so that only the entries that are new are notified.
+            match (&*user_account, &*sponsorship_states, &*sponsorship_log) {
+                (Some(acc), Some(sponsors), Some(sponsorship_log)) => {
                     let ctx = AppCtx {
                         state: auction_state.data.clone(),
                         users: auction_members.data.clone(),
                         my_account: acc.clone(),
                         sponsorships: sponsors.data.clone(),
+                        sponsorship_log: sponsorship_log.data.clone(),
+                        silent_auction: silent_auction_state.data.clone(),
                         send: send_cb.clone(),
                     };
                     let ctx = Rc::new(ctx);
                     html!(
                         <ContextProvider<Rc<AppCtx>> context={ctx}>
+                            <SponsorshipNotifications />
                             <AuctionView />
                         </ContextProvider<Rc<AppCtx>>>
                     )
@@ -187,6 +201,8 @@ pub struct AppCtx {
     users: Vec<UserAccountData>,
     my_account: UserAccountDataWithSecrets,
     sponsorships: Vec<Sponsorship>,
+    sponsorship_log: Vec<SponsorshipLogEntry>,
+    silent_auction: SilentAuctionState,
     send: Callback<UserClientMessage>,
 }
 